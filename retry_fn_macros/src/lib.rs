@@ -0,0 +1,180 @@
+//! # retry_fn_macros
+//!
+//! The `#[retry]` attribute for [retry_fn](https://docs.rs/retry_fn). Don't
+//! depend on this crate directly, enable the `macros` feature of `retry_fn`
+//! and use `retry_fn::attr::retry`
+#![warn(
+    missing_debug_implementations,
+    missing_docs,
+    rust_2018_idioms,
+    unreachable_pub,
+    non_snake_case,
+    non_upper_case_globals
+)]
+
+mod spec;
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{
+    parse::Parser, punctuated::Punctuated, Error, Expr, ExprLit, FnArg, ItemFn, Lit, LitInt,
+    LitStr, MetaNameValue, Pat, Token, Visibility,
+};
+
+/// Retry a function returning a `Result` whenever it returns `Err`, see
+/// `retry_fn::attr::retry`
+#[proc_macro_attribute]
+pub fn retry(args: TokenStream, item: TokenStream) -> TokenStream {
+    let args = Punctuated::<MetaNameValue, Token![,]>::parse_terminated.parse(args);
+    let res = args.and_then(Args::new).and_then(|args| {
+        let func = syn::parse::<ItemFn>(item)?;
+        expand(args, func)
+    });
+    match res {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+/// the arguments of `#[retry(...)]`
+struct Args {
+    strategy: LitStr,
+    max_attempts: Option<LitInt>,
+}
+
+impl Args {
+    fn new(args: Punctuated<MetaNameValue, Token![,]>) -> syn::Result<Self> {
+        let mut strategy = None;
+        let mut max_attempts = None;
+        for arg in args {
+            let lit = match &arg.value {
+                Expr::Lit(ExprLit { lit, .. }) => lit,
+                value => return Err(Error::new_spanned(value, "expected a literal")),
+            };
+            match (arg.path.get_ident().map(|i| i.to_string()).as_deref(), lit) {
+                (Some("strategy"), Lit::Str(s)) => strategy = Some(s.clone()),
+                (Some("max_attempts"), Lit::Int(n)) => max_attempts = Some(n.clone()),
+                (Some("strategy"), _) => {
+                    return Err(Error::new_spanned(lit, "expected a strategy spec string"))
+                }
+                (Some("max_attempts"), _) => {
+                    return Err(Error::new_spanned(lit, "expected an integer"))
+                }
+                _ => {
+                    return Err(Error::new_spanned(
+                        &arg.path,
+                        "expected `strategy` or `max_attempts`",
+                    ))
+                }
+            }
+        }
+        let strategy = strategy.ok_or_else(|| {
+            Error::new(
+                proc_macro2::Span::call_site(),
+                "missing `strategy = \"...\"`",
+            )
+        })?;
+        spec::check(&strategy.value()).map_err(|msg| {
+            Error::new(strategy.span(), format!("invalid strategy spec: {}", msg))
+        })?;
+        Ok(Self {
+            strategy,
+            max_attempts,
+        })
+    }
+}
+
+fn expand(args: Args, func: ItemFn) -> syn::Result<TokenStream2> {
+    let mut names = Vec::new();
+    let mut outer = func.sig.clone();
+    for input in &mut outer.inputs {
+        match input {
+            FnArg::Receiver(recv) => {
+                return Err(Error::new_spanned(
+                    recv,
+                    "#[retry] can only be used on free functions",
+                ))
+            }
+            FnArg::Typed(arg) => match &mut *arg.pat {
+                Pat::Ident(pat) if pat.subpat.is_none() => {
+                    // only the inner function binds the argument mutably
+                    pat.mutability = None;
+                    names.push(pat.ident.clone());
+                }
+                pat => {
+                    return Err(Error::new_spanned(
+                        pat,
+                        "#[retry] needs a plain name for each argument",
+                    ))
+                }
+            },
+        }
+    }
+
+    let inner_name = format_ident!("__retry_{}", func.sig.ident);
+    let mut inner = func.clone();
+    inner.attrs.clear();
+    inner.vis = Visibility::Inherited;
+    inner.sig.ident = inner_name.clone();
+
+    let spec = &args.strategy;
+    // parsed once, the spec was already checked so this can't panic
+    let strategy = quote! {{
+        static SPEC: ::std::sync::OnceLock<::retry_fn::strategy::Spec> =
+            ::std::sync::OnceLock::new();
+        ::core::clone::Clone::clone(SPEC.get_or_init(|| {
+            <::retry_fn::strategy::Spec as ::core::str::FromStr>::from_str(#spec)
+                .expect("strategy checked by #[retry]")
+        }))
+    }};
+    let max_attempts = match &args.max_attempts {
+        Some(n) => quote!(#n),
+        None => quote!(::core::primitive::usize::MAX),
+    };
+    let call = quote!(#inner_name(#(::core::clone::Clone::clone(&#names)),*));
+    let to_retry = quote! {
+        match res {
+            ::core::result::Result::Ok(val) => ::retry_fn::RetryResult::Ok(val),
+            ::core::result::Result::Err(err) => ::retry_fn::RetryResult::RetryWith(err),
+        }
+    };
+    let run = if outer.asyncness.is_some() {
+        quote! {
+            ::retry_fn::__private::retry_times_async(#strategy, #max_attempts, |_| {
+                let fut = #call;
+                async move {
+                    let res = fut.await;
+                    #to_retry
+                }
+            })
+            .await
+        }
+    } else {
+        quote! {
+            ::retry_fn::retry_times(#strategy, #max_attempts, |_| {
+                let res = #call;
+                #to_retry
+            })
+        }
+    };
+
+    let attrs = &func.attrs;
+    let vis = &func.vis;
+    Ok(quote! {
+        #(#attrs)*
+        #vis #outer {
+            #inner
+            #[allow(clippy::clone_on_copy, clippy::redundant_clone)]
+            let res = #run;
+            match res {
+                ::core::result::Result::Ok(val) => ::core::result::Result::Ok(val),
+                ::core::result::Result::Err(::retry_fn::RetryErr::FailedAttempt { err, .. }) => {
+                    ::core::result::Result::Err(err)
+                }
+                // every failed attempt is `RetryWith`, so the error is kept
+                ::core::result::Result::Err(_) => ::core::unreachable!(),
+            }
+        }
+    })
+}
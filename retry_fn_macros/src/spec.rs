@@ -0,0 +1,125 @@
+//! checks a strategy spec while expanding, so a typo is a compile error
+//! rather than a panic on the first call. This follows the grammar of
+//! `retry_fn::strategy::Spec`, keep the two in sync
+
+/// check `s` is a spec `retry_fn::strategy::Spec` will parse
+pub(crate) fn check(s: &str) -> Result<(), String> {
+    let s = s.trim();
+    let (name, args) = match s.find([':', '(']) {
+        Some(i) if s[i..].starts_with('(') => {
+            let args = s[i + 1..]
+                .strip_suffix(')')
+                .ok_or_else(|| format!("missing `)` in `{}`", s))?;
+            (&s[..i], split_args(args))
+        }
+        Some(i) => (&s[..i], split_args(&s[i + 1..])),
+        None => (s, Vec::new()),
+    };
+    let mut args = args.into_iter();
+    let mut arg = |what: &str| {
+        args.next()
+            .filter(|a| !a.is_empty() && !a.contains('='))
+            .ok_or_else(|| format!("`{}` needs {}", name, what))
+    };
+    let name = name.trim();
+    match name {
+        "immediate" => {}
+        "constant" | "exponential" | "decorrelated" => {
+            duration(arg("a delay")?)?;
+        }
+        "random" => {
+            let range = arg("a range")?;
+            let i = range
+                .find("..")
+                .ok_or_else(|| format!("bad range `{}`", range))?;
+            duration(&range[..i])?;
+            duration(&range[i + 2..])?;
+        }
+        other => return Err(format!("unknown kind `{}`", other)),
+    }
+    for opt in args {
+        let (key, val) = match opt.find('=') {
+            Some(i) => (opt[..i].trim(), Some(opt[i + 1..].trim())),
+            None => (opt, None),
+        };
+        match (key, val) {
+            ("max", Some(v)) => duration(v)?,
+            ("retries", Some(v)) => {
+                v.parse::<usize>()
+                    .map_err(|_| format!("bad retries `{}`", v))?;
+            }
+            ("jitter", None) => {}
+            ("base", Some(v)) if name == "exponential" => {
+                v.parse::<u32>().map_err(|_| format!("bad base `{}`", v))?;
+            }
+            _ => return Err(format!("unknown option `{}`", opt)),
+        }
+    }
+    Ok(())
+}
+
+fn split_args(args: &str) -> Vec<&str> {
+    if args.trim().is_empty() {
+        return Vec::new();
+    }
+    args.split(',').map(str::trim).collect()
+}
+
+/// check a duration like `100ms` or `1m30s`
+fn duration(s: &str) -> Result<(), String> {
+    let bad = || format!("bad duration `{}`", s);
+    let mut rest = s.trim();
+    if rest.is_empty() {
+        return Err(bad());
+    }
+    let mut total: u128 = 0;
+    while !rest.is_empty() {
+        let digits = rest.find(|c: char| !c.is_ascii_digit()).ok_or_else(bad)?;
+        let n: u64 = rest[..digits].parse().map_err(|_| bad())?;
+        rest = &rest[digits..];
+        let unit = rest
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let nanos: u128 = match &rest[..unit] {
+            "ns" => 1,
+            "us" => 1_000,
+            "ms" => 1_000_000,
+            "s" => 1_000_000_000,
+            "m" => 60_000_000_000,
+            "h" => 3_600_000_000_000,
+            _ => return Err(bad()),
+        };
+        total = total.checked_add(u128::from(n) * nanos).ok_or_else(bad)?;
+        rest = &rest[unit..];
+    }
+    // the most a `Duration` holds
+    if total > u128::from(u64::MAX) * 1_000_000_000 + 999_999_999 {
+        return Err(bad());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn specs() {
+        assert_eq!(check("exponential:100ms,max=10s"), Ok(()));
+        assert_eq!(check("exponential(100ms, max=10s, base=3)"), Ok(()));
+        assert_eq!(check("random(1s..2s, jitter, retries=4)"), Ok(()));
+        assert_eq!(check("immediate"), Ok(()));
+        assert_eq!(check("fixed:1s"), Err("unknown kind `fixed`".to_owned()));
+        assert_eq!(
+            check("constant(1s"),
+            Err("missing `)` in `constant(1s`".to_owned())
+        );
+        assert_eq!(check("constant:10"), Err("bad duration `10`".to_owned()));
+        assert_eq!(
+            check("constant:1s,base=2"),
+            Err("unknown option `base=2`".to_owned())
+        );
+        assert!(check("constant:1m30").is_err());
+        assert!(check("constant:18446744073709551615h").is_err());
+    }
+}
@@ -0,0 +1,60 @@
+//! retry impls for actix
+//!
+//! Enable the `actix` feature to get access to these functions. They wait
+//! with `actix_rt::time::sleep`, and since an arbiter runs on a single thread
+//! the futures don't need to be `Send`, so they can be used from an actor's
+//! `ResponseFuture` or a handler
+//!
+//! ```rust,no_run
+//! use retry_fn::{actix::retry, strategy::Constant, RetryResult};
+//! # #[actix_rt::main]
+//! # async fn main() {
+//! let res = retry(Constant::from_millis(100).take(5), |op| async move {
+//!     if op.retries >= 2 {
+//!         RetryResult::<_, ()>::Ok(op.retries)
+//!     } else {
+//!         RetryResult::Retry()
+//!     }
+//! })
+//! .await;
+//! assert_eq!(res.ok(), Some(2));
+//! # }
+//! ```
+
+/// actix-rt's timer
+#[derive(Debug, Clone, Copy)]
+pub struct Timer;
+
+impl crate::runtime::Sleeper for Timer {
+    type Sleep = ::actix_rt::time::Sleep;
+
+    fn sleep(dur: Duration) -> Self::Sleep {
+        ::actix_rt::time::sleep(dur)
+    }
+}
+
+retry_impl!(Timer);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::strategy::Constant;
+    use std::rc::Rc;
+
+    #[actix_rt::test]
+    async fn in_arbiter() {
+        let shared = Rc::new(2);
+        let res = retry(Constant::from_millis(1).take(3), |op| {
+            let shared = shared.clone();
+            async move {
+                if op.retries >= *shared {
+                    RetryResult::<_, ()>::Ok(op.total_delay)
+                } else {
+                    RetryResult::Retry()
+                }
+            }
+        })
+        .await;
+        assert!(matches!(res, Ok(d) if d == Duration::from_millis(2)));
+    }
+}
@@ -1,6 +1,20 @@
 //! retry impls for async-std
 
-retry_impl!(async_std::task::sleep);
+use std::pin::Pin;
+
+/// async-std's timer
+#[derive(Debug, Clone, Copy)]
+pub struct Timer;
+
+impl crate::runtime::Sleeper for Timer {
+    type Sleep = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+    fn sleep(dur: Duration) -> Self::Sleep {
+        Box::pin(async_std::task::sleep(dur))
+    }
+}
+
+retry_impl!(Timer);
 
 #[cfg(test)]
 mod test {
@@ -0,0 +1,196 @@
+//! attempts iterator
+//!
+//! `Attempts` runs a retry loop one attempt per call to `next`, without
+//! sleeping. A failed attempt that will be retried yields the delay to wait
+//! before the next one, so the caller decides how to wait and can do other
+//! work in the meantime, or stop early by dropping the iterator
+//!
+//! ```rust,no_run
+//! # use std::thread;
+//! use retry_fn::{attempts, strategy::Constant, Attempt, RetryResult};
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! for attempt in attempts(Constant::from_millis(100).take(3), |op| {
+//!     if op.retries >= 2 {
+//!         RetryResult::Ok(op.retries)
+//!     } else {
+//!         RetryResult::RetryWith("busy")
+//!     }
+//! }) {
+//!     match attempt {
+//!         Attempt::Retry { delay, .. } => {
+//!             // do some other work, then
+//!             thread::sleep(delay);
+//!         }
+//!         Attempt::Ok(n) => assert_eq!(n, 2),
+//!         Attempt::Err(err) => return Err(err.into()),
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+use crate::{exhausted, next_delay, RetryErr, RetryOp, RetryResult, Stopwatch};
+use core::ops::ControlFlow;
+use std::{fmt, iter::FusedIterator, time::Duration};
+
+/// The outcome of one attempt
+#[derive(Debug)]
+pub enum Attempt<T, E> {
+    /// The attempt succeeded, this is the last item
+    Ok(T),
+    /// The attempt failed and won't be retried, this is the last item
+    Err(RetryErr<E>),
+    /// The attempt failed and will be retried once `delay` has been waited
+    Retry {
+        /// the failed attempt
+        op: RetryOp,
+        /// how long to wait before the next attempt
+        delay: Duration,
+        /// the error, if the attempt returned `RetryWith`
+        err: Option<E>,
+    },
+}
+
+/// Define a type for an iterator running one attempt per item, see
+/// [`attempts`]
+pub struct Attempts<I, F>
+where
+    I: Iterator,
+{
+    delays: I,
+    f: F,
+    watch: Stopwatch,
+    count: usize,
+    total_delay: Duration,
+    done: bool,
+}
+
+impl<I, F> fmt::Debug for Attempts<I, F>
+where
+    I: Iterator,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Attempts")
+            .field("count", &self.count)
+            .field("total_delay", &self.total_delay)
+            .field("done", &self.done)
+            .finish()
+    }
+}
+
+/// Run `f` one attempt at a time with the delays of `iter`, see [`Attempts`].
+/// Nothing runs until the first call to `next`, and each delay yielded is
+/// counted in `total_delay` as if it was waited
+pub fn attempts<I, F, T, E>(iter: I, f: F) -> Attempts<I::IntoIter, F>
+where
+    I: IntoIterator<Item = Duration>,
+    F: FnMut(RetryOp) -> RetryResult<T, E>,
+{
+    Attempts {
+        delays: iter.into_iter(),
+        f,
+        watch: Stopwatch::new(),
+        count: 0,
+        total_delay: Duration::from_millis(0),
+        done: false,
+    }
+}
+
+impl<I, F, T, E> Iterator for Attempts<I, F>
+where
+    I: Iterator<Item = Duration>,
+    F: FnMut(RetryOp) -> RetryResult<T, E>,
+{
+    type Item = Attempt<T, E>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        if exhausted(&self.delays) {
+            self.done = true;
+            return Some(Attempt::Err(RetryErr::IteratorEnded {
+                tries: self.count,
+                total_delay: self.total_delay,
+            }));
+        }
+        let op = self.watch.op(self.count, self.total_delay);
+        let err = match (self.f)(op).step(self.count, self.total_delay) {
+            ControlFlow::Continue(err) => err,
+            ControlFlow::Break(res) => {
+                self.done = true;
+                return Some(match res {
+                    Ok(val) => Attempt::Ok(val),
+                    Err(err) => Attempt::Err(err),
+                });
+            }
+        };
+        self.watch.done();
+        match next_delay(&mut self.delays) {
+            Some(delay) => {
+                self.total_delay = self.total_delay.saturating_add(delay);
+                self.count += 1;
+                Some(Attempt::Retry { op, delay, err })
+            }
+            None => {
+                self.done = true;
+                Some(Attempt::Err(RetryErr::ended(
+                    self.count,
+                    self.total_delay,
+                    err,
+                )))
+            }
+        }
+    }
+}
+
+impl<I, F, T, E> FusedIterator for Attempts<I, F>
+where
+    I: Iterator<Item = Duration>,
+    F: FnMut(RetryOp) -> RetryResult<T, E>,
+{
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::strategy::Constant;
+
+    #[test]
+    fn yields_each_attempt() {
+        let mut it = attempts(Constant::from_millis(10).take(3), |op| {
+            if op.retries == 0 {
+                RetryResult::<(), _>::RetryWith("busy")
+            } else {
+                RetryResult::Retry()
+            }
+        });
+        assert!(matches!(
+            it.next(),
+            Some(Attempt::Retry { op, delay, err: Some("busy") })
+                if op.retries == 0 && delay == Duration::from_millis(10)
+        ));
+        assert!(matches!(it.next(), Some(Attempt::Retry { err: None, .. })));
+        assert!(matches!(
+            it.next(),
+            Some(Attempt::Err(RetryErr::IteratorEnded { tries: 2, total_delay }))
+                if total_delay == Duration::from_millis(20)
+        ));
+        assert!(it.next().is_none());
+    }
+
+    #[test]
+    fn stops_on_ok() {
+        let mut calls = 0;
+        let outcomes = attempts(Constant::from_millis(10), |op| {
+            calls += 1;
+            if op.retries == 1 {
+                RetryResult::<_, ()>::Ok(op.total_delay)
+            } else {
+                RetryResult::Retry()
+            }
+        })
+        .count();
+        assert_eq!(outcomes, 2);
+        assert_eq!(calls, 2);
+    }
+}
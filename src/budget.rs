@@ -0,0 +1,162 @@
+//! retry budget
+//!
+//! A `RetryBudget` limits retries to a percentage of requests over a sliding
+//! window, plus a minimum number of retries per second so low traffic can
+//! still retry. Share one budget (ex. in an `Arc`) between every call to a
+//! service, so that when the service is failing, retries stay a fraction of
+//! the traffic instead of multiplying it
+//!
+//! ```rust
+//! # use retry_fn::RetryBudget;
+//! # use std::time::Duration;
+//! let budget = RetryBudget::new(Duration::from_secs(10), 0, 0.5);
+//! budget.deposit();
+//! budget.deposit();
+//! assert!(budget.try_withdraw());
+//! assert!(!budget.try_withdraw());
+//! ```
+use crate::Instant;
+use std::{
+    collections::VecDeque,
+    sync::{Mutex, MutexGuard},
+    time::Duration,
+};
+
+/// A retry budget, shared between calls to limit retries across all of them
+#[derive(Debug)]
+pub struct RetryBudget {
+    ttl: Duration,
+    min_retries: usize,
+    percent_can_retry: f64,
+    state: Mutex<State>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    deposits: VecDeque<Instant>,
+    withdrawals: VecDeque<Instant>,
+}
+
+impl State {
+    fn expire(&mut self, cutoff: Option<Instant>) {
+        if let Some(cutoff) = cutoff {
+            for times in [&mut self.deposits, &mut self.withdrawals].iter_mut() {
+                while times.front().is_some_and(|t| *t <= cutoff) {
+                    times.pop_front();
+                }
+            }
+        }
+    }
+}
+
+impl Default for RetryBudget {
+    /// A budget over 10 seconds, allowing 10 retries per second plus 20% of
+    /// requests
+    fn default() -> Self {
+        Self::new(Duration::from_secs(10), 10, 0.2)
+    }
+}
+
+impl RetryBudget {
+    /// Create a new `RetryBudget` counting requests and retries over the last
+    /// `ttl`. Allows `min_retries_per_sec` retries per second, plus
+    /// `percent_can_retry` (from `0.0` to `1.0`) of requests
+    pub fn new(ttl: Duration, min_retries_per_sec: u32, percent_can_retry: f64) -> Self {
+        let min_retries = (ttl.as_secs_f64() * f64::from(min_retries_per_sec)) as usize;
+        Self {
+            ttl,
+            min_retries,
+            percent_can_retry: percent_can_retry.clamp(0.0, 1.0),
+            state: Mutex::new(State::default()),
+        }
+    }
+
+    /// Record a request
+    pub fn deposit(&self) {
+        let now = Instant::now();
+        let mut state = self.lock(now);
+        state.deposits.push_back(now);
+    }
+
+    /// Record a retry if the budget allows one, returning `false` otherwise
+    pub fn try_withdraw(&self) -> bool {
+        let now = Instant::now();
+        let mut state = self.lock(now);
+        if state.withdrawals.len() < self.allowed(&state) {
+            state.withdrawals.push_back(now);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// The number of retries currently available
+    pub fn balance(&self) -> usize {
+        let state = self.lock(Instant::now());
+        self.allowed(&state).saturating_sub(state.withdrawals.len())
+    }
+
+    fn allowed(&self, state: &State) -> usize {
+        self.min_retries + (state.deposits.len() as f64 * self.percent_can_retry) as usize
+    }
+
+    fn lock(&self, now: Instant) -> MutexGuard<'_, State> {
+        // the state is only counters, so it's still usable if a holder panicked
+        let mut state = self
+            .state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        state.expire(now.checked_sub(self.ttl));
+        state
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::{sync::Arc, thread};
+
+    #[test]
+    fn percent_of_requests() {
+        let budget = RetryBudget::new(Duration::from_secs(60), 0, 0.1);
+        for _ in 0..20 {
+            budget.deposit();
+        }
+        assert_eq!(budget.balance(), 2);
+        assert!(budget.try_withdraw());
+        assert!(budget.try_withdraw());
+        assert!(!budget.try_withdraw());
+    }
+
+    #[test]
+    fn min_retries() {
+        let budget = RetryBudget::new(Duration::from_secs(2), 5, 0.0);
+        assert_eq!(budget.balance(), 10);
+    }
+
+    #[test]
+    fn expires() {
+        let budget = RetryBudget::new(Duration::from_millis(50), 0, 1.0);
+        budget.deposit();
+        assert!(budget.try_withdraw());
+        thread::sleep(Duration::from_millis(60));
+        assert_eq!(budget.balance(), 0);
+        budget.deposit();
+        assert!(budget.try_withdraw());
+    }
+
+    #[test]
+    fn shared() {
+        let budget = Arc::new(RetryBudget::new(Duration::from_secs(60), 0, 1.0));
+        let handles = (0..4)
+            .map(|_| {
+                let budget = budget.clone();
+                thread::spawn(move || budget.deposit())
+            })
+            .collect::<Vec<_>>();
+        for h in handles {
+            h.join().unwrap();
+        }
+        assert_eq!(budget.balance(), 4);
+    }
+}
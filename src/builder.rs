@@ -0,0 +1,491 @@
+//! retry builder
+//!
+//! `RetryBuilder` gathers the options for a retry loop (strategy, attempt
+//! limit, overall deadline, jitter, panic handling and callbacks) instead of
+//! growing the arguments of `retry`. Finish with `run` for a blocking loop,
+//! or `run_async` with the `tokio-runtime` or `async-runtime` feature
+//!
+//! ```rust,no_run
+//! # use std::time::Duration;
+//! use retry_fn::{strategy::ExponentialBackoff, RetryBuilder, RetryResult};
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let res = RetryBuilder::new()
+//!     .strategy(ExponentialBackoff::from_millis(100))
+//!     .max_attempts(5)
+//!     .timeout(Duration::from_secs(10))
+//!     .jitter(true)
+//!     .on_retry(|op, delay| eprintln!("attempt {} failed, waiting {:?}", op.retries, delay))
+//!     .run(|op| {
+//!         if op.retries >= 2 {
+//!             RetryResult::<_, &str>::Ok(op.retries)
+//!         } else {
+//!             RetryResult::Retry()
+//!         }
+//!     });
+//! assert_eq!(res.ok(), Some(2));
+//! Ok(())
+//! # }
+//! ```
+#[cfg(any(feature = "tokio-runtime", feature = "async-runtime"))]
+use crate::runtime::{DefaultSleeper, Sleeper};
+use crate::{
+    exhausted, next_delay, strategy::Immediate, Instant, RetryErr, RetryOp, RetryResult, Stopwatch,
+};
+use core::ops::ControlFlow;
+use std::{
+    fmt,
+    panic::{self, AssertUnwindSafe},
+    thread,
+    time::Duration,
+};
+#[cfg(any(feature = "tokio-runtime", feature = "async-runtime"))]
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+type OnRetry = Box<dyn FnMut(RetryOp, Duration) + Send>;
+
+/// Builder for a retry loop
+pub struct RetryBuilder<S = Immediate> {
+    strategy: S,
+    max_attempts: Option<usize>,
+    deadline: Option<Instant>,
+    timeout: Option<Duration>,
+    jitter: bool,
+    catch_unwind: bool,
+    on_retry: Option<OnRetry>,
+}
+
+impl<S> fmt::Debug for RetryBuilder<S>
+where
+    S: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RetryBuilder")
+            .field("strategy", &self.strategy)
+            .field("max_attempts", &self.max_attempts)
+            .field("deadline", &self.deadline)
+            .field("timeout", &self.timeout)
+            .field("jitter", &self.jitter)
+            .field("catch_unwind", &self.catch_unwind)
+            .field("on_retry", &self.on_retry.is_some())
+            .finish()
+    }
+}
+
+impl Default for RetryBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RetryBuilder {
+    /// Create a new builder, retrying immediately with no limits
+    pub fn new() -> Self {
+        Self {
+            strategy: Immediate,
+            max_attempts: None,
+            deadline: None,
+            timeout: None,
+            jitter: false,
+            catch_unwind: false,
+            on_retry: None,
+        }
+    }
+}
+
+impl<S> RetryBuilder<S> {
+    /// The strategy to take delays from, any `IntoIterator<Item = Duration>`
+    pub fn strategy<T>(self, strategy: T) -> RetryBuilder<T> {
+        RetryBuilder {
+            strategy,
+            max_attempts: self.max_attempts,
+            deadline: self.deadline,
+            timeout: self.timeout,
+            jitter: self.jitter,
+            catch_unwind: self.catch_unwind,
+            on_retry: self.on_retry,
+        }
+    }
+
+    /// Call the function at most `max` times, including the first attempt
+    pub fn max_attempts(mut self, max: usize) -> Self {
+        self.max_attempts = Some(max);
+        self
+    }
+
+    /// Stop retrying once `deadline` has passed, a delay that would sleep
+    /// past it is cut short
+    pub fn deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Stop retrying once `timeout` has elapsed from the start of the run
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// When `true`, apply full jitter to each delay. `false` is the default
+    pub fn jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// When `true`, a panic in an attempt is caught and retried like `Retry`.
+    /// If the strategy ends after a panic, `Panicked` is returned with the
+    /// panic message. `false` is the default
+    pub fn catch_unwind(mut self, catch_unwind: bool) -> Self {
+        self.catch_unwind = catch_unwind;
+        self
+    }
+
+    /// Call `f` before each retry with the failed attempt and the delay
+    /// about to be waited
+    pub fn on_retry<F>(mut self, f: F) -> Self
+    where
+        F: FnMut(RetryOp, Duration) + Send + 'static,
+    {
+        self.on_retry = Some(Box::new(f));
+        self
+    }
+}
+
+impl<S> RetryBuilder<S>
+where
+    S: IntoIterator<Item = Duration>,
+{
+    /// split into the delays to wait, the deadline and the callback
+    fn start(self) -> (Delays<S::IntoIter>, Option<Instant>, Option<OnRetry>) {
+        let deadline = match (self.deadline, self.timeout) {
+            (Some(d), Some(t)) => Some(d.min(Instant::now() + t)),
+            (d, t) => d.or_else(|| t.map(|t| Instant::now() + t)),
+        };
+        let delays = Delays {
+            inner: self.strategy.into_iter(),
+            retries: self.max_attempts,
+            rng: if self.jitter {
+                Some(fastrand::Rng::new())
+            } else {
+                None
+            },
+        };
+        (delays, deadline, self.on_retry)
+    }
+
+    /// Retry `f`, sleeping the thread between attempts
+    ///
+    /// # Returns
+    /// If successful, return `Ok`, otherwise return `Retry` to try again or
+    /// `Err` to exit with an error
+    pub fn run<F, T, E>(self, mut f: F) -> Result<T, RetryErr<E>>
+    where
+        F: FnMut(RetryOp) -> RetryResult<T, E>,
+    {
+        let catch = self.catch_unwind;
+        let (mut delays, deadline, mut on_retry) = self.start();
+        let mut watch = Stopwatch::new();
+        let mut count = 0;
+        let mut total_delay = Duration::from_millis(0);
+        if exhausted(&delays) {
+            return Err(RetryErr::IteratorEnded {
+                tries: 0,
+                total_delay,
+            });
+        }
+        loop {
+            let op = watch.op(count, total_delay);
+            let (res, panicked) = if catch {
+                caught(panic::catch_unwind(AssertUnwindSafe(|| f(op))))
+            } else {
+                (f(op), None)
+            };
+            let err = match res.step(count, total_delay) {
+                ControlFlow::Continue(err) => err,
+                ControlFlow::Break(res) => return res,
+            };
+            watch.done();
+            let dur = match next_delay(&mut delays).and_then(|dur| until(deadline, dur)) {
+                Some(dur) => dur,
+                None => return Err(ended(count, total_delay, err, panicked)),
+            };
+            if let Some(on_retry) = &mut on_retry {
+                on_retry(op, dur);
+            }
+            thread::sleep(dur);
+            total_delay = total_delay.saturating_add(dur);
+            count += 1;
+        }
+    }
+
+    /// Retry the future returned by `f`, using tokio's timer if the
+    /// `tokio-runtime` feature is enabled and async-std's otherwise
+    ///
+    /// # Returns
+    /// If successful, return `Ok`, otherwise return `Retry` to try again or
+    /// `Err` to exit with an error
+    #[cfg(any(feature = "tokio-runtime", feature = "async-runtime"))]
+    pub async fn run_async<F, Fut, T, E>(self, mut f: F) -> Result<T, RetryErr<E>>
+    where
+        F: FnMut(RetryOp) -> Fut,
+        Fut: Future<Output = RetryResult<T, E>>,
+    {
+        let catch = self.catch_unwind;
+        let (mut delays, deadline, mut on_retry) = self.start();
+        let mut watch = Stopwatch::timed(DefaultSleeper::now);
+        let mut count = 0;
+        let mut total_delay = Duration::from_millis(0);
+        if exhausted(&delays) {
+            return Err(RetryErr::IteratorEnded {
+                tries: 0,
+                total_delay,
+            });
+        }
+        loop {
+            let op = watch.op(count, total_delay);
+            let (res, panicked) = if catch {
+                caught(match panic::catch_unwind(AssertUnwindSafe(|| f(op))) {
+                    Ok(fut) => CatchUnwind(Box::pin(fut)).await,
+                    Err(payload) => Err(payload),
+                })
+            } else {
+                (f(op).await, None)
+            };
+            let err = match res.step(count, total_delay) {
+                ControlFlow::Continue(err) => err,
+                ControlFlow::Break(res) => return res,
+            };
+            watch.done();
+            let dur = match next_delay(&mut delays).and_then(|dur| until(deadline, dur)) {
+                Some(dur) => dur,
+                None => return Err(ended(count, total_delay, err, panicked)),
+            };
+            if let Some(on_retry) = &mut on_retry {
+                on_retry(op, dur);
+            }
+            DefaultSleeper::sleep(dur).await;
+            total_delay = total_delay.saturating_add(dur);
+            count += 1;
+        }
+    }
+}
+
+/// the strategy with the builder's attempt limit and jitter applied
+struct Delays<I> {
+    inner: I,
+    retries: Option<usize>,
+    rng: Option<fastrand::Rng>,
+}
+
+impl<I> Iterator for Delays<I>
+where
+    I: Iterator<Item = Duration>,
+{
+    type Item = Duration;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(retries) = &mut self.retries {
+            *retries = retries.checked_sub(1)?;
+        }
+        let mut next = self.inner.next()?;
+        if let Some(rng) = &mut self.rng {
+            next = crate::strategy::rand_between(rng, Duration::from_millis(0), next);
+        }
+        Some(next)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lo, hi) = self.inner.size_hint();
+        match self.retries {
+            Some(left) => (lo.min(left), Some(hi.map_or(left, |hi| hi.min(left)))),
+            None => (lo, hi),
+        }
+    }
+}
+
+/// a panic turned into a retry, along with its message
+fn caught<T, E>(res: thread::Result<RetryResult<T, E>>) -> (RetryResult<T, E>, Option<String>) {
+    match res {
+        Ok(res) => (res, None),
+        Err(payload) => {
+            let msg = if let Some(msg) = payload.downcast_ref::<&str>() {
+                (*msg).to_owned()
+            } else if let Some(msg) = payload.downcast_ref::<String>() {
+                msg.clone()
+            } else {
+                "Box<dyn Any>".to_owned()
+            };
+            (RetryResult::Retry(), Some(msg))
+        }
+    }
+}
+
+/// the error once the delays end, `Panicked` if the last attempt panicked
+fn ended<E>(
+    tries: usize,
+    total_delay: Duration,
+    err: Option<E>,
+    panicked: Option<String>,
+) -> RetryErr<E> {
+    match panicked {
+        Some(msg) => RetryErr::Panicked {
+            tries,
+            total_delay,
+            msg,
+        },
+        None => RetryErr::ended(tries, total_delay, err),
+    }
+}
+
+/// a future that catches a panic while polling the inner one
+#[cfg(any(feature = "tokio-runtime", feature = "async-runtime"))]
+struct CatchUnwind<F>(Pin<Box<F>>);
+
+#[cfg(any(feature = "tokio-runtime", feature = "async-runtime"))]
+impl<F> Future for CatchUnwind<F>
+where
+    F: Future,
+{
+    type Output = thread::Result<F::Output>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let inner = self.0.as_mut();
+        match panic::catch_unwind(AssertUnwindSafe(|| inner.poll(cx))) {
+            Ok(Poll::Pending) => Poll::Pending,
+            Ok(Poll::Ready(out)) => Poll::Ready(Ok(out)),
+            Err(payload) => Poll::Ready(Err(payload)),
+        }
+    }
+}
+
+/// `dur` cut short to end by `deadline`, or `None` once it has passed
+fn until(deadline: Option<Instant>, dur: Duration) -> Option<Duration> {
+    match deadline {
+        Some(deadline) => {
+            let left = deadline.saturating_duration_since(Instant::now());
+            if left == Duration::from_millis(0) {
+                None
+            } else {
+                Some(dur.min(left))
+            }
+        }
+        None => Some(dur),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::strategy::Constant;
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    #[test]
+    fn max_attempts() {
+        let mut calls = 0;
+        let res = RetryBuilder::new()
+            .strategy(Constant::from_millis(1))
+            .max_attempts(3)
+            .run(|_| {
+                calls += 1;
+                RetryResult::<(), &str>::Retry()
+            });
+        assert_eq!(calls, 3);
+        assert!(matches!(res, Err(RetryErr::IteratorEnded { tries: 2, .. })));
+    }
+
+    #[test]
+    fn timeout() {
+        let res = RetryBuilder::new()
+            .strategy(Constant::from_millis(10))
+            .timeout(Duration::from_millis(50))
+            .run(|_| RetryResult::<(), &str>::Retry());
+        match res {
+            Err(RetryErr::IteratorEnded { total_delay, .. }) => {
+                assert!(total_delay <= Duration::from_millis(50))
+            }
+            _ => panic!("expected IteratorEnded"),
+        }
+    }
+
+    #[test]
+    fn jitter_and_callback() {
+        let seen = Arc::new(AtomicUsize::new(0));
+        let counter = seen.clone();
+        let res = RetryBuilder::new()
+            .strategy(Constant::from_millis(10))
+            .jitter(true)
+            .on_retry(move |op, delay| {
+                assert!(delay <= Duration::from_millis(10));
+                counter.fetch_add(1, Ordering::Relaxed);
+                assert_eq!(op.retries + 1, counter.load(Ordering::Relaxed));
+            })
+            .run(|op| {
+                if op.retries >= 3 {
+                    RetryResult::<_, &str>::Ok(op.retries)
+                } else {
+                    RetryResult::Retry()
+                }
+            });
+        assert_eq!(res.ok(), Some(3));
+        assert_eq!(seen.load(Ordering::Relaxed), 3);
+    }
+
+    #[test]
+    fn catch_unwind() {
+        let res = RetryBuilder::new()
+            .strategy(Constant::from_millis(1).take(2))
+            .catch_unwind(true)
+            .run(|op| {
+                if op.retries == 0 {
+                    panic!("corrupt");
+                }
+                RetryResult::<_, &str>::Ok(op.retries)
+            });
+        assert_eq!(res.ok(), Some(1));
+        let res = RetryBuilder::new()
+            .strategy(Constant::from_millis(1).take(2))
+            .catch_unwind(true)
+            .run(|_| -> RetryResult<(), &str> { panic!("corrupt {}", 1) });
+        match res {
+            Err(RetryErr::Panicked { tries: 1, msg, .. }) => assert_eq!(msg, "corrupt 1"),
+            _ => panic!("expected Panicked"),
+        }
+    }
+
+    #[cfg(feature = "tokio-runtime")]
+    #[tokio::test(start_paused = true)]
+    async fn run_async() {
+        let res = RetryBuilder::new()
+            .strategy(Constant::from_secs(1))
+            .max_attempts(4)
+            .run_async(|_| async { RetryResult::<(), &str>::Retry() })
+            .await;
+        assert!(matches!(
+            res,
+            Err(RetryErr::IteratorEnded { tries: 3, total_delay })
+                if total_delay == Duration::from_secs(3)
+        ));
+    }
+
+    #[cfg(feature = "tokio-runtime")]
+    #[tokio::test(start_paused = true)]
+    async fn catch_unwind_async() {
+        let res = RetryBuilder::new()
+            .strategy(Constant::from_secs(1).take(2))
+            .catch_unwind(true)
+            .run_async(|op| async move {
+                if op.retries == 0 {
+                    panic!("corrupt");
+                }
+                RetryResult::<_, &str>::Ok(op.total_delay)
+            })
+            .await;
+        assert_eq!(res.ok(), Some(Duration::from_secs(1)));
+    }
+}
@@ -0,0 +1,65 @@
+//! bulkhead
+//!
+//! A `Bulkhead` caps how many operations of one kind run at the same time.
+//! Share one between every call retrying an operation with
+//! [`retry_with_bulkhead`](crate::retry_with_bulkhead): an attempt only runs
+//! if it gets one of the bulkhead's permits, otherwise it waits for the next
+//! delay of its strategy, so during a partial outage retries queue up behind
+//! the backoff instead of adding load
+//!
+//! ```rust
+//! use retry_fn::Bulkhead;
+//!
+//! let bulkhead = Bulkhead::new(1);
+//! let permit = bulkhead.try_acquire();
+//! assert!(permit.is_some());
+//! assert!(bulkhead.try_acquire().is_none());
+//! drop(permit);
+//! assert_eq!(bulkhead.available(), 1);
+//! ```
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// A limit on concurrent operations, shared between calls
+#[derive(Debug)]
+pub struct Bulkhead {
+    max: usize,
+    running: AtomicUsize,
+}
+
+/// A permit to run an operation, given back to its [`Bulkhead`] when dropped
+#[derive(Debug)]
+pub struct BulkheadPermit<'a> {
+    bulkhead: &'a Bulkhead,
+}
+
+impl Bulkhead {
+    /// Create a new `Bulkhead` letting `max` operations run at once
+    pub fn new(max: usize) -> Self {
+        Self {
+            max,
+            running: AtomicUsize::new(0),
+        }
+    }
+
+    /// Take a permit, or `None` if `max` operations are already running
+    pub fn try_acquire(&self) -> Option<BulkheadPermit<'_>> {
+        self.running
+            .fetch_update(Ordering::Acquire, Ordering::Relaxed, |running| {
+                (running < self.max).then(|| running + 1)
+            })
+            .ok()
+            .map(|_| BulkheadPermit { bulkhead: self })
+    }
+
+    /// number of permits left
+    pub fn available(&self) -> usize {
+        self.max
+            .saturating_sub(self.running.load(Ordering::Relaxed))
+    }
+}
+
+impl Drop for BulkheadPermit<'_> {
+    fn drop(&mut self) {
+        self.bulkhead.running.fetch_sub(1, Ordering::Release);
+    }
+}
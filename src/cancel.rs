@@ -0,0 +1,98 @@
+//! cancellation
+//!
+//! A `CancelToken` stops a retry loop from another thread. Cancelling wakes
+//! a loop that is waiting out a delay right away instead of after the delay,
+//! so shutdown doesn't hang for a whole backoff period
+//!
+//! ```rust
+//! # use std::{thread, time::{Duration, Instant}};
+//! use retry_fn::{retry_cancellable, strategy::Constant, CancelToken, RetryErr, RetryResult};
+//!
+//! let token = CancelToken::new();
+//! let handle = {
+//!     let token = token.clone();
+//!     thread::spawn(move || {
+//!         retry_cancellable(&token, Constant::from_secs(60), |_| {
+//!             RetryResult::<(), &str>::Retry()
+//!         })
+//!     })
+//! };
+//! let start = Instant::now();
+//! token.cancel();
+//! let res = handle.join().unwrap();
+//! assert!(matches!(res, Err(RetryErr::Cancelled { .. })));
+//! assert!(start.elapsed() < Duration::from_secs(60));
+//! ```
+use std::{
+    sync::{Arc, Condvar, Mutex, MutexGuard},
+    time::Duration,
+};
+
+/// A handle to cancel retry loops, shared by cloning
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken {
+    inner: Arc<(Mutex<bool>, Condvar)>,
+}
+
+impl CancelToken {
+    /// Create a new `CancelToken` that isn't cancelled
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancel every loop using this token or a clone of it, waking any that
+    /// are waiting out a delay
+    pub fn cancel(&self) {
+        *self.lock() = true;
+        self.inner.1.notify_all();
+    }
+
+    /// Whether `cancel` has been called
+    pub fn is_cancelled(&self) -> bool {
+        *self.lock()
+    }
+
+    /// Block for `dur` or until cancelled, returning `false` if cancelled
+    pub fn sleep(&self, dur: Duration) -> bool {
+        let guard = self.lock();
+        let (cancelled, _) = self
+            .inner
+            .1
+            .wait_timeout_while(guard, dur, |cancelled| !*cancelled)
+            .unwrap_or_else(|e| e.into_inner());
+        !*cancelled
+    }
+
+    fn lock(&self) -> MutexGuard<'_, bool> {
+        // a bool can't be left half-written, so a poisoned lock is still usable
+        self.inner.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Instant;
+    use std::thread;
+
+    #[test]
+    fn wakes_sleep() {
+        let token = CancelToken::new();
+        let other = token.clone();
+        let handle = thread::spawn(move || other.sleep(Duration::from_secs(60)));
+        thread::sleep(Duration::from_millis(10));
+        let start = Instant::now();
+        token.cancel();
+        assert!(!handle.join().unwrap());
+        assert!(start.elapsed() < Duration::from_secs(1));
+        assert!(token.is_cancelled());
+    }
+
+    #[test]
+    fn sleeps_full_delay() {
+        let token = CancelToken::new();
+        let start = Instant::now();
+        assert!(token.sleep(Duration::from_millis(20)));
+        assert!(start.elapsed() >= Duration::from_millis(20));
+    }
+}
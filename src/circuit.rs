@@ -0,0 +1,172 @@
+//! circuit breaker
+//!
+//! A `CircuitBreaker` stops calling a service that keeps failing. It starts
+//! closed, letting every attempt through. After `failure_threshold` failures
+//! in a row it opens, and attempts are short-circuited without running until
+//! `cool_down` has passed. Then it's half-open: a single probe attempt runs,
+//! closing the breaker if it succeeds and opening it again if it fails.
+//!
+//! Share one breaker (ex. in an `Arc`) between every call to a service and
+//! retry with [`retry_with_breaker`](crate::retry_with_breaker), so retries
+//! back off from a failing service instead of piling onto it
+//!
+//! ```rust
+//! # use std::time::Duration;
+//! use retry_fn::circuit::{CircuitBreaker, CircuitState};
+//!
+//! let breaker = CircuitBreaker::new(2, Duration::from_secs(30));
+//! breaker.record_failure();
+//! assert_eq!(breaker.state(), CircuitState::Closed);
+//! breaker.record_failure();
+//! assert_eq!(breaker.state(), CircuitState::Open);
+//! assert!(!breaker.try_acquire());
+//! ```
+use crate::Instant;
+use std::{
+    sync::{Mutex, MutexGuard},
+    time::Duration,
+};
+
+/// The state of a [`CircuitBreaker`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// attempts run
+    Closed,
+    /// attempts are short-circuited
+    Open,
+    /// a single probe attempt may run
+    HalfOpen,
+}
+
+#[derive(Debug)]
+enum State {
+    Closed {
+        failures: u32,
+    },
+    Open {
+        until: Instant,
+    },
+    /// a probe is running until `until`, after which another may start in
+    /// case the first was never recorded
+    HalfOpen {
+        until: Option<Instant>,
+    },
+}
+
+/// A circuit breaker shared between calls to a service, see the
+/// [module docs](self)
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    failure_threshold: u32,
+    cool_down: Duration,
+    state: Mutex<State>,
+}
+
+impl Default for CircuitBreaker {
+    /// A breaker opening after 5 failures in a row for 30 seconds
+    fn default() -> Self {
+        Self::new(5, Duration::from_secs(30))
+    }
+}
+
+impl CircuitBreaker {
+    /// Create a new closed `CircuitBreaker`, opening after `failure_threshold`
+    /// failures in a row (at least 1) and staying open for `cool_down`
+    pub fn new(failure_threshold: u32, cool_down: Duration) -> Self {
+        Self {
+            failure_threshold: failure_threshold.max(1),
+            cool_down,
+            state: Mutex::new(State::Closed { failures: 0 }),
+        }
+    }
+
+    /// Ask to run an attempt. Returns `false` if it should be
+    /// short-circuited, and once the cool-down has passed returns `true`
+    /// for a single probe
+    pub fn try_acquire(&self) -> bool {
+        let now = Instant::now();
+        let mut state = self.lock();
+        match *state {
+            State::Closed { .. } => true,
+            State::Open { until } | State::HalfOpen { until: Some(until) } if now < until => false,
+            State::Open { .. } | State::HalfOpen { .. } => {
+                *state = State::HalfOpen {
+                    until: Some(now + self.cool_down),
+                };
+                true
+            }
+        }
+    }
+
+    /// Record a successful attempt, closing the breaker
+    pub fn record_success(&self) {
+        *self.lock() = State::Closed { failures: 0 };
+    }
+
+    /// Record a failed attempt, opening the breaker once there have been
+    /// `failure_threshold` in a row or if it was half-open
+    pub fn record_failure(&self) {
+        let mut state = self.lock();
+        let failures = match *state {
+            State::Closed { failures } => failures.saturating_add(1),
+            State::Open { .. } => return,
+            State::HalfOpen { .. } => self.failure_threshold,
+        };
+        *state = if failures >= self.failure_threshold {
+            State::Open {
+                until: Instant::now() + self.cool_down,
+            }
+        } else {
+            State::Closed { failures }
+        };
+    }
+
+    /// The current state. An open breaker whose cool-down has passed is
+    /// `HalfOpen`
+    pub fn state(&self) -> CircuitState {
+        match *self.lock() {
+            State::Closed { .. } => CircuitState::Closed,
+            State::Open { until } if Instant::now() < until => CircuitState::Open,
+            State::Open { .. } | State::HalfOpen { .. } => CircuitState::HalfOpen,
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        // the state is only counters, so it's still usable if a holder panicked
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn opens_and_probes() {
+        let breaker = CircuitBreaker::new(2, Duration::from_millis(20));
+        assert!(breaker.try_acquire());
+        breaker.record_failure();
+        breaker.record_success();
+        breaker.record_failure();
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        breaker.record_failure();
+        assert_eq!(breaker.state(), CircuitState::Open);
+        assert!(!breaker.try_acquire());
+
+        thread::sleep(Duration::from_millis(30));
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+        // one probe at a time
+        assert!(breaker.try_acquire());
+        assert!(!breaker.try_acquire());
+        breaker.record_failure();
+        assert_eq!(breaker.state(), CircuitState::Open);
+
+        thread::sleep(Duration::from_millis(30));
+        assert!(breaker.try_acquire());
+        breaker.record_success();
+        assert_eq!(breaker.state(), CircuitState::Closed);
+    }
+}
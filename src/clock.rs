@@ -0,0 +1,203 @@
+//! clocks
+//!
+//! A `Clock` is where a blocking retry loop gets the time and waits out its
+//! delays. [`SystemClock`] uses `Instant::now` and `thread::sleep`,
+//! [`SpinClock`] busy-waits short delays that `thread::sleep` would overshoot,
+//! and [`ManualClock`] only moves when slept, so retry behavior can be tested
+//! without waiting. Run a loop on a clock with
+//! [`retry_with_clock`](crate::retry_with_clock)
+//!
+//! ```rust
+//! # use std::time::Duration;
+//! use retry_fn::{retry_with_clock, strategy::Constant, ManualClock, RetryResult};
+//!
+//! let mut clock = ManualClock::new();
+//! let res = retry_with_clock(&mut clock, Constant::from_secs(60), |op| {
+//!     if op.retries >= 3 {
+//!         RetryResult::<_, ()>::Ok(op.total_elapsed)
+//!     } else {
+//!         RetryResult::Retry()
+//!     }
+//! });
+//! assert_eq!(res.ok(), Some(Duration::from_secs(180)));
+//! assert_eq!(clock.elapsed(), Duration::from_secs(180));
+//! ```
+use crate::Instant;
+use std::{hint, thread, time::Duration};
+
+/// A source of time for a retry loop
+pub trait Clock {
+    /// The current time
+    fn now(&self) -> Instant;
+
+    /// Wait for `dur`
+    fn sleep(&mut self, dur: Duration);
+}
+
+impl<C> Clock for &mut C
+where
+    C: Clock + ?Sized,
+{
+    fn now(&self) -> Instant {
+        (**self).now()
+    }
+
+    fn sleep(&mut self, dur: Duration) {
+        (**self).sleep(dur)
+    }
+}
+
+/// The real clock, used by [`retry`](crate::retry())
+#[derive(Debug, Copy, Clone, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn sleep(&mut self, dur: Duration) {
+        thread::sleep(dur)
+    }
+}
+
+/// Define a type for a clock that busy-waits delays shorter than a threshold
+/// and sleeps the thread for longer ones. `thread::sleep` can overshoot
+/// delays of a few microseconds many times over, spinning keeps them exact at
+/// the cost of a busy core
+#[derive(Debug, Copy, Clone)]
+pub struct SpinClock {
+    threshold: Duration,
+}
+
+impl SpinClock {
+    /// Create a new `SpinClock` spinning for delays shorter than `threshold`
+    pub const fn new(threshold: Duration) -> Self {
+        Self { threshold }
+    }
+
+    /// create a new type spinning below n microseconds
+    pub const fn from_micros(micros: u64) -> Self {
+        Self::new(Duration::from_micros(micros))
+    }
+}
+
+impl Clock for SpinClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn sleep(&mut self, dur: Duration) {
+        if dur >= self.threshold {
+            thread::sleep(dur);
+            return;
+        }
+        let start = Instant::now();
+        while start.elapsed() < dur {
+            hint::spin_loop();
+        }
+    }
+}
+
+/// Define a type for a clock that only moves forward when slept, returning
+/// right away
+#[derive(Debug, Copy, Clone)]
+pub struct ManualClock {
+    start: Instant,
+    elapsed: Duration,
+}
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ManualClock {
+    /// Create a new `ManualClock` starting at the current time
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            elapsed: Duration::from_millis(0),
+        }
+    }
+
+    /// Move the clock forward by `dur` without sleeping, to stand in for the
+    /// time an attempt takes
+    pub fn advance(&mut self, dur: Duration) {
+        self.elapsed = self.elapsed.saturating_add(dur);
+    }
+
+    /// How far the clock has moved since it was created
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        self.start + self.elapsed
+    }
+
+    fn sleep(&mut self, dur: Duration) {
+        self.advance(dur)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{retry_with_clock, strategy::Constant, RetryErr, RetryResult};
+    use std::cell::RefCell;
+
+    #[test]
+    fn manual_clock_times_attempts() {
+        let clock = RefCell::new(ManualClock::new());
+        let mut seen = Vec::new();
+        let res = retry_with_clock(Shared(&clock), Constant::from_secs(10).take(3), |op| {
+            seen.push((op.total_elapsed, op.last_attempt));
+            // each attempt takes a second
+            clock.borrow_mut().advance(Duration::from_secs(1));
+            RetryResult::<(), ()>::Retry()
+        });
+        assert!(matches!(
+            res,
+            Err(RetryErr::IteratorEnded { tries: 2, total_delay })
+                if total_delay == Duration::from_secs(20)
+        ));
+        assert_eq!(
+            seen,
+            vec![
+                (Duration::from_secs(0), Duration::from_secs(0)),
+                (Duration::from_secs(11), Duration::from_secs(1)),
+                (Duration::from_secs(22), Duration::from_secs(1)),
+            ]
+        );
+        assert_eq!(clock.borrow().elapsed(), Duration::from_secs(23));
+    }
+
+    #[test]
+    fn spin_clock() {
+        let mut clock = SpinClock::from_micros(500);
+        let start = Instant::now();
+        clock.sleep(Duration::from_micros(50));
+        assert!(start.elapsed() >= Duration::from_micros(50));
+        let res = retry_with_clock(clock, Constant::from_micros(20).take(3), |_| {
+            RetryResult::<(), ()>::Retry()
+        });
+        assert!(matches!(res, Err(RetryErr::IteratorEnded { tries: 2, .. })));
+    }
+
+    /// lets the attempts move the clock too
+    struct Shared<'a>(&'a RefCell<ManualClock>);
+
+    impl Clock for Shared<'_> {
+        fn now(&self) -> Instant {
+            self.0.borrow().now()
+        }
+
+        fn sleep(&mut self, dur: Duration) {
+            self.0.borrow_mut().sleep(dur)
+        }
+    }
+}
@@ -0,0 +1,226 @@
+//! compatibility with the `retry` crate
+//!
+//! Mirrors the parts of the [`retry`](https://docs.rs/retry) crate's API that
+//! most code uses, running on this crate's loop and strategies, so a codebase
+//! can move over one call site at a time by swapping `retry::` for
+//! `retry_fn::compat::`
+//!
+//! ```rust,no_run
+//! use retry_fn::compat::{delay::Fixed, retry, OperationResult};
+//! # fn main() {
+//! let mut collection = vec![1, 2, 3].into_iter();
+//! let res = retry(Fixed::from_millis(100), || match collection.next() {
+//!     Some(n) if n == 3 => OperationResult::Ok("n is 3!"),
+//!     Some(_) => OperationResult::Retry("n must be 3!"),
+//!     None => OperationResult::Err("n was never 3!"),
+//! });
+//! assert_eq!(res.ok(), Some("n is 3!"));
+//! # }
+//! ```
+use crate::{RetryErr, RetryResult};
+use std::{error, fmt, iter, time::Duration};
+
+/// The result of an attempt, like `retry::OperationResult`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OperationResult<T, E> {
+    /// succeeded
+    Ok(T),
+    /// failed and should be retried
+    Retry(E),
+    /// failed and shouldn't be retried
+    Err(E),
+}
+
+impl<T, E> OperationResult<T, E> {
+    /// `true` if this is `Ok`
+    pub fn is_ok(&self) -> bool {
+        matches!(self, OperationResult::Ok(_))
+    }
+
+    /// `true` if this is `Retry`
+    pub fn is_retry(&self) -> bool {
+        matches!(self, OperationResult::Retry(_))
+    }
+
+    /// `true` if this is `Err`
+    pub fn is_err(&self) -> bool {
+        matches!(self, OperationResult::Err(_))
+    }
+}
+
+/// `Err` is retried, as in the `retry` crate
+impl<T, E> From<Result<T, E>> for OperationResult<T, E> {
+    fn from(res: Result<T, E>) -> Self {
+        match res {
+            Ok(val) => OperationResult::Ok(val),
+            Err(err) => OperationResult::Retry(err),
+        }
+    }
+}
+
+/// `Retry(E)` is `RetryWith(E)`
+impl<T, E> From<OperationResult<T, E>> for RetryResult<T, E> {
+    fn from(res: OperationResult<T, E>) -> Self {
+        match res {
+            OperationResult::Ok(val) => RetryResult::Ok(val),
+            OperationResult::Retry(err) => RetryResult::RetryWith(err),
+            OperationResult::Err(err) => RetryResult::Err(err),
+        }
+    }
+}
+
+/// The error of a failed retry, like `retry::Error`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error<E> {
+    /// the error of the last attempt
+    pub error: E,
+    /// total delay
+    pub total_delay: Duration,
+    /// number of attempts, including the first
+    pub tries: u64,
+}
+
+impl<E> fmt::Display for Error<E>
+where
+    E: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.error.fmt(f)
+    }
+}
+
+impl<E> error::Error for Error<E> where E: error::Error {}
+
+/// Retry `operation` with the delays of `iterable`, like `retry::retry`
+pub fn retry<I, O, R, E, OR>(iterable: I, mut operation: O) -> Result<R, Error<E>>
+where
+    I: IntoIterator<Item = Duration>,
+    O: FnMut() -> OR,
+    OR: Into<OperationResult<R, E>>,
+{
+    retry_with_index(iterable, |_| operation())
+}
+
+/// Retry `operation` with the delays of `iterable`, passing it the number of
+/// the attempt starting at 1, like `retry::retry_with_index`
+pub fn retry_with_index<I, O, R, E, OR>(iterable: I, mut operation: O) -> Result<R, Error<E>>
+where
+    I: IntoIterator<Item = Duration>,
+    O: FnMut(u64) -> OR,
+    OR: Into<OperationResult<R, E>>,
+{
+    // the `retry` crate tries once more than there are delays, the last
+    // delay is never waited
+    let delays = iterable
+        .into_iter()
+        .chain(iter::once(Duration::from_millis(0)));
+    let res = crate::retry(delays, |op| {
+        RetryResult::from(operation(op.retries as u64 + 1).into())
+    });
+    match res {
+        Ok(val) => Ok(val),
+        Err(RetryErr::FailedAttempt {
+            tries,
+            total_delay,
+            err,
+        }) => Err(Error {
+            error: err,
+            total_delay,
+            tries: tries as u64 + 1,
+        }),
+        // every failed attempt carries an error, so it's always kept
+        Err(_) => unreachable!(),
+    }
+}
+
+/// delays named like `retry::delay`
+pub mod delay {
+    use crate::strategy::{rand_between, ExponentialBackoff};
+    use std::{convert::TryFrom, time::Duration};
+
+    pub use crate::strategy::{Constant as Fixed, Immediate as NoDelay};
+
+    /// Delays growing by multiplying with the starting delay, like
+    /// `retry::delay::Exponential`. From 10ms the series is 10ms, 100ms, 1s
+    #[derive(Debug, Copy, Clone)]
+    pub struct Exponential(ExponentialBackoff);
+
+    impl Exponential {
+        /// create a new type using `base` milliseconds as the first delay
+        /// and the factor
+        pub fn from_millis(base: u64) -> Self {
+            let factor = u32::try_from(base).unwrap_or(u32::MAX);
+            Self(
+                ExponentialBackoff::from_millis(base)
+                    .base(factor)
+                    .include_first(true),
+            )
+        }
+    }
+
+    impl Iterator for Exponential {
+        type Item = Duration;
+
+        fn next(&mut self) -> Option<Self::Item> {
+            self.0.next()
+        }
+    }
+
+    /// A random duration between zero and `duration`, like
+    /// `retry::delay::jitter`
+    pub fn jitter(duration: Duration) -> Duration {
+        rand_between(
+            &mut fastrand::Rng::new(),
+            Duration::from_millis(0),
+            duration,
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{delay::*, *};
+
+    #[test]
+    fn retry_until_ok() {
+        let mut n = 0;
+        let res = retry(Fixed::from_millis(1), || {
+            n += 1;
+            if n < 3 {
+                Err("busy")
+            } else {
+                Ok(n)
+            }
+        });
+        assert_eq!(res, Ok(3));
+    }
+
+    #[test]
+    fn error_of_last_attempt() {
+        let res = retry_with_index(NoDelay.take(2), OperationResult::<(), _>::Retry);
+        assert_eq!(
+            res,
+            Err(Error {
+                error: 3,
+                total_delay: Duration::from_millis(0),
+                tries: 3,
+            })
+        );
+        let res = retry(NoDelay, || OperationResult::<(), _>::Err("fatal"));
+        assert!(matches!(res, Err(Error { tries: 1, .. })));
+    }
+
+    #[test]
+    fn delays() {
+        let s = Exponential::from_millis(10).take(3).collect::<Vec<_>>();
+        assert_eq!(
+            s,
+            vec![
+                Duration::from_millis(10),
+                Duration::from_millis(100),
+                Duration::from_millis(1000)
+            ]
+        );
+        assert!(jitter(Duration::from_millis(10)) <= Duration::from_millis(10));
+    }
+}
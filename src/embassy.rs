@@ -0,0 +1,209 @@
+//! retry impls for embassy
+//!
+//! Enable the `embassy` feature to get access to these functions. They wait
+//! with `embassy_time::Timer` and don't need `std`, so the same strategies can
+//! be used in firmware. The application provides embassy's time driver as
+//! usual
+//!
+//! ```rust,no_run
+//! use retry_fn::{embassy::retry, strategy::ExponentialBackoff, RetryResult};
+//! # async fn read_sensor() -> Result<u16, ()> { Ok(0) }
+//! # async fn run() {
+//! let res = retry(ExponentialBackoff::from_millis(10).take(5), |_| async {
+//!     match read_sensor().await {
+//!         Ok(value) => RetryResult::Ok(value),
+//!         Err(()) => RetryResult::<_, ()>::Retry(),
+//!     }
+//! })
+//! .await;
+//! # }
+//! ```
+use crate::{
+    exhausted, next_delay,
+    strategy::{MaxRetries, RetryStrategy},
+    RetryErr, RetryOp, RetryResult,
+};
+use core::{convert::TryFrom, future::Future, ops::ControlFlow, time::Duration};
+use embassy_time::{Instant, Timer};
+
+/// Retry a future based on an iterator over Duration, waiting between
+/// attempts with `embassy_time::Timer`
+///
+/// # Returns
+/// If successful, return `Ok`, otherwise return `Retry` to try again or `Err`
+/// to exit with an error
+pub async fn retry<I, F, Fut, T, E>(iter: I, mut f: F) -> Result<T, RetryErr<E>>
+where
+    I: IntoIterator<Item = Duration>,
+    F: FnMut(RetryOp) -> Fut,
+    Fut: Future<Output = RetryResult<T, E>>,
+{
+    let mut watch = Watch::new();
+    let mut count = 0;
+    let mut total_delay = Duration::from_millis(0);
+    let mut delays = iter.into_iter();
+    if exhausted(&delays) {
+        return Err(RetryErr::IteratorEnded {
+            tries: 0,
+            total_delay,
+        });
+    }
+    loop {
+        let err = match f(watch.op(count, total_delay))
+            .await
+            .step(count, total_delay)
+        {
+            ControlFlow::Continue(err) => err,
+            ControlFlow::Break(res) => return res,
+        };
+        watch.done();
+        let dur = match next_delay(&mut delays) {
+            Some(dur) => dur,
+            None => return Err(RetryErr::ended(count, total_delay, err)),
+        };
+        sleep(dur).await;
+        total_delay = total_delay.saturating_add(dur);
+        count += 1;
+    }
+}
+
+/// Retry a future based on an iterator over Duration, calling `f` at most
+/// `times` times however long the iterator is
+///
+/// # Returns
+/// If successful, return `Ok`, otherwise return `Retry` to try again or `Err`
+/// to exit with an error
+pub async fn retry_times<I, F, Fut, T, E>(iter: I, times: usize, f: F) -> Result<T, RetryErr<E>>
+where
+    I: IntoIterator<Item = Duration>,
+    F: FnMut(RetryOp) -> Fut,
+    Fut: Future<Output = RetryResult<T, E>>,
+{
+    retry(MaxRetries::new(iter.into_iter(), times), f).await
+}
+
+/// Retry a future, using the error of each failed attempt to pick the next
+/// delay. Return `RetryWith` from the future to pass the error to the
+/// strategy
+///
+/// # Returns
+/// If successful, return `Ok`, otherwise return `Retry` or `RetryWith` to try
+/// again or `Err` to exit with an error. When the strategy ends after a
+/// `RetryWith`, that error is returned as a `FailedAttempt`
+pub async fn retry_strategy<S, F, Fut, T, E>(mut strategy: S, mut f: F) -> Result<T, RetryErr<E>>
+where
+    S: RetryStrategy<E>,
+    F: FnMut(RetryOp) -> Fut,
+    Fut: Future<Output = RetryResult<T, E>>,
+{
+    let mut watch = Watch::new();
+    let mut count = 0;
+    let mut total_delay = Duration::from_millis(0);
+    if strategy.remaining() == Some(0) {
+        return Err(RetryErr::IteratorEnded {
+            tries: 0,
+            total_delay,
+        });
+    }
+    loop {
+        let err = match f(watch.op(count, total_delay))
+            .await
+            .step(count, total_delay)
+        {
+            ControlFlow::Continue(err) => err,
+            ControlFlow::Break(res) => return res,
+        };
+        watch.done();
+        let dur = match strategy
+            .delay(count, err.as_ref())
+            .filter(|_| strategy.remaining() != Some(0))
+        {
+            Some(dur) => dur,
+            None => return Err(RetryErr::ended(count, total_delay, err)),
+        };
+        sleep(dur).await;
+        total_delay = total_delay.saturating_add(dur);
+        count += 1;
+    }
+}
+
+/// wait `dur` on embassy's timer, saturating at the longest it can represent
+async fn sleep(dur: Duration) {
+    let dur = embassy_time::Duration::try_from(dur).unwrap_or(embassy_time::Duration::MAX);
+    Timer::after(dur).await
+}
+
+/// times the attempts like `Stopwatch`, with embassy's clock
+struct Watch {
+    start: Instant,
+    attempt: Instant,
+    last_attempt: Duration,
+    #[cfg(feature = "std")]
+    started: crate::Instant,
+}
+
+impl Watch {
+    fn new() -> Self {
+        let now = Instant::now();
+        Self {
+            start: now,
+            attempt: now,
+            last_attempt: Duration::from_millis(0),
+            #[cfg(feature = "std")]
+            started: crate::Instant::now(),
+        }
+    }
+
+    fn op(&mut self, retries: usize, total_delay: Duration) -> RetryOp {
+        self.attempt = Instant::now();
+        RetryOp {
+            retries,
+            total_delay,
+            total_elapsed: self.attempt.saturating_duration_since(self.start).into(),
+            last_attempt: self.last_attempt,
+            #[cfg(feature = "std")]
+            started: self.started,
+        }
+    }
+
+    fn done(&mut self) {
+        self.last_attempt = Instant::now()
+            .saturating_duration_since(self.attempt)
+            .into();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::strategy::Constant;
+
+    #[tokio::test]
+    async fn waits_on_embassy_timer() {
+        let res = retry(Constant::from_millis(5).take(3), |op| async move {
+            if op.retries >= 2 {
+                RetryResult::<_, ()>::Ok(op)
+            } else {
+                RetryResult::Retry()
+            }
+        })
+        .await;
+        assert!(matches!(res, Ok(op) if op.total_elapsed >= Duration::from_millis(10)));
+    }
+
+    #[tokio::test]
+    async fn strategy_ends() {
+        let res = retry_times(Constant::from_millis(1), 2, |op| async move {
+            RetryResult::<(), _>::RetryWith(op.retries)
+        })
+        .await;
+        assert!(matches!(
+            res,
+            Err(RetryErr::FailedAttempt {
+                tries: 1,
+                err: 1,
+                ..
+            })
+        ));
+    }
+}
@@ -0,0 +1,52 @@
+//! retry impls for futures-timer
+//!
+//! Enable the `futures-timer` feature to get access to these functions. The
+//! timer runs on its own thread, so they work on any executor
+//!
+//! ```rust,no_run
+//! use retry_fn::{futures_timer::retry, strategy::Constant, RetryResult};
+//! # async fn run() {
+//! let res = retry(Constant::from_millis(100).take(5), |op| async move {
+//!     if op.retries >= 2 {
+//!         RetryResult::<_, ()>::Ok(op.retries)
+//!     } else {
+//!         RetryResult::Retry()
+//!     }
+//! })
+//! .await;
+//! assert_eq!(res.ok(), Some(2));
+//! # }
+//! ```
+
+/// futures-timer's timer
+#[derive(Debug, Clone, Copy)]
+pub struct Timer;
+
+impl crate::runtime::Sleeper for Timer {
+    type Sleep = ::futures_timer::Delay;
+
+    fn sleep(dur: Duration) -> Self::Sleep {
+        ::futures_timer::Delay::new(dur)
+    }
+}
+
+retry_impl!(Timer);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::strategy::Constant;
+
+    #[tokio::test]
+    async fn waits_between_attempts() {
+        let res = retry(Constant::from_millis(5).take(3), |op| async move {
+            if op.retries >= 2 {
+                RetryResult::<_, ()>::Ok(op.total_elapsed)
+            } else {
+                RetryResult::Retry()
+            }
+        })
+        .await;
+        assert!(matches!(res, Ok(elapsed) if elapsed >= Duration::from_millis(10)));
+    }
+}
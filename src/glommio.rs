@@ -0,0 +1,65 @@
+//! retry impls for glommio
+//!
+//! Enable the `glommio` feature to get access to these functions. Since
+//! glommio runs one executor per core the futures don't need to be `Send`
+//!
+//! ```rust,no_run
+//! use retry_fn::{glommio::retry, strategy::Constant, RetryResult};
+//! use std::rc::Rc;
+//! # async fn run() {
+//! let shared = Rc::new(3);
+//! let res = retry(Constant::from_millis(100).take(5), |op| {
+//!     let shared = shared.clone();
+//!     async move {
+//!         if op.retries >= *shared {
+//!             RetryResult::<_, ()>::Ok(op.retries)
+//!         } else {
+//!             RetryResult::Retry()
+//!         }
+//!     }
+//! })
+//! .await;
+//! assert_eq!(res.ok(), Some(3));
+//! # }
+//! ```
+use std::pin::Pin;
+
+/// glommio's timer
+#[derive(Debug, Clone, Copy)]
+pub struct Timer;
+
+impl crate::runtime::Sleeper for Timer {
+    type Sleep = Pin<Box<dyn Future<Output = ()>>>;
+
+    fn sleep(dur: Duration) -> Self::Sleep {
+        Box::pin(::glommio::timer::sleep(dur))
+    }
+}
+
+retry_impl!(Timer);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::strategy::Constant;
+    use std::rc::Rc;
+
+    #[test]
+    fn not_send() {
+        let res = ::glommio::LocalExecutor::default().run(async {
+            let shared = Rc::new(2);
+            retry(Constant::from_millis(1).take(3), |op| {
+                let shared = shared.clone();
+                async move {
+                    if op.retries >= *shared {
+                        RetryResult::<_, ()>::Ok(op.total_delay)
+                    } else {
+                        RetryResult::Retry()
+                    }
+                }
+            })
+            .await
+        });
+        assert!(matches!(res, Ok(d) if d == Duration::from_millis(2)));
+    }
+}
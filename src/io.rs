@@ -0,0 +1,63 @@
+//! io errors
+//!
+//! Which `std::io` errors are worth retrying. [`is_transient`] can be passed
+//! straight to [`retry_if`](crate::retry_if) as the predicate, and
+//! `io::Error`'s [`Retryable`](crate::Retryable) impl uses the same list, so
+//! [`retry_transient`](crate::retry_transient) works on io results as is
+//!
+//! ```rust,no_run
+//! # use std::fs;
+//! use retry_fn::{io::is_transient, retry_if, strategy::Constant};
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let text = retry_if(
+//!     Constant::from_millis(100).take(3),
+//!     |_| fs::read_to_string("status"),
+//!     is_transient,
+//! )?;
+//! # Ok(())
+//! # }
+//! ```
+use std::io::{Error, ErrorKind};
+
+/// The error kinds that usually clear up on their own: interruptions,
+/// timeouts and refused, reset or dropped connections
+pub const TRANSIENT_KINDS: &[ErrorKind] = &[
+    ErrorKind::Interrupted,
+    ErrorKind::WouldBlock,
+    ErrorKind::TimedOut,
+    ErrorKind::ConnectionRefused,
+    ErrorKind::ConnectionReset,
+    ErrorKind::ConnectionAborted,
+    ErrorKind::BrokenPipe,
+];
+
+/// Whether `err` is one of the [`TRANSIENT_KINDS`]
+pub fn is_transient(err: &Error) -> bool {
+    TRANSIENT_KINDS.contains(&err.kind())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{retry_if, strategy::Constant, RetryErr};
+
+    #[test]
+    fn classifies() {
+        assert!(is_transient(&Error::from(ErrorKind::TimedOut)));
+        assert!(!is_transient(&Error::from(ErrorKind::NotFound)));
+        let mut calls = 0;
+        let res = retry_if(
+            Constant::from_millis(1).take(5),
+            |_| {
+                calls += 1;
+                Err::<(), _>(Error::from(if calls < 3 {
+                    ErrorKind::ConnectionReset
+                } else {
+                    ErrorKind::PermissionDenied
+                }))
+            },
+            is_transient,
+        );
+        assert!(matches!(res, Err(RetryErr::FailedAttempt { tries: 2, .. })));
+    }
+}
@@ -1,10 +1,20 @@
 #![doc(html_root_url = "https://docs.rs/retry_fn/0.3.0")]
+#![cfg_attr(not(feature = "std"), no_std)]
 //! # retry
 //!
 //! Function for executing retry either as a closure with a std-based sleep
 //! (`thread::sleep`) or using either of the most popular async runtimes. See
 //! `tokio` or `async-std` module for futures-aware versions.
 //!
+//! ## no_std
+//!
+//! The `std` feature is on by default. Without it, the crate is `no_std` and
+//! provides `RetryResult`, `RetryErr`, `RetryOp` and the strategies that
+//! don't need a clock, so they can be driven by your own retry loop. Jittered
+//! strategies are built with `with_rng` to supply the randomness. The `alloc`
+//! feature adds the strategies that allocate, and `embassy` adds an async
+//! retry loop on embassy's timer for firmware.
+//!
 //! ## Sync Example
 //!
 //! ```rust,no_run
@@ -28,6 +38,16 @@
 //! Ok(())
 //! # }
 //! ```
+//!
+//! ## Delays and attempts
+//!
+//! A strategy of `n` delays makes up to `n` attempts, and an empty one never
+//! calls the function. A delay is only taken from the strategy once an
+//! attempt fails, and it isn't slept if the strategy's `size_hint` shows it
+//! was the last, since no attempt would follow. A strategy whose length
+//! isn't known is asked again after every failure and ends the loop by
+//! returning `None`. The `tries` of a [`RetryErr`] is the number of retries,
+//! one less than the number of attempts
 #![warn(
     missing_debug_implementations,
     missing_docs,
@@ -44,50 +64,588 @@
     attr(deny(warnings, rust_2018_idioms), allow(dead_code, unused_variables))
 ))]
 
-use crate::strategy::Immediate;
+#[cfg(feature = "alloc")]
+extern crate alloc;
 
-#[cfg(any(feature = "tokio-runtime", feature = "async-runtime"))]
+#[cfg(any(
+    feature = "tokio-runtime",
+    feature = "async-runtime",
+    feature = "futures-timer",
+    feature = "wasm",
+    feature = "glommio",
+    feature = "monoio",
+    feature = "actix"
+))]
 #[macro_use]
 mod macros;
+#[cfg(feature = "std")]
+mod attempts;
+#[cfg(feature = "std")]
+mod budget;
+#[cfg(feature = "std")]
+mod builder;
+#[cfg(feature = "std")]
+mod bulkhead;
+#[cfg(feature = "std")]
+mod cancel;
+#[cfg(feature = "std")]
+pub mod circuit;
+#[cfg(feature = "std")]
+mod clock;
+#[cfg(feature = "std")]
+pub mod compat;
+#[cfg(feature = "std")]
+pub mod io;
+#[cfg(feature = "std")]
+pub mod lock;
+#[cfg(feature = "std")]
+pub mod net;
+mod operation;
+#[cfg(feature = "std")]
+mod policy;
+#[cfg(feature = "std")]
+mod retry_iter;
+mod retryable;
+#[cfg(feature = "std")]
+mod retryable_fn;
+#[cfg(feature = "std")]
+pub mod runtime;
+mod state;
 pub mod strategy;
 
 #[cfg(feature = "tokio-runtime")]
 pub mod tokio;
 
+#[cfg(feature = "queue")]
+pub mod queue;
+
 #[cfg(feature = "async-runtime")]
 pub mod async_std;
 
-use std::{error::Error, fmt, thread, time::Duration};
+#[cfg(feature = "futures-timer")]
+pub mod futures_timer;
+
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+#[cfg(feature = "embassy")]
+pub mod embassy;
+
+#[cfg(feature = "glommio")]
+pub mod glommio;
+
+#[cfg(feature = "monoio")]
+pub mod monoio;
+
+#[cfg(feature = "actix")]
+pub mod actix;
+
+#[cfg(feature = "tower")]
+pub mod tower;
+
+/// attribute macros, enabled with the `macros` feature
+#[cfg(feature = "macros")]
+pub mod attr {
+    /// Retry a function whenever it returns `Err`, leaving its body and
+    /// signature as they are. `strategy` is a [`Spec`](crate::strategy::Spec)
+    /// string and `max_attempts` optionally limits the number of calls.
+    /// Once the strategy ends the last error is returned. The spec is
+    /// checked when the macro expands, so an invalid one fails to compile.
+    ///
+    /// The function must return a `Result`, and since it's called again for
+    /// every attempt its arguments are cloned each time. Async functions need
+    /// the `tokio-runtime` or `async-runtime` feature. Methods aren't
+    /// supported.
+    ///
+    /// This shares its name with the `retry!` macro, so import it from here
+    /// rather than alongside `retry_fn::retry`
+    ///
+    /// ```rust,no_run
+    /// # use std::{fs, io};
+    /// use retry_fn::attr::retry;
+    ///
+    /// #[retry(strategy = "exponential(100ms, max=10s)", max_attempts = 5)]
+    /// fn read_status(path: &str) -> io::Result<String> {
+    ///     fs::read_to_string(path)
+    /// }
+    /// # fn main() -> io::Result<()> {
+    /// let status = read_status("status")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub use retry_fn_macros::retry;
+}
+
+/// used by the code `#[retry]` generates
+#[cfg(feature = "macros")]
+#[doc(hidden)]
+pub mod __private {
+    #[cfg(all(feature = "async-runtime", not(feature = "tokio-runtime")))]
+    pub use crate::async_std::retry_times as retry_times_async;
+    #[cfg(feature = "tokio-runtime")]
+    pub use crate::tokio::retry_times as retry_times_async;
+}
+
+#[cfg(feature = "std")]
+pub use attempts::{attempts, Attempt, Attempts};
+#[cfg(feature = "std")]
+pub use budget::RetryBudget;
+#[cfg(feature = "std")]
+pub use builder::RetryBuilder;
+#[cfg(feature = "std")]
+pub use bulkhead::{Bulkhead, BulkheadPermit};
+#[cfg(feature = "std")]
+pub use cancel::CancelToken;
+#[cfg(feature = "std")]
+pub use clock::{Clock, ManualClock, SpinClock, SystemClock};
+pub use operation::Operation;
+#[cfg(feature = "std")]
+pub use policy::{default_policy, set_default_policy, DefaultPolicy, RetryPolicy};
+#[cfg(feature = "std")]
+pub use retry_iter::{retry_iter, RetryIter};
+pub use retryable::Retryable;
+#[cfg(any(feature = "tokio-runtime", feature = "async-runtime"))]
+pub use retryable_fn::AsyncRetryExt;
+#[cfg(feature = "std")]
+pub use retryable_fn::RetryableFn;
+pub use state::{Action, RetrySnapshot, RetryState};
+
+#[cfg(feature = "std")]
+use crate::strategy::{Constant, Immediate, MaxRetries, RetryStrategy};
+use core::{fmt, ops::ControlFlow, time::Duration};
+/// `web-time`'s `Instant` with the `wasm` feature, since `std`'s panics in
+/// the browser. It's `std`'s on every other target
+#[cfg(all(feature = "std", not(feature = "wasm")))]
+pub(crate) use std::time::Instant;
+#[cfg(feature = "std")]
+use std::{cell::Cell, convert::Infallible, error::Error};
+#[cfg(feature = "wasm")]
+pub(crate) use web_time::Instant;
 
-/// `RetryOp` gives some inspection into the current state of retries
+/// `RetryOp` gives some inspection into the current state of retries. The
+/// next delay isn't known until an attempt fails, so to stop before a delay
+/// would run past some deadline wrap the strategy in
+/// [`Deadline`](strategy::Deadline)
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[non_exhaustive]
 pub struct RetryOp {
     /// number of retries
     pub retries: usize,
     /// total duration we've delayed
     pub total_delay: Duration,
+    /// wall time since the first attempt started, including delays
+    pub total_elapsed: Duration,
+    /// how long the previous attempt took, zero on the first attempt
+    pub last_attempt: Duration,
+    #[cfg(feature = "std")]
+    started: Instant,
+}
+
+impl RetryOp {
+    /// when the first attempt started
+    #[cfg(feature = "std")]
+    pub fn started(&self) -> Instant {
+        self.started
+    }
+}
+
+/// How a successful retry went, see [`retry_with_stats`]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct RetryStats {
+    /// number of attempts, including the successful one
+    pub attempts: usize,
+    /// total duration we've delayed
+    pub total_delay: Duration,
+    /// wall time from the first attempt starting to the last one finishing
+    pub total_elapsed: Duration,
+}
+
+#[cfg(feature = "std")]
+impl RetryStats {
+    /// the stats once the attempt given `op` has succeeded
+    fn finish(op: RetryOp) -> Self {
+        Self {
+            attempts: op.retries.saturating_add(1),
+            total_delay: op.total_delay,
+            total_elapsed: op.started.elapsed(),
+        }
+    }
+}
+
+/// `true` once `delays` is known to have nothing left, read from its
+/// `size_hint` so nothing is taken from it
+#[cfg(any(feature = "std", feature = "embassy"))]
+pub(crate) fn exhausted<I>(delays: &I) -> bool
+where
+    I: Iterator,
+{
+    delays.size_hint().1 == Some(0)
+}
+
+/// the delay to wait after a failed attempt, `None` if the strategy has
+/// ended or that was its last delay, so the loop stops instead of sleeping
+/// before an attempt that will never run
+#[cfg(any(feature = "std", feature = "embassy"))]
+pub(crate) fn next_delay<I>(delays: &mut I) -> Option<Duration>
+where
+    I: Iterator<Item = Duration>,
+{
+    delays.next().filter(|_| !exhausted(delays))
+}
+
+/// times the attempts of a retry loop for `RetryOp`
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub(crate) struct Stopwatch {
+    now: fn() -> Instant,
+    start: Instant,
+    attempt: Instant,
+    last_attempt: Duration,
+}
+
+#[cfg(feature = "std")]
+impl Stopwatch {
+    pub(crate) fn new() -> Self {
+        Self::at(Instant::now())
+    }
+
+    /// a stopwatch reading the time from `now`, e.g. the clock of a timer
+    pub(crate) fn timed(now: fn() -> Instant) -> Self {
+        Self {
+            now,
+            ..Self::at(now())
+        }
+    }
+
+    /// a stopwatch started at `now`
+    pub(crate) fn at(now: Instant) -> Self {
+        Self {
+            now: Instant::now,
+            start: now,
+            attempt: now,
+            last_attempt: Duration::from_millis(0),
+        }
+    }
+
+    /// the `RetryOp` for an attempt starting now
+    pub(crate) fn op(&mut self, retries: usize, total_delay: Duration) -> RetryOp {
+        self.op_at((self.now)(), retries, total_delay)
+    }
+
+    /// the `RetryOp` for an attempt starting at `now`
+    pub(crate) fn op_at(&mut self, now: Instant, retries: usize, total_delay: Duration) -> RetryOp {
+        self.attempt = now;
+        RetryOp {
+            retries,
+            total_delay,
+            total_elapsed: self.attempt.saturating_duration_since(self.start),
+            last_attempt: self.last_attempt,
+            started: self.start,
+        }
+    }
+
+    /// mark the end of the attempt started by the last `op`
+    pub(crate) fn done(&mut self) {
+        self.done_at((self.now)())
+    }
+
+    /// mark the end at `now` of the attempt started by the last `op`
+    pub(crate) fn done_at(&mut self, now: Instant) {
+        self.last_attempt = now.saturating_duration_since(self.attempt);
+    }
 }
 
 /// What to do with the current result of the function
 ///
-/// `Retry` will execute the function again, `Err(E)` will return an error with
-/// E, `Ok(T)` will return success with T
+/// `Retry` will execute the function again, `RetryWith(E)` will execute the
+/// function again after handing E to the strategy (see [`retry_strategy`]),
+/// `Err(E)` will return an error with E, `Ok(T)` will return success with T.
+/// If the strategy ends after a `RetryWith(E)`, E is returned as a
+/// `FailedAttempt` instead of `IteratorEnded`, so the cause isn't lost
 #[derive(Debug, Clone)]
 pub enum RetryResult<T, E> {
     /// try again
     Retry(),
+    /// try again, passing the error to the strategy
+    RetryWith(E),
     /// return with an error
     Err(E),
     /// return with success
     Ok(T),
 }
 
+impl<T, E> RetryResult<T, E> {
+    /// `true` if this is `Ok`
+    pub fn is_ok(&self) -> bool {
+        matches!(self, RetryResult::Ok(_))
+    }
+
+    /// `true` if this is `Err`
+    pub fn is_err(&self) -> bool {
+        matches!(self, RetryResult::Err(_))
+    }
+
+    /// `true` if this is `Retry` or `RetryWith`
+    pub fn is_retry(&self) -> bool {
+        matches!(self, RetryResult::Retry() | RetryResult::RetryWith(_))
+    }
+
+    /// The success value, if any
+    pub fn ok(self) -> Option<T> {
+        match self {
+            RetryResult::Ok(val) => Some(val),
+            _ => None,
+        }
+    }
+
+    /// The error of `Err` or `RetryWith`, if any
+    pub fn err(self) -> Option<E> {
+        match self {
+            RetryResult::Err(err) | RetryResult::RetryWith(err) => Some(err),
+            _ => None,
+        }
+    }
+
+    /// Map the success value with `f`
+    pub fn map<U, F>(self, f: F) -> RetryResult<U, E>
+    where
+        F: FnOnce(T) -> U,
+    {
+        match self {
+            RetryResult::Ok(val) => RetryResult::Ok(f(val)),
+            RetryResult::Retry() => RetryResult::Retry(),
+            RetryResult::RetryWith(err) => RetryResult::RetryWith(err),
+            RetryResult::Err(err) => RetryResult::Err(err),
+        }
+    }
+
+    /// Map the error of `Err` or `RetryWith` with `f`
+    pub fn map_err<G, F>(self, f: F) -> RetryResult<T, G>
+    where
+        F: FnOnce(E) -> G,
+    {
+        match self {
+            RetryResult::Ok(val) => RetryResult::Ok(val),
+            RetryResult::Retry() => RetryResult::Retry(),
+            RetryResult::RetryWith(err) => RetryResult::RetryWith(f(err)),
+            RetryResult::Err(err) => RetryResult::Err(f(err)),
+        }
+    }
+
+    /// Call `f` with the success value, otherwise keep the retry or error
+    ///
+    /// ```rust
+    /// # use retry_fn::RetryResult;
+    /// let res = RetryResult::<_, &str>::Ok(2).and_then(|n| {
+    ///     if n > 1 {
+    ///         RetryResult::Ok(n * 10)
+    ///     } else {
+    ///         RetryResult::Retry()
+    ///     }
+    /// });
+    /// assert_eq!(res.ok(), Some(20));
+    /// ```
+    pub fn and_then<U, F>(self, f: F) -> RetryResult<U, E>
+    where
+        F: FnOnce(T) -> RetryResult<U, E>,
+    {
+        match self {
+            RetryResult::Ok(val) => f(val),
+            RetryResult::Retry() => RetryResult::Retry(),
+            RetryResult::RetryWith(err) => RetryResult::RetryWith(err),
+            RetryResult::Err(err) => RetryResult::Err(err),
+        }
+    }
+
+    /// Turn an `Err` into `RetryWith` if `predicate` returns `true` for it
+    ///
+    /// ```rust
+    /// # use retry_fn::RetryResult;
+    /// let res = RetryResult::<(), _>::Err("busy").retry_if(|e| *e == "busy");
+    /// assert!(res.is_retry());
+    /// ```
+    pub fn retry_if<P>(self, predicate: P) -> Self
+    where
+        P: FnOnce(&E) -> bool,
+    {
+        match self {
+            RetryResult::Err(err) if predicate(&err) => RetryResult::RetryWith(err),
+            other => other,
+        }
+    }
+
+    /// `Continue` with the error to hand the strategy if the attempt should be
+    /// retried, otherwise `Break` with the final result
+    pub(crate) fn step(
+        self,
+        tries: usize,
+        total_delay: Duration,
+    ) -> ControlFlow<Result<T, RetryErr<E>>, Option<E>> {
+        match self {
+            RetryResult::Retry() => ControlFlow::Continue(None),
+            RetryResult::RetryWith(err) => ControlFlow::Continue(Some(err)),
+            RetryResult::Err(err) => ControlFlow::Break(Err(RetryErr::FailedAttempt {
+                tries,
+                total_delay,
+                err,
+            })),
+            RetryResult::Ok(val) => ControlFlow::Break(Ok(val)),
+        }
+    }
+
+    /// `Ok` on success, retry on any error, passing it to the strategy with
+    /// `RetryWith`
+    ///
+    /// ```rust
+    /// # use retry_fn::RetryResult;
+    /// let res = RetryResult::retry_on_err("x".parse::<u8>());
+    /// assert!(matches!(res, RetryResult::RetryWith(_)));
+    /// ```
+    pub fn retry_on_err(res: Result<T, E>) -> Self {
+        match res {
+            Ok(val) => RetryResult::Ok(val),
+            Err(err) => RetryResult::RetryWith(err),
+        }
+    }
+
+    /// `Ok` if there is a value, otherwise retry
+    ///
+    /// ```rust
+    /// # use retry_fn::RetryResult;
+    /// let res = RetryResult::<u8, ()>::ok_or_retry(None);
+    /// assert!(matches!(res, RetryResult::Retry()));
+    /// ```
+    pub fn ok_or_retry(opt: Option<T>) -> Self {
+        match opt {
+            Some(val) => RetryResult::Ok(val),
+            None => RetryResult::Retry(),
+        }
+    }
+}
+
+/// `Ok` on success, otherwise stop with the error. See
+/// [`RetryResult::retry_on_err`] to retry on errors instead
+impl<T, E> From<Result<T, E>> for RetryResult<T, E> {
+    fn from(res: Result<T, E>) -> Self {
+        match res {
+            Ok(val) => RetryResult::Ok(val),
+            Err(err) => RetryResult::Err(err),
+        }
+    }
+}
+
+/// `Ok` if there is a value, otherwise retry, same as
+/// [`RetryResult::ok_or_retry`]
+impl<T, E> From<Option<T>> for RetryResult<T, E> {
+    fn from(opt: Option<T>) -> Self {
+        RetryResult::ok_or_retry(opt)
+    }
+}
+
+/// `Break(Ok(T))` is `Ok`, `Break(Err(E))` is `Err` and `Continue` retries
+impl<T, E> From<ControlFlow<Result<T, E>>> for RetryResult<T, E> {
+    fn from(flow: ControlFlow<Result<T, E>>) -> Self {
+        match flow {
+            ControlFlow::Break(res) => res.into(),
+            ControlFlow::Continue(()) => RetryResult::Retry(),
+        }
+    }
+}
+
+/// `Ok` and `Err` break, `Retry` and `RetryWith` continue, dropping the error
+/// of `RetryWith`
+impl<T, E> From<RetryResult<T, E>> for ControlFlow<Result<T, E>> {
+    fn from(res: RetryResult<T, E>) -> Self {
+        match res {
+            RetryResult::Ok(val) => ControlFlow::Break(Ok(val)),
+            RetryResult::Err(err) => ControlFlow::Break(Err(err)),
+            RetryResult::Retry() | RetryResult::RetryWith(_) => ControlFlow::Continue(()),
+        }
+    }
+}
+
+/// Unwrap a `Result` inside a retry closure, like `?`. On error, return from
+/// the closure with `RetryResult::RetryWith`, converting the error with
+/// `From` first. With a predicate, errors it returns `false` for are
+/// returned as `RetryResult::Err` instead, ending the retries
+///
+/// ```rust,no_run
+/// # use std::{fs, io};
+/// use retry_fn::{retry_strategy, strategy::Constant, try_retry, RetryResult};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let res = retry_strategy(Constant::from_millis(100).take(3), |_| {
+///     let text = try_retry!(fs::read_to_string("status"), |e: &io::Error| {
+///         e.kind() != io::ErrorKind::PermissionDenied
+///     });
+///     let code: u32 = try_retry!(text.trim().parse().map_err(|_| {
+///         io::Error::new(io::ErrorKind::InvalidData, "bad status")
+///     }));
+///     RetryResult::Ok(code)
+/// });
+/// # Ok(())
+/// # }
+/// ```
+#[macro_export]
+macro_rules! try_retry {
+    ($e:expr $(,)?) => {
+        match $e {
+            ::core::result::Result::Ok(val) => val,
+            ::core::result::Result::Err(err) => {
+                return $crate::RetryResult::RetryWith(::core::convert::From::from(err));
+            }
+        }
+    };
+    ($e:expr, $predicate:expr $(,)?) => {
+        match $e {
+            ::core::result::Result::Ok(val) => val,
+            ::core::result::Result::Err(err) => {
+                return $crate::RetryResult::Err(::core::convert::From::from(err))
+                    .retry_if($predicate);
+            }
+        }
+    };
+}
+
+/// Retry an expression or block on some time interval until it stops
+/// failing. The body can use `?`, any error retries it, and once the strategy
+/// ends the last error is returned as a `FailedAttempt`. The error type is
+/// inferred from how the result is used, so it may need annotating
+///
+/// ```rust,no_run
+/// # use std::{fs, io};
+/// use retry_fn::{retry, strategy::ExponentialBackoff, RetryErr};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let res: Result<u32, RetryErr<io::Error>> =
+///     retry!(ExponentialBackoff::from_millis(100).take(5), {
+///         let text = fs::read_to_string("status")?;
+///         text.trim()
+///             .parse()
+///             .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "bad status"))?
+///     });
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "std")]
+#[macro_export]
+macro_rules! retry {
+    ($strategy:expr, $body:expr $(,)?) => {
+        $crate::retry($strategy, |_| {
+            #[allow(clippy::redundant_closure_call)]
+            match (|| ::core::result::Result::Ok($body))() {
+                ::core::result::Result::Ok(val) => $crate::RetryResult::Ok(val),
+                ::core::result::Result::Err(err) => $crate::RetryResult::RetryWith(err),
+            }
+        })
+    };
+}
+
 /// Error type for retry
 #[derive(Debug, Clone)]
+#[non_exhaustive]
 pub enum RetryErr<E> {
     /// Attempt failed with an error
     FailedAttempt {
-        /// number of attempts
+        /// number of retries, one less than the number of attempts
         tries: usize,
         /// total delay
         total_delay: Duration,
@@ -96,13 +654,82 @@ pub enum RetryErr<E> {
     },
     /// Attempt failed by reaching the end of the iterator
     IteratorEnded {
-        /// number of attempts
+        /// number of retries, one less than the number of attempts
+        tries: usize,
+        /// total delay
+        total_delay: Duration,
+    },
+    /// Attempt failed because the [`RetryBudget`] had no retries left
+    BudgetExhausted {
+        /// number of retries, one less than the number of attempts
+        tries: usize,
+        /// total delay
+        total_delay: Duration,
+    },
+    /// Retrying stopped because it was cancelled, e.g. by a [`CancelToken`]
+    Cancelled {
+        /// number of retries, one less than the number of attempts
+        tries: usize,
+        /// total delay
+        total_delay: Duration,
+    },
+    /// Retrying stopped because the deadline passed
+    DeadlineExceeded {
+        /// number of retries, one less than the number of attempts
+        tries: usize,
+        /// total delay
+        total_delay: Duration,
+    },
+    /// The last attempt was short-circuited by an open
+    /// [`CircuitBreaker`](circuit::CircuitBreaker)
+    CircuitOpen {
+        /// number of retries, one less than the number of attempts
+        tries: usize,
+        /// total delay
+        total_delay: Duration,
+    },
+    /// The last attempt didn't get a permit from a full [`Bulkhead`]
+    BulkheadFull {
+        /// number of retries, one less than the number of attempts
+        tries: usize,
+        /// total delay
+        total_delay: Duration,
+    },
+    /// The last attempt took longer than the per-attempt timeout
+    TimedOut {
+        /// number of retries, one less than the number of attempts
+        tries: usize,
+        /// total delay
+        total_delay: Duration,
+    },
+    /// The last attempt panicked, see `RetryBuilder::catch_unwind`
+    #[cfg(feature = "alloc")]
+    Panicked {
+        /// number of retries, one less than the number of attempts
         tries: usize,
         /// total delay
         total_delay: Duration,
+        /// the panic message
+        msg: alloc::string::String,
     },
 }
 
+impl<E> RetryErr<E> {
+    /// the error for a strategy that ended, carrying the error of the last
+    /// attempt if it returned `RetryWith`
+    pub(crate) fn ended(tries: usize, total_delay: Duration, err: Option<E>) -> Self {
+        match err {
+            Some(err) => RetryErr::FailedAttempt {
+                tries,
+                total_delay,
+                err,
+            },
+            None => RetryErr::IteratorEnded { tries, total_delay },
+        }
+    }
+}
+
+#[cfg(feature = "std")]
 impl<E> Error for RetryErr<E> where E: fmt::Display + fmt::Debug {}
 
 impl<E> fmt::Display for RetryErr<E>
@@ -125,6 +752,46 @@ where
                 "iterator ended, retries {}, total delay {:#?}",
                 tries, total_delay
             ),
+            RetryErr::BudgetExhausted { tries, total_delay } => write!(
+                f,
+                "retry budget exhausted, retries {}, total delay {:#?}",
+                tries, total_delay
+            ),
+            RetryErr::Cancelled { tries, total_delay } => write!(
+                f,
+                "cancelled, retries {}, total delay {:#?}",
+                tries, total_delay
+            ),
+            RetryErr::DeadlineExceeded { tries, total_delay } => write!(
+                f,
+                "deadline exceeded, retries {}, total delay {:#?}",
+                tries, total_delay
+            ),
+            RetryErr::CircuitOpen { tries, total_delay } => write!(
+                f,
+                "circuit open, retries {}, total delay {:#?}",
+                tries, total_delay
+            ),
+            RetryErr::BulkheadFull { tries, total_delay } => write!(
+                f,
+                "bulkhead full, retries {}, total delay {:#?}",
+                tries, total_delay
+            ),
+            RetryErr::TimedOut { tries, total_delay } => write!(
+                f,
+                "attempt timed out, retries {}, total delay {:#?}",
+                tries, total_delay
+            ),
+            #[cfg(feature = "alloc")]
+            RetryErr::Panicked {
+                tries,
+                total_delay,
+                msg,
+            } => write!(
+                f,
+                "panicked with {}, retries {}, total delay {:#?}",
+                msg, tries, total_delay
+            ),
         }
     }
 }
@@ -156,6 +823,7 @@ where
 /// # Returns
 /// If successful, return `Ok`, otherwise return `Retry` to try again or `Err`
 /// to exit with an error
+#[cfg(feature = "std")]
 pub fn retry_immediate<F, T, E>(f: F) -> Result<T, RetryErr<E>>
 where
     F: FnMut(RetryOp) -> RetryResult<T, E>,
@@ -163,7 +831,8 @@ where
     retry(Immediate, f)
 }
 
-/// Retry a function on some time interval
+/// Retry a function on some time interval, one attempt per delay. The last
+/// delay isn't waited if the iterator's `size_hint` shows it was the last
 ///
 /// ```rust,no_run
 /// # use std::{io, time::Duration};
@@ -190,51 +859,1104 @@ where
 /// # Returns
 /// If successful, return `Ok`, otherwise return `Retry` to try again or `Err`
 /// to exit with an error
-pub fn retry<I, F, T, E>(iter: I, mut f: F) -> Result<T, RetryErr<E>>
+#[cfg(feature = "std")]
+pub fn retry<I, F, T, E>(iter: I, f: F) -> Result<T, RetryErr<E>>
 where
     I: IntoIterator<Item = Duration>,
     F: FnMut(RetryOp) -> RetryResult<T, E>,
 {
-    let mut count = 0;
-    let mut total_delay = Duration::from_millis(0);
-    for dur in iter.into_iter() {
-        match f(RetryOp {
-            retries: count,
+    retry_delayed(Duration::from_millis(0), iter, f)
+}
+
+/// Retry a function on some time interval, returning how many attempts it
+/// took along with the value on success
+///
+/// ```rust,no_run
+/// use retry_fn::{retry_with_stats, strategy::Constant, RetryResult};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let (val, stats) = retry_with_stats(Constant::from_millis(100), |op| {
+///     if op.retries >= 3 {
+///         RetryResult::<_, &str>::Ok("done")
+///     } else {
+///         RetryResult::Retry()
+///     }
+/// })?;
+/// assert_eq!(val, "done");
+/// assert_eq!(stats.attempts, 4);
+/// Ok(())
+/// # }
+/// ```
+///
+/// # Returns
+/// If successful, return `Ok`, otherwise return `Retry` to try again or `Err`
+/// to exit with an error
+#[cfg(feature = "std")]
+pub fn retry_with_stats<I, F, T, E>(iter: I, mut f: F) -> Result<(T, RetryStats), RetryErr<E>>
+where
+    I: IntoIterator<Item = Duration>,
+    F: FnMut(RetryOp) -> RetryResult<T, E>,
+{
+    retry(iter, |op| f(op).map(|val| (val, op))).map(|(val, op)| (val, RetryStats::finish(op)))
+}
+
+/// Retry a function on some time interval, keeping every error it returns
+/// with `RetryWith` or `Err`. On failure all of them are returned in order as
+/// a `FailedAttempt`, so the pattern of failures isn't hidden behind the last
+/// one
+///
+/// ```rust,no_run
+/// use retry_fn::{retry_collecting, strategy::Constant, RetryErr, RetryResult};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let res = retry_collecting(Constant::from_millis(100).take(3), |op| {
+///     RetryResult::<(), _>::RetryWith(op.retries)
+/// });
+/// assert!(matches!(res, Err(RetryErr::FailedAttempt { err, .. }) if err == vec![0, 1, 2]));
+/// Ok(())
+/// # }
+/// ```
+///
+/// # Returns
+/// If successful, return `Ok`, otherwise return `Retry` or `RetryWith` to try
+/// again or `Err` to exit with an error
+#[cfg(feature = "std")]
+pub fn retry_collecting<I, F, T, E>(iter: I, mut f: F) -> Result<T, RetryErr<Vec<E>>>
+where
+    I: IntoIterator<Item = Duration>,
+    F: FnMut(RetryOp) -> RetryResult<T, E>,
+{
+    let mut errors = Vec::new();
+    let res = retry(iter, |op| match f(op) {
+        RetryResult::Ok(val) => RetryResult::Ok(val),
+        RetryResult::Retry() => RetryResult::Retry(),
+        RetryResult::RetryWith(err) => {
+            errors.push(err);
+            RetryResult::Retry()
+        }
+        RetryResult::Err(err) => {
+            errors.push(err);
+            RetryResult::Err(())
+        }
+    });
+    match res {
+        Ok(val) => Ok(val),
+        Err(RetryErr::FailedAttempt {
+            tries, total_delay, ..
+        }) => Err(RetryErr::FailedAttempt {
+            tries,
             total_delay,
-        }) {
-            RetryResult::Retry() => {
-                thread::sleep(dur);
-                total_delay += dur;
-                count += 1;
-            }
-            RetryResult::Err(err) => {
-                return Err(RetryErr::FailedAttempt {
-                    tries: count,
-                    total_delay,
-                    err,
-                });
-            }
-            RetryResult::Ok(val) => {
-                return Ok(val);
-            }
+            err: errors,
+        }),
+        Err(RetryErr::IteratorEnded { tries, total_delay }) if errors.is_empty() => {
+            Err(RetryErr::IteratorEnded { tries, total_delay })
+        }
+        Err(RetryErr::IteratorEnded { tries, total_delay }) => Err(RetryErr::FailedAttempt {
+            tries,
+            total_delay,
+            err: errors,
+        }),
+        // the rest carry no error, they're passed through as they are
+        Err(RetryErr::BudgetExhausted { tries, total_delay }) => {
+            Err(RetryErr::BudgetExhausted { tries, total_delay })
         }
+        Err(RetryErr::Cancelled { tries, total_delay }) => {
+            Err(RetryErr::Cancelled { tries, total_delay })
+        }
+        Err(RetryErr::DeadlineExceeded { tries, total_delay }) => {
+            Err(RetryErr::DeadlineExceeded { tries, total_delay })
+        }
+        Err(RetryErr::CircuitOpen { tries, total_delay }) => {
+            Err(RetryErr::CircuitOpen { tries, total_delay })
+        }
+        Err(RetryErr::BulkheadFull { tries, total_delay }) => {
+            Err(RetryErr::BulkheadFull { tries, total_delay })
+        }
+        Err(RetryErr::TimedOut { tries, total_delay }) => {
+            Err(RetryErr::TimedOut { tries, total_delay })
+        }
+        Err(RetryErr::Panicked {
+            tries,
+            total_delay,
+            msg,
+        }) => Err(RetryErr::Panicked {
+            tries,
+            total_delay,
+            msg,
+        }),
     }
-    Err(RetryErr::IteratorEnded {
-        tries: count,
-        total_delay,
-    })
 }
 
-#[cfg(test)]
-mod test {
-    use crate::RetryResult;
-
-    use super::*;
-    use crate::strategy::*;
-
-    use std::io;
-
-    #[test]
+/// Retry an [`Operation`] on some time interval, like [`retry`](retry()) but for
+/// operations that aren't closures
+///
+/// # Returns
+/// If successful, return `Ok`, otherwise return `Retry` to try again or `Err`
+/// to exit with an error
+#[cfg(feature = "std")]
+pub fn retry_operation<I, O, T, E>(iter: I, mut operation: O) -> Result<T, RetryErr<E>>
+where
+    I: IntoIterator<Item = Duration>,
+    O: Operation<T, E>,
+{
+    retry(iter, |op| operation.run(op))
+}
+
+/// Retry a function on some time interval, calling it at most `times` times
+/// however long the strategy is
+///
+/// ```rust,no_run
+/// use retry_fn::{retry_times, strategy::Constant, RetryResult};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut count = 0;
+/// let res = retry_times(Constant::from_millis(100), 5, |_| {
+///     count += 1;
+///     RetryResult::<(), &str>::Retry()
+/// });
+/// assert_eq!(count, 5);
+/// assert!(res.is_err());
+/// Ok(())
+/// # }
+/// ```
+///
+/// # Returns
+/// If successful, return `Ok`, otherwise return `Retry` to try again or `Err`
+/// to exit with an error
+#[cfg(feature = "std")]
+pub fn retry_times<I, F, T, E>(iter: I, times: usize, f: F) -> Result<T, RetryErr<E>>
+where
+    I: IntoIterator<Item = Duration>,
+    F: FnMut(RetryOp) -> RetryResult<T, E>,
+{
+    retry(MaxRetries::new(iter.into_iter(), times), f)
+}
+
+/// Retry a function up to `attempts` times in all, waiting `delay` between
+/// attempts
+///
+/// ```rust,no_run
+/// # use std::time::Duration;
+/// use retry_fn::{retry_n, RetryResult};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut count = 0;
+/// let res = retry_n(3, Duration::from_millis(100), |_| {
+///     count += 1;
+///     RetryResult::<(), &str>::Retry()
+/// });
+/// assert_eq!(count, 3);
+/// assert!(res.is_err());
+/// Ok(())
+/// # }
+/// ```
+///
+/// # Returns
+/// If successful, return `Ok`, otherwise return `Retry` to try again or `Err`
+/// to exit with an error
+#[cfg(feature = "std")]
+pub fn retry_n<F, T, E>(attempts: usize, delay: Duration, f: F) -> Result<T, RetryErr<E>>
+where
+    F: FnMut(RetryOp) -> RetryResult<T, E>,
+{
+    retry_times(Constant::new(delay), attempts, f)
+}
+
+/// Retry a function on some time interval, sleeping for `delay` once before
+/// the first attempt. The initial delay is counted in `total_delay`
+///
+/// ```rust,no_run
+/// # use std::time::Duration;
+/// use retry_fn::{retry_delayed, strategy::ExponentialBackoff, RetryResult};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let res = retry_delayed(
+///     Duration::from_secs(1),
+///     ExponentialBackoff::new(Duration::from_secs(2)),
+///     |op| {
+///         if op.retries >= 3 {
+///             RetryResult::<_, &str>::Ok(op.total_delay)
+///         } else {
+///             RetryResult::Retry()
+///         }
+///     },
+/// );
+/// assert!(res.is_ok());
+/// Ok(())
+/// # }
+/// ```
+///
+/// # Returns
+/// If successful, return `Ok`, otherwise return `Retry` to try again or `Err`
+/// to exit with an error
+#[cfg(feature = "std")]
+pub fn retry_delayed<I, F, T, E>(delay: Duration, iter: I, mut f: F) -> Result<T, RetryErr<E>>
+where
+    I: IntoIterator<Item = Duration>,
+    F: FnMut(RetryOp) -> RetryResult<T, E>,
+{
+    clock_loop(SystemClock, delay, iter.into_iter(), |op, _| f(op), wait)
+}
+
+/// Retry a function on some time interval, getting the time from `clock` and
+/// waiting out each delay with it, see [`Clock`]. With a [`ManualClock`] the
+/// loop runs without sleeping, for testing retry behavior
+///
+/// # Returns
+/// If successful, return `Ok`, otherwise return `Retry` to try again or `Err`
+/// to exit with an error
+#[cfg(feature = "std")]
+pub fn retry_with_clock<C, I, F, T, E>(clock: C, iter: I, mut f: F) -> Result<T, RetryErr<E>>
+where
+    C: Clock,
+    I: IntoIterator<Item = Duration>,
+    F: FnMut(RetryOp) -> RetryResult<T, E>,
+{
+    clock_loop(
+        clock,
+        Duration::from_millis(0),
+        iter.into_iter(),
+        |op, _| f(op),
+        wait,
+    )
+}
+
+/// where the delays of a retry loop come from, an iterator or a
+/// `RetryStrategy` handed each error
+#[cfg(feature = "std")]
+trait Delays<E> {
+    /// `true` if no attempt should run at all
+    fn exhausted(&self) -> bool;
+
+    /// the delay after failed attempt `attempt`, `None` to stop
+    fn next_delay(&mut self, attempt: usize, err: Option<&E>) -> Option<Duration>;
+}
+
+#[cfg(feature = "std")]
+impl<I, E> Delays<E> for I
+where
+    I: Iterator<Item = Duration>,
+{
+    fn exhausted(&self) -> bool {
+        exhausted(self)
+    }
+
+    fn next_delay(&mut self, _: usize, _: Option<&E>) -> Option<Duration> {
+        next_delay(self)
+    }
+}
+
+/// a `RetryStrategy` as `Delays`, its delay isn't known until the error is
+#[cfg(feature = "std")]
+struct Strategy<S>(S);
+
+#[cfg(feature = "std")]
+impl<S, E> Delays<E> for Strategy<S>
+where
+    S: RetryStrategy<E>,
+{
+    fn exhausted(&self) -> bool {
+        self.0.remaining() == Some(0)
+    }
+
+    fn next_delay(&mut self, attempt: usize, err: Option<&E>) -> Option<Duration> {
+        self.0.delay(attempt, err).filter(|_| !self.exhausted())
+    }
+}
+
+/// why a pause ended the loop, see `clock_loop`
+#[cfg(feature = "std")]
+enum Halt {
+    Cancelled,
+    DeadlineExceeded,
+    BudgetExhausted,
+}
+
+#[cfg(feature = "std")]
+impl Halt {
+    fn into_err<E>(self, tries: usize, total_delay: Duration) -> RetryErr<E> {
+        match self {
+            Halt::Cancelled => RetryErr::Cancelled { tries, total_delay },
+            Halt::DeadlineExceeded => RetryErr::DeadlineExceeded { tries, total_delay },
+            Halt::BudgetExhausted => RetryErr::BudgetExhausted { tries, total_delay },
+        }
+    }
+}
+
+/// the pause of a plain retry loop, waiting out the whole delay
+#[cfg(feature = "std")]
+fn wait<C, E>(clock: &mut C, _: RetryOp, dur: Duration, _: Option<&E>) -> Result<Duration, Halt>
+where
+    C: Clock,
+{
+    clock.sleep(dur);
+    Ok(dur)
+}
+
+/// the loop of every sync retry function. `f` also gets the error of the
+/// previous attempt, and `pause` waits out each delay on `clock` given the
+/// failed attempt and its error, returning how long it waited or why to stop
+#[cfg(feature = "std")]
+fn clock_loop<C, D, F, P, T, E>(
+    mut clock: C,
+    delay: Duration,
+    mut delays: D,
+    mut f: F,
+    mut pause: P,
+) -> Result<T, RetryErr<E>>
+where
+    C: Clock,
+    D: Delays<E>,
+    F: FnMut(RetryOp, Option<&E>) -> RetryResult<T, E>,
+    P: FnMut(&mut C, RetryOp, Duration, Option<&E>) -> Result<Duration, Halt>,
+{
+    if delays.exhausted() {
+        return Err(RetryErr::IteratorEnded {
+            tries: 0,
+            total_delay: Duration::from_millis(0),
+        });
+    }
+    let mut watch = Stopwatch::at(clock.now());
+    if delay > Duration::from_millis(0) {
+        clock.sleep(delay);
+    }
+    let mut count = 0;
+    let mut total_delay = delay;
+    let mut last = None;
+    loop {
+        let op = watch.op_at(clock.now(), count, total_delay);
+        last = match f(op, last.as_ref()).step(count, total_delay) {
+            ControlFlow::Continue(err) => err,
+            ControlFlow::Break(res) => return res,
+        };
+        watch.done_at(clock.now());
+        let dur = match delays.next_delay(count, last.as_ref()) {
+            Some(dur) => dur,
+            None => return Err(RetryErr::ended(count, total_delay, last)),
+        };
+        let dur = match pause(&mut clock, op, dur, last.as_ref()) {
+            Ok(dur) => dur,
+            Err(halt) => return Err(halt.into_err(count, total_delay)),
+        };
+        total_delay = total_delay.saturating_add(dur);
+        count += 1;
+    }
+}
+
+/// Retry a function on some time interval, calling `notify` before each sleep
+/// with the failed attempt, the delay about to be waited and the error if the
+/// function returned `RetryWith`. Useful for logging every retry without
+/// doing it in the function itself
+///
+/// ```rust,no_run
+/// use retry_fn::{retry_notify, strategy::Constant, RetryResult};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let res = retry_notify(
+///     Constant::from_millis(100),
+///     |op| {
+///         if op.retries >= 3 {
+///             RetryResult::Ok(op.retries)
+///         } else {
+///             RetryResult::RetryWith("busy")
+///         }
+///     },
+///     |op, delay, err| eprintln!("try {} failed: {:?}, retrying in {:?}", op.retries, err, delay),
+/// );
+/// assert_eq!(res.ok(), Some(3));
+/// Ok(())
+/// # }
+/// ```
+///
+/// # Returns
+/// If successful, return `Ok`, otherwise return `Retry` to try again or `Err`
+/// to exit with an error
+#[cfg(feature = "std")]
+pub fn retry_notify<I, F, N, T, E>(iter: I, mut f: F, mut notify: N) -> Result<T, RetryErr<E>>
+where
+    I: IntoIterator<Item = Duration>,
+    F: FnMut(RetryOp) -> RetryResult<T, E>,
+    N: FnMut(RetryOp, Duration, Option<&E>),
+{
+    clock_loop(
+        SystemClock,
+        Duration::from_millis(0),
+        iter.into_iter(),
+        |op, _| f(op),
+        |clock, op, dur, err| {
+            notify(op, dur, err);
+            wait(clock, op, dur, err)
+        },
+    )
+}
+
+/// Retry a function on some time interval, also passing it the error from the
+/// previous attempt if that attempt returned `RetryWith`. Useful for changing
+/// what the next attempt does (another endpoint, fresh credentials) based on
+/// what failed
+///
+/// ```rust,no_run
+/// use retry_fn::{retry_with_last_error, strategy::Constant, RetryResult};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let res = retry_with_last_error(Constant::from_millis(100).take(3), |_, last| match last {
+///     Some(&"expired") => RetryResult::Ok("refreshed"),
+///     _ => RetryResult::RetryWith("expired"),
+/// });
+/// assert_eq!(res.ok(), Some("refreshed"));
+/// Ok(())
+/// # }
+/// ```
+///
+/// # Returns
+/// If successful, return `Ok`, otherwise return `Retry` to try again or `Err`
+/// to exit with an error
+#[cfg(feature = "std")]
+pub fn retry_with_last_error<I, F, T, E>(iter: I, f: F) -> Result<T, RetryErr<E>>
+where
+    I: IntoIterator<Item = Duration>,
+    F: FnMut(RetryOp, Option<&E>) -> RetryResult<T, E>,
+{
+    clock_loop(
+        SystemClock,
+        Duration::from_millis(0),
+        iter.into_iter(),
+        f,
+        wait,
+    )
+}
+
+/// Retry a function on some time interval, handing it `ctx` mutably on every
+/// attempt. State like a connection or a buffer can be reused between
+/// attempts without sharing it through an `Arc<Mutex<_>>`
+///
+/// ```rust,no_run
+/// use retry_fn::{retry_with_context, strategy::Constant, RetryResult};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut log = Vec::new();
+/// let res = retry_with_context(Constant::from_millis(100), &mut log, |log, op| {
+///     log.push(op.retries);
+///     if op.retries >= 2 {
+///         RetryResult::<_, &str>::Ok(log.len())
+///     } else {
+///         RetryResult::Retry()
+///     }
+/// });
+/// assert_eq!(res.ok(), Some(3));
+/// assert_eq!(log, vec![0, 1, 2]);
+/// Ok(())
+/// # }
+/// ```
+///
+/// # Returns
+/// If successful, return `Ok`, otherwise return `Retry` to try again or `Err`
+/// to exit with an error
+#[cfg(feature = "std")]
+pub fn retry_with_context<I, C, F, T, E>(iter: I, ctx: &mut C, mut f: F) -> Result<T, RetryErr<E>>
+where
+    I: IntoIterator<Item = Duration>,
+    C: ?Sized,
+    F: FnMut(&mut C, RetryOp) -> RetryResult<T, E>,
+{
+    retry(iter, |op| f(ctx, op))
+}
+
+/// Retry a function on some time interval until `token` is cancelled.
+/// Cancelling wakes a pending delay right away, see [`CancelToken`].
+/// `total_delay` counts only the delays that ran to completion
+///
+/// # Returns
+/// If successful, return `Ok`, otherwise return `Retry` to try again or `Err`
+/// to exit with an error. Once cancelled, returns `Cancelled` without calling
+/// the function again
+#[cfg(feature = "std")]
+pub fn retry_cancellable<I, F, T, E>(
+    token: &CancelToken,
+    iter: I,
+    mut f: F,
+) -> Result<T, RetryErr<E>>
+where
+    I: IntoIterator<Item = Duration>,
+    F: FnMut(RetryOp) -> RetryResult<T, E>,
+{
+    if token.is_cancelled() {
+        return Err(RetryErr::Cancelled {
+            tries: 0,
+            total_delay: Duration::from_millis(0),
+        });
+    }
+    clock_loop(
+        SystemClock,
+        Duration::from_millis(0),
+        iter.into_iter(),
+        |op, _| f(op),
+        |_, _, dur, _| {
+            if token.sleep(dur) {
+                Ok(dur)
+            } else {
+                Err(Halt::Cancelled)
+            }
+        },
+    )
+}
+
+/// Retry a function on some time interval until `deadline`. A delay that
+/// would sleep past the deadline is cut short so the last attempt runs right
+/// at it. Unlike checking `op.total_delay`, this counts the time the function
+/// itself takes
+///
+/// ```rust,no_run
+/// # use std::time::{Duration, Instant};
+/// use retry_fn::{retry_with_deadline, strategy::Constant, RetryErr, RetryResult};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let deadline = Instant::now() + Duration::from_secs(5);
+/// let res = retry_with_deadline(Constant::from_secs(2), deadline, |_| {
+///     RetryResult::<(), &str>::Retry()
+/// });
+/// assert!(matches!(res, Err(RetryErr::DeadlineExceeded { .. })));
+/// Ok(())
+/// # }
+/// ```
+///
+/// # Returns
+/// If successful, return `Ok`, otherwise return `Retry` to try again or `Err`
+/// to exit with an error. Once the deadline has passed, returns
+/// `DeadlineExceeded`
+#[cfg(feature = "std")]
+pub fn retry_with_deadline<I, F, T, E>(
+    iter: I,
+    deadline: Instant,
+    mut f: F,
+) -> Result<T, RetryErr<E>>
+where
+    I: IntoIterator<Item = Duration>,
+    F: FnMut(RetryOp) -> RetryResult<T, E>,
+{
+    clock_loop(
+        SystemClock,
+        Duration::from_millis(0),
+        iter.into_iter(),
+        |op, _| f(op),
+        |clock, _, dur, _| {
+            let left = deadline.saturating_duration_since(clock.now());
+            if left == Duration::from_millis(0) {
+                return Err(Halt::DeadlineExceeded);
+            }
+            let dur = dur.min(left);
+            clock.sleep(dur);
+            Ok(dur)
+        },
+    )
+}
+
+/// Retry a function at a fixed rate: each delay is measured between the
+/// starts of two attempts, so the time the function took is subtracted from
+/// the sleep. Wake-ups are instants counted from the first attempt, so
+/// neither slow attempts nor oversleeping push the schedule later. An
+/// attempt that takes longer than its delay is retried right away.
+/// `total_delay` counts only the time spent sleeping
+///
+/// ```rust,no_run
+/// use retry_fn::{retry_fixed_rate, strategy::Constant, RetryResult};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// // poll once a second, however long each poll takes
+/// let res = retry_fixed_rate(Constant::from_secs(1), |op| {
+///     if op.retries >= 3 {
+///         RetryResult::<_, &str>::Ok(op.retries)
+///     } else {
+///         RetryResult::Retry()
+///     }
+/// });
+/// assert_eq!(res.ok(), Some(3));
+/// Ok(())
+/// # }
+/// ```
+///
+/// # Returns
+/// If successful, return `Ok`, otherwise return `Retry` to try again or `Err`
+/// to exit with an error
+#[cfg(feature = "std")]
+pub fn retry_fixed_rate<I, F, T, E>(iter: I, mut f: F) -> Result<T, RetryErr<E>>
+where
+    I: IntoIterator<Item = Duration>,
+    F: FnMut(RetryOp) -> RetryResult<T, E>,
+{
+    let clock = SystemClock;
+    let mut wake = clock.now();
+    clock_loop(
+        clock,
+        Duration::from_millis(0),
+        iter.into_iter(),
+        |op, _| f(op),
+        |clock, _, dur, _| {
+            let dur = match wake.checked_add(dur) {
+                Some(at) => {
+                    wake = at;
+                    at.saturating_duration_since(clock.now())
+                }
+                // too far off to be an instant, so it won't wake anyway
+                None => dur,
+            };
+            clock.sleep(dur);
+            Ok(dur)
+        },
+    )
+}
+
+/// Retry a function returning `ControlFlow` on some time interval. `Continue`
+/// tries again, `Break` returns its `Result`
+///
+/// ```rust,no_run
+/// # use std::ops::ControlFlow;
+/// use retry_fn::{retry_flow, strategy::Constant};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let res = retry_flow(Constant::from_millis(100), |op| {
+///     if op.retries >= 3 {
+///         ControlFlow::Break(Ok::<_, &str>(op.retries))
+///     } else {
+///         ControlFlow::Continue(())
+///     }
+/// });
+/// assert_eq!(res.ok(), Some(3));
+/// Ok(())
+/// # }
+/// ```
+///
+/// # Returns
+/// If successful, return `Break(Ok)`, otherwise return `Continue` to try again
+/// or `Break(Err)` to exit with an error
+#[cfg(feature = "std")]
+pub fn retry_flow<I, F, T, E>(iter: I, mut f: F) -> Result<T, RetryErr<E>>
+where
+    I: IntoIterator<Item = Duration>,
+    F: FnMut(RetryOp) -> ControlFlow<Result<T, E>>,
+{
+    retry(iter, |op| f(op).into())
+}
+
+/// Retry a function on some time interval through a shared
+/// [`CircuitBreaker`](circuit::CircuitBreaker). Attempts are short-circuited
+/// without calling the function while the breaker is open, and the loop
+/// keeps backing off until it lets one through. Any result other than `Ok`
+/// is recorded as a failure
+///
+/// ```rust,no_run
+/// # use std::{sync::Arc, time::Duration};
+/// use retry_fn::{circuit::CircuitBreaker, retry_with_breaker, strategy::Constant, RetryResult};
+/// # fn call() -> Result<(), std::io::Error> { Ok(()) }
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let breaker = Arc::new(CircuitBreaker::new(5, Duration::from_secs(30)));
+/// retry_with_breaker(&breaker, Constant::from_secs(1).take(10), |_| {
+///     RetryResult::retry_on_err(call())
+/// })?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Returns
+/// If successful, return `Ok`, otherwise return `Retry` to try again or `Err`
+/// to exit with an error. If the strategy ends with the last attempt
+/// short-circuited, returns `CircuitOpen`
+#[cfg(feature = "std")]
+pub fn retry_with_breaker<I, F, T, E>(
+    breaker: &circuit::CircuitBreaker,
+    iter: I,
+    mut f: F,
+) -> Result<T, RetryErr<E>>
+where
+    I: IntoIterator<Item = Duration>,
+    F: FnMut(RetryOp) -> RetryResult<T, E>,
+{
+    let open = Cell::new(false);
+    let res = retry(iter, |op| {
+        open.set(!breaker.try_acquire());
+        if open.get() {
+            return RetryResult::Retry();
+        }
+        let res = f(op);
+        if res.is_ok() {
+            breaker.record_success();
+        } else {
+            breaker.record_failure();
+        }
+        res
+    });
+    match res {
+        Err(RetryErr::IteratorEnded { tries, total_delay }) if open.get() => {
+            Err(RetryErr::CircuitOpen { tries, total_delay })
+        }
+        res => res,
+    }
+}
+
+/// Retry a function on some time interval, running each attempt under a
+/// permit of a shared [`Bulkhead`]. An attempt that can't get a permit isn't
+/// run, the loop waits for the next delay and tries again
+///
+/// ```rust,no_run
+/// # use std::sync::Arc;
+/// use retry_fn::{retry_with_bulkhead, strategy::Constant, Bulkhead, RetryResult};
+/// # fn call() -> Result<(), std::io::Error> { Ok(()) }
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let bulkhead = Arc::new(Bulkhead::new(8));
+/// retry_with_bulkhead(&bulkhead, Constant::from_millis(100).take(10), |_| {
+///     RetryResult::retry_on_err(call())
+/// })?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Returns
+/// If successful, return `Ok`, otherwise return `Retry` to try again or `Err`
+/// to exit with an error. If the strategy ends without a permit for the last
+/// attempt, returns `BulkheadFull`
+#[cfg(feature = "std")]
+pub fn retry_with_bulkhead<I, F, T, E>(
+    bulkhead: &Bulkhead,
+    iter: I,
+    mut f: F,
+) -> Result<T, RetryErr<E>>
+where
+    I: IntoIterator<Item = Duration>,
+    F: FnMut(RetryOp) -> RetryResult<T, E>,
+{
+    let full = Cell::new(false);
+    let res = retry(iter, |op| {
+        let permit = bulkhead.try_acquire();
+        full.set(permit.is_none());
+        match permit {
+            Some(_permit) => f(op),
+            None => RetryResult::Retry(),
+        }
+    });
+    match res {
+        Err(RetryErr::IteratorEnded { tries, total_delay }) if full.get() => {
+            Err(RetryErr::BulkheadFull { tries, total_delay })
+        }
+        res => res,
+    }
+}
+
+/// Retry a function on some time interval, taking each retry from a shared
+/// [`RetryBudget`]. The call is recorded as a request in the budget, and
+/// retrying stops with `BudgetExhausted` once the budget has none left
+///
+/// ```rust,no_run
+/// # use std::{io, sync::Arc};
+/// use retry_fn::{retry_with_budget, strategy::Constant, RetryBudget, RetryResult};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let budget = Arc::new(RetryBudget::default());
+/// let res = retry_with_budget(&budget, Constant::from_millis(100), |op| {
+///    if op.retries >= 3 {
+///        RetryResult::<&str, _>::Err(io::Error::new(
+///            io::ErrorKind::TimedOut,
+///            "timed out",
+///        ))
+///    } else {
+///        RetryResult::Retry()
+///    }
+/// });
+/// assert!(res.is_err());
+/// Ok(())
+/// # }
+/// ```
+///
+/// # Returns
+/// If successful, return `Ok`, otherwise return `Retry` to try again or `Err`
+/// to exit with an error
+#[cfg(feature = "std")]
+pub fn retry_with_budget<I, F, T, E>(
+    budget: &RetryBudget,
+    iter: I,
+    mut f: F,
+) -> Result<T, RetryErr<E>>
+where
+    I: IntoIterator<Item = Duration>,
+    F: FnMut(RetryOp) -> RetryResult<T, E>,
+{
+    budget.deposit();
+    clock_loop(
+        SystemClock,
+        Duration::from_millis(0),
+        iter.into_iter(),
+        |op, _| f(op),
+        |clock, op, dur, err| {
+            if budget.try_withdraw() {
+                wait(clock, op, dur, err)
+            } else {
+                Err(Halt::BudgetExhausted)
+            }
+        },
+    )
+}
+
+/// Retry a function, using the error of each failed attempt to pick the next
+/// delay. Return `RetryWith` from the function to pass the error to the
+/// strategy, any `Iterator<Item = Duration>` can also be used and will ignore
+/// it
+///
+/// ```rust,no_run
+/// # use std::time::Duration;
+/// use retry_fn::{retry_strategy, strategy::RetryStrategy, RetryResult};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// struct Busy {
+///     retry_after: Duration,
+/// }
+///
+/// struct RetryAfter;
+///
+/// impl RetryStrategy<Busy> for RetryAfter {
+///     fn delay(&mut self, attempt: usize, err: Option<&Busy>) -> Option<Duration> {
+///         if attempt >= 3 {
+///             None
+///         } else {
+///             err.map(|e| e.retry_after)
+///         }
+///     }
+/// }
+///
+/// let res = retry_strategy(RetryAfter, |op| {
+///     if op.retries >= 2 {
+///         RetryResult::Ok(5)
+///     } else {
+///         RetryResult::RetryWith(Busy {
+///             retry_after: Duration::from_millis(100),
+///         })
+///     }
+/// });
+/// assert_eq!(res.ok(), Some(5));
+/// Ok(())
+/// # }
+/// ```
+///
+/// # Returns
+/// If successful, return `Ok`, otherwise return `Retry` or `RetryWith` to try
+/// again or `Err` to exit with an error. When the strategy ends after a
+/// `RetryWith`, that error is returned as a `FailedAttempt`
+#[cfg(feature = "std")]
+pub fn retry_strategy<S, F, T, E>(strategy: S, mut f: F) -> Result<T, RetryErr<E>>
+where
+    S: RetryStrategy<E>,
+    F: FnMut(RetryOp) -> RetryResult<T, E>,
+{
+    clock_loop(
+        SystemClock,
+        Duration::from_millis(0),
+        Strategy(strategy),
+        |op, _| f(op),
+        wait,
+    )
+}
+
+/// Retry a function returning a plain `Result` with the process-wide default
+/// policy, see [`set_default_policy`]. Errors the policy doesn't classify as
+/// retryable are returned right away
+///
+/// ```rust,no_run
+/// # use std::io;
+/// use retry_fn::{retry_default, set_default_policy, strategy::Constant, DefaultPolicy};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// // once, at startup
+/// set_default_policy(DefaultPolicy::new(|| Constant::from_millis(500).take(5)));
+///
+/// let mut count = 0;
+/// let res = retry_default(|_| {
+///     count += 1;
+///     if count < 3 {
+///         Err(io::Error::new(io::ErrorKind::TimedOut, "timed out"))
+///     } else {
+///         Ok(count)
+///     }
+/// });
+/// assert_eq!(res.ok(), Some(3));
+/// Ok(())
+/// # }
+/// ```
+///
+/// # Returns
+/// The first `Ok`, otherwise the last error as a `FailedAttempt` once an
+/// error isn't retryable or the strategy ends
+#[cfg(feature = "std")]
+pub fn retry_default<F, T, E>(f: F) -> Result<T, RetryErr<E>>
+where
+    F: FnMut(RetryOp) -> Result<T, E>,
+    E: Error + 'static,
+{
+    let policy = default_policy();
+    retry_if(policy.delays(), f, |e| policy.is_retryable(e))
+}
+
+/// Retry a function returning a plain `Result`, using `predicate` to decide
+/// which errors are worth retrying. Errors it accepts are passed to the
+/// strategy like `RetryWith`, any other error is returned right away
+///
+/// ```rust,no_run
+/// # use std::io;
+/// use retry_fn::{retry_if, strategy::Constant};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut count = 0;
+/// let res = retry_if(
+///     Constant::from_millis(100).take(5),
+///     |_| {
+///         count += 1;
+///         if count < 3 {
+///             Err(io::Error::new(io::ErrorKind::TimedOut, "timed out"))
+///         } else {
+///             Ok(count)
+///         }
+///     },
+///     |e| e.kind() == io::ErrorKind::TimedOut,
+/// );
+/// assert_eq!(res.ok(), Some(3));
+/// Ok(())
+/// # }
+/// ```
+///
+/// # Returns
+/// The first `Ok`, otherwise the last error as a `FailedAttempt` once an
+/// error isn't retryable or the strategy ends
+#[cfg(feature = "std")]
+pub fn retry_if<S, F, P, T, E>(strategy: S, mut f: F, mut predicate: P) -> Result<T, RetryErr<E>>
+where
+    S: RetryStrategy<E>,
+    F: FnMut(RetryOp) -> Result<T, E>,
+    P: FnMut(&E) -> bool,
+{
+    retry_strategy(strategy, |op| match f(op) {
+        Ok(val) => RetryResult::Ok(val),
+        Err(err) if predicate(&err) => RetryResult::RetryWith(err),
+        Err(err) => RetryResult::Err(err),
+    })
+}
+
+/// Retry a function returning a plain `Result`, retrying on any error until
+/// the strategy ends
+///
+/// ```rust,no_run
+/// # use std::io;
+/// use retry_fn::{retry_result, strategy::Constant};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let res = retry_result(Constant::from_millis(100).take(3), |op| {
+///     if op.retries < 2 {
+///         Err(io::Error::new(io::ErrorKind::Other, "failed"))
+///     } else {
+///         Ok(op.retries)
+///     }
+/// });
+/// assert_eq!(res.ok(), Some(2));
+/// Ok(())
+/// # }
+/// ```
+///
+/// # Returns
+/// The first `Ok`, otherwise the last error as a `FailedAttempt` once the
+/// strategy ends
+#[cfg(feature = "std")]
+pub fn retry_result<S, F, T, E>(strategy: S, f: F) -> Result<T, RetryErr<E>>
+where
+    S: RetryStrategy<E>,
+    F: FnMut(RetryOp) -> Result<T, E>,
+{
+    retry_if(strategy, f, |_| true)
+}
+
+/// Poll `condition` on some time interval until it returns `true`
+///
+/// ```rust,no_run
+/// # use std::path::Path;
+/// use retry_fn::{strategy::Constant, wait_until};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// wait_until(Constant::from_millis(100).take(50), || Path::new("ready").exists())?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Returns
+/// `Ok` once `condition` is `true`, otherwise `IteratorEnded` once the
+/// strategy ends
+#[cfg(feature = "std")]
+pub fn wait_until<I, F>(iter: I, mut condition: F) -> Result<(), RetryErr<Infallible>>
+where
+    I: IntoIterator<Item = Duration>,
+    F: FnMut() -> bool,
+{
+    retry(iter, |_| {
+        if condition() {
+            RetryResult::Ok(())
+        } else {
+            RetryResult::Retry()
+        }
+    })
+}
+
+/// Poll `f` on some time interval until it returns `Some`, for APIs that
+/// signal "not ready yet" with `None`
+///
+/// ```rust,no_run
+/// # use std::sync::mpsc;
+/// use retry_fn::{poll_until, strategy::Constant};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let (tx, rx) = mpsc::channel();
+/// # tx.send(1)?;
+/// let msg = poll_until(Constant::from_millis(100).take(50), || rx.try_recv().ok())?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Returns
+/// The first `Some` value, otherwise `IteratorEnded` once the strategy ends
+#[cfg(feature = "std")]
+pub fn poll_until<I, F, T>(iter: I, mut f: F) -> Result<T, RetryErr<Infallible>>
+where
+    I: IntoIterator<Item = Duration>,
+    F: FnMut() -> Option<T>,
+{
+    retry(iter, |_| RetryResult::ok_or_retry(f()))
+}
+
+/// Retry a function returning a plain `Result` whose error implements
+/// [`Retryable`]. Errors that are retryable are passed to the strategy like
+/// `RetryWith`, and their `retry_hint`, if any, replaces the strategy's delay.
+/// Any other error is returned right away
+///
+/// ```rust,no_run
+/// # use std::io;
+/// use retry_fn::{retry_transient, strategy::ExponentialBackoff};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut count = 0;
+/// let res = retry_transient(ExponentialBackoff::from_millis(100).take(5), |_| {
+///     count += 1;
+///     if count < 3 {
+///         Err(io::Error::new(io::ErrorKind::ConnectionReset, "reset"))
+///     } else {
+///         Ok(count)
+///     }
+/// });
+/// assert_eq!(res.ok(), Some(3));
+/// Ok(())
+/// # }
+/// ```
+///
+/// # Returns
+/// The first `Ok`, otherwise the last error as a `FailedAttempt` once an
+/// error isn't retryable or the strategy ends
+#[cfg(feature = "std")]
+pub fn retry_transient<S, F, T, E>(strategy: S, mut f: F) -> Result<T, RetryErr<E>>
+where
+    S: RetryStrategy<E>,
+    F: FnMut(RetryOp) -> Result<T, E>,
+    E: Retryable,
+{
+    retry_strategy(retryable::Hinted(strategy), |op| match f(op) {
+        Ok(val) => RetryResult::Ok(val),
+        Err(err) if err.is_retryable() => RetryResult::RetryWith(err),
+        Err(err) => RetryResult::Err(err),
+    })
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use crate::RetryResult;
+
+    use super::*;
+    use crate::strategy::*;
+
+    use std::{io, thread};
+
+    #[test]
     fn fail_on_three() -> io::Result<()> {
         let mut count = 0;
         let res = retry(Constant::from_millis(100), |op| {
@@ -267,4 +1989,570 @@ mod test {
 
         Ok(())
     }
+
+    #[test]
+    fn initial_delay() {
+        let mut first = None;
+        let res = retry_delayed(Duration::from_millis(50), Constant::from_millis(10), |op| {
+            first.get_or_insert(op.total_delay);
+            if op.retries >= 2 {
+                RetryResult::<Duration, &str>::Ok(op.total_delay)
+            } else {
+                RetryResult::Retry()
+            }
+        });
+        assert_eq!(first, Some(Duration::from_millis(50)));
+        assert_eq!(res.ok(), Some(Duration::from_millis(70)));
+    }
+
+    #[test]
+    fn fixed_rate() {
+        let start = Instant::now();
+        let res = retry_fixed_rate(Constant::from_millis(50), |op| {
+            thread::sleep(Duration::from_millis(30));
+            if op.retries >= 2 {
+                RetryResult::<Duration, &str>::Ok(op.total_delay)
+            } else {
+                RetryResult::Retry()
+            }
+        });
+        let slept = res.unwrap();
+        assert!(slept < Duration::from_millis(100));
+        assert!(start.elapsed() >= Duration::from_millis(130));
+    }
+
+    #[test]
+    fn retry_if_predicate() {
+        let mut count = 0;
+        let res = retry_if(
+            Constant::from_millis(10).take(5),
+            |_| {
+                count += 1;
+                if count < 3 {
+                    Err::<(), _>("busy")
+                } else {
+                    Err("fatal")
+                }
+            },
+            |e| *e == "busy",
+        );
+        assert_eq!(count, 3);
+        assert!(matches!(
+            res,
+            Err(RetryErr::FailedAttempt {
+                tries: 2,
+                err: "fatal",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn result_last_error() {
+        let res = retry_result(Constant::from_millis(10).take(3), |op| {
+            Err::<(), _>(op.retries)
+        });
+        assert!(matches!(
+            res,
+            Err(RetryErr::FailedAttempt {
+                tries: 2,
+                err: 2,
+                ..
+            })
+        ));
+    }
+
+    #[derive(Debug, PartialEq)]
+    enum Flaky {
+        Busy(Duration),
+        Down,
+        Gone,
+    }
+
+    impl Retryable for Flaky {
+        fn is_retryable(&self) -> bool {
+            *self != Flaky::Gone
+        }
+
+        fn retry_hint(&self) -> Option<Duration> {
+            match self {
+                Flaky::Busy(d) => Some(*d),
+                _ => None,
+            }
+        }
+    }
+
+    #[test]
+    fn transient_errors() {
+        let mut errs = vec![
+            Flaky::Gone,
+            Flaky::Down,
+            Flaky::Busy(Duration::from_millis(30)),
+        ];
+        let res = retry_transient(Constant::from_millis(10), |_| {
+            Err::<(), _>(errs.pop().unwrap())
+        });
+        match res {
+            Err(RetryErr::FailedAttempt {
+                tries,
+                total_delay,
+                err,
+            }) => {
+                assert_eq!(tries, 2);
+                assert_eq!(total_delay, Duration::from_millis(40));
+                assert_eq!(err, Flaky::Gone);
+            }
+            _ => panic!("expected FailedAttempt"),
+        }
+    }
+
+    #[test]
+    fn combinators() {
+        let res = RetryResult::<u8, &str>::Ok(1).map(|n| n + 1);
+        assert!(res.is_ok());
+        assert_eq!(res.ok(), Some(2));
+
+        let res = RetryResult::<u8, &str>::RetryWith("busy").map_err(str::len);
+        assert!(res.is_retry() && !res.is_err());
+        assert_eq!(res.err(), Some(4));
+
+        let res = RetryResult::<u8, &str>::Err("fatal").retry_if(|e| *e == "busy");
+        assert!(res.is_err());
+        let res = RetryResult::<u8, &str>::Retry().and_then(|n| RetryResult::Ok(n + 1));
+        assert!(res.is_retry());
+        assert_eq!(res.err(), None);
+    }
+
+    #[test]
+    fn try_retry_macro() {
+        let mut inputs = vec!["fatal", "7", "busy", "x"];
+        let res = retry_strategy(Constant::from_millis(10), |_| {
+            let input = inputs.pop().unwrap();
+            let n: u8 = try_retry!(input.parse::<u8>().map_err(|_| input), |e: &&str| {
+                *e != "fatal"
+            });
+            RetryResult::Ok(n)
+        });
+        assert!(matches!(res, Ok(7)));
+
+        let res = retry_strategy(Constant::from_millis(10).take(2), |_| {
+            try_retry!(Err::<(), _>(io::Error::from(io::ErrorKind::TimedOut)));
+            RetryResult::<(), Box<dyn std::error::Error>>::Ok(())
+        });
+        assert!(matches!(res, Err(RetryErr::FailedAttempt { tries: 1, .. })));
+    }
+
+    #[test]
+    fn conversions() {
+        let mut polls = vec![Some(5), None, None];
+        let res: Result<_, RetryErr<()>> =
+            retry(Constant::from_millis(10), |_| polls.pop().unwrap().into());
+        assert_eq!(res.ok(), Some(5));
+
+        let res = retry(Constant::from_millis(10), |op| {
+            if op.retries < 2 {
+                RetryResult::<(), _>::retry_on_err(Err("busy"))
+            } else {
+                Err("fatal").into()
+            }
+        });
+        assert!(matches!(
+            res,
+            Err(RetryErr::FailedAttempt {
+                tries: 2,
+                err: "fatal",
+                ..
+            })
+        ));
+        assert!(matches!(
+            RetryResult::<_, ()>::from(Ok(1)),
+            RetryResult::Ok(1)
+        ));
+    }
+
+    #[test]
+    fn ended_with_error() {
+        let res = retry(Constant::from_millis(10).take(3), |op| {
+            RetryResult::<(), _>::RetryWith(op.retries)
+        });
+        assert!(matches!(
+            res,
+            Err(RetryErr::FailedAttempt {
+                tries: 2,
+                err: 2,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn control_flow() {
+        let res = retry_flow(Constant::from_millis(10).take(5), |op| {
+            if op.retries < 2 {
+                ControlFlow::Continue(())
+            } else {
+                ControlFlow::Break(Err::<(), _>(op.retries))
+            }
+        });
+        assert!(matches!(
+            res,
+            Err(RetryErr::FailedAttempt {
+                tries: 2,
+                err: 2,
+                ..
+            })
+        ));
+        let flow: ControlFlow<Result<(), &str>> = RetryResult::RetryWith("busy").into();
+        assert_eq!(flow, ControlFlow::Continue(()));
+    }
+
+    #[test]
+    fn elapsed() {
+        let res = retry(Constant::from_millis(10), |op| {
+            if op.retries == 0 {
+                assert_eq!(op.last_attempt, Duration::from_millis(0));
+                std::thread::sleep(Duration::from_millis(20));
+                RetryResult::<_, ()>::Retry()
+            } else {
+                RetryResult::Ok(op)
+            }
+        });
+        let op = res.unwrap();
+        assert!(op.last_attempt >= Duration::from_millis(20));
+        assert!(op.total_elapsed >= op.last_attempt + op.total_delay);
+    }
+
+    #[test]
+    fn started() {
+        let start = Instant::now();
+        let res = retry(Constant::from_millis(1).take(2), |op| {
+            assert!(op.started() >= start && op.started() <= Instant::now());
+            RetryResult::<(), ()>::Retry()
+        });
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn deadline_checked_after_attempt() {
+        let deadline = Instant::now() + Duration::from_millis(150);
+        let mut starts = Vec::new();
+        let res = retry(Deadline::new(Constant::from_millis(100), deadline), |op| {
+            starts.push(op.total_elapsed);
+            thread::sleep(Duration::from_millis(100));
+            RetryResult::<(), ()>::Retry()
+        });
+        assert!(res.is_err());
+        // the second attempt starts at the deadline, not a full delay after
+        // the first one ended
+        assert_eq!(starts.len(), 2);
+        assert!(starts[1] < Duration::from_millis(190));
+    }
+
+    #[test]
+    fn token_bucket_untouched_on_success() {
+        let mut bucket = TokenBucket::new(Immediate, 3, Duration::from_secs(60));
+        for _ in 0..3 {
+            let res = retry(bucket.by_ref(), |_| RetryResult::<_, ()>::Ok(()));
+            assert!(res.is_ok());
+        }
+        assert_eq!(bucket.tokens(), 3);
+    }
+
+    #[test]
+    fn notify_each_retry() {
+        let mut seen = Vec::new();
+        let res = retry_notify(
+            Constant::from_millis(10).take(3),
+            |op| {
+                if op.retries == 0 {
+                    RetryResult::<(), _>::Retry()
+                } else {
+                    RetryResult::RetryWith("busy")
+                }
+            },
+            |op, delay, err| seen.push((op.retries, delay, err.copied())),
+        );
+        assert!(matches!(
+            res,
+            Err(RetryErr::FailedAttempt { err: "busy", .. })
+        ));
+        assert_eq!(
+            seen,
+            vec![
+                (0, Duration::from_millis(10), None),
+                (1, Duration::from_millis(10), Some("busy")),
+            ]
+        );
+    }
+
+    #[test]
+    fn last_error() {
+        let mut seen = Vec::new();
+        let res = retry_with_last_error(Constant::from_millis(1).take(3), |op, last| {
+            seen.push(last.copied());
+            if op.retries == 1 {
+                RetryResult::<(), _>::Retry()
+            } else {
+                RetryResult::RetryWith(op.retries)
+            }
+        });
+        assert_eq!(seen, vec![None, Some(0), None]);
+        assert!(matches!(
+            res,
+            Err(RetryErr::FailedAttempt {
+                tries: 2,
+                err: 2,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn stats() {
+        let (val, stats) = retry_with_stats(Constant::from_millis(5), |op| {
+            if op.retries >= 2 {
+                RetryResult::<_, ()>::Ok(op.retries)
+            } else {
+                RetryResult::Retry()
+            }
+        })
+        .unwrap();
+        assert_eq!(val, 2);
+        assert_eq!(stats.attempts, 3);
+        assert_eq!(stats.total_delay, Duration::from_millis(10));
+        assert!(stats.total_elapsed >= stats.total_delay);
+    }
+
+    #[test]
+    fn collecting() {
+        let res = retry_collecting(Constant::from_millis(1).take(3), |op| match op.retries {
+            0 => RetryResult::<(), _>::RetryWith("busy"),
+            1 => RetryResult::Retry(),
+            _ => RetryResult::Err("fatal"),
+        });
+        assert!(matches!(
+            res,
+            Err(RetryErr::FailedAttempt { tries: 2, err, .. }) if err == vec!["busy", "fatal"]
+        ));
+        let res = retry_collecting(Constant::from_millis(1).take(2), |_| {
+            RetryResult::<(), ()>::Retry()
+        });
+        assert!(matches!(res, Err(RetryErr::IteratorEnded { tries: 1, .. })));
+    }
+
+    #[test]
+    fn retry_macro() {
+        fn flaky(n: &mut usize) -> Result<usize, &'static str> {
+            *n += 1;
+            if *n < 3 {
+                Err("busy")
+            } else {
+                Ok(*n)
+            }
+        }
+        let mut n = 0;
+        let res: Result<_, RetryErr<&str>> =
+            crate::retry!(Constant::from_millis(1).take(5), flaky(&mut n)? * 10);
+        assert_eq!(res.ok(), Some(30));
+        let res: Result<(), RetryErr<&str>> = crate::retry!(Constant::from_millis(1).take(2), {
+            Err("busy")?;
+        });
+        assert!(matches!(
+            res,
+            Err(RetryErr::FailedAttempt {
+                tries: 1,
+                err: "busy",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn wait_for_condition() {
+        let mut polls = 0;
+        let res = wait_until(Constant::from_millis(1).take(5), || {
+            polls += 1;
+            polls == 3
+        });
+        assert!(res.is_ok());
+        assert_eq!(polls, 3);
+        let res = wait_until(Constant::from_millis(1).take(3), || false);
+        assert!(matches!(res, Err(RetryErr::IteratorEnded { tries: 2, .. })));
+    }
+
+    #[test]
+    fn poll_for_value() {
+        let mut polls = 0;
+        let res = poll_until(Constant::from_millis(1).take(5), || {
+            polls += 1;
+            if polls == 3 {
+                Some(polls * 10)
+            } else {
+                None
+            }
+        });
+        assert_eq!(res.ok(), Some(30));
+        let res = poll_until(Constant::from_millis(1).take(3), || None::<()>);
+        assert!(matches!(res, Err(RetryErr::IteratorEnded { tries: 2, .. })));
+    }
+
+    #[test]
+    fn context() {
+        let mut buf = String::new();
+        let res = retry_with_context(Constant::from_millis(1).take(3), &mut buf, |buf, op| {
+            buf.push_str("try ");
+            if op.retries < 2 {
+                RetryResult::<_, ()>::Retry()
+            } else {
+                RetryResult::Ok(buf.len())
+            }
+        });
+        assert_eq!(res.ok(), Some(12));
+        assert_eq!(buf, "try try try ");
+    }
+
+    #[test]
+    fn breaker_short_circuits() {
+        let breaker = circuit::CircuitBreaker::new(1, Duration::from_secs(60));
+        let mut calls = 0;
+        let res = retry_with_breaker(&breaker, Constant::from_millis(1).take(4), |_| {
+            calls += 1;
+            RetryResult::<(), _>::RetryWith("down")
+        });
+        assert_eq!(calls, 1);
+        assert!(matches!(res, Err(RetryErr::CircuitOpen { tries: 3, .. })));
+    }
+
+    #[test]
+    fn bulkhead_full() {
+        let bulkhead = Bulkhead::new(1);
+        let held = bulkhead.try_acquire();
+        let res = retry_with_bulkhead(&bulkhead, Constant::from_millis(1).take(3), |_| {
+            RetryResult::<(), ()>::Ok(())
+        });
+        assert!(matches!(res, Err(RetryErr::BulkheadFull { tries: 2, .. })));
+        drop(held);
+        let res = retry_with_bulkhead(&bulkhead, Constant::from_millis(1).take(2), |op| {
+            assert_eq!(bulkhead.available(), 0);
+            if op.retries < 1 {
+                RetryResult::Retry()
+            } else {
+                RetryResult::<_, ()>::Ok(op.retries)
+            }
+        });
+        assert_eq!(res.ok(), Some(1));
+        assert_eq!(bulkhead.available(), 1);
+    }
+
+    #[test]
+    fn cancelled_before_attempt() {
+        let token = CancelToken::new();
+        token.cancel();
+        let mut calls = 0;
+        let res = retry_cancellable(&token, Constant::from_millis(10), |_| {
+            calls += 1;
+            RetryResult::<(), &str>::Retry()
+        });
+        assert_eq!(calls, 0);
+        assert!(matches!(res, Err(RetryErr::Cancelled { tries: 0, .. })));
+    }
+
+    #[test]
+    fn deadline_truncates() {
+        let deadline = Instant::now() + Duration::from_millis(50);
+        let mut calls = 0;
+        let res = retry_with_deadline(Constant::from_millis(40), deadline, |_| {
+            calls += 1;
+            RetryResult::<(), &str>::Retry()
+        });
+        assert!(Instant::now() >= deadline);
+        assert_eq!(calls, 3);
+        match res {
+            Err(RetryErr::DeadlineExceeded { tries, total_delay }) => {
+                assert_eq!(tries, 2);
+                assert!(total_delay <= Duration::from_millis(50));
+            }
+            _ => panic!("expected DeadlineExceeded"),
+        }
+    }
+
+    #[test]
+    fn times_limits_attempts() {
+        let mut count = 0;
+        let res = retry_times(Constant::from_millis(1), 4, |_| {
+            count += 1;
+            RetryResult::<(), &str>::Retry()
+        });
+        assert_eq!(count, 4);
+        assert!(matches!(res, Err(RetryErr::IteratorEnded { tries: 3, .. })));
+    }
+
+    #[test]
+    fn retry_n_fixed_delay() {
+        let res = retry_n(3, Duration::from_millis(1), |op| {
+            RetryResult::<(), _>::RetryWith(op.total_delay)
+        });
+        assert!(matches!(
+            res,
+            Err(RetryErr::FailedAttempt { tries: 2, err, .. }) if err == Duration::from_millis(2)
+        ));
+    }
+
+    #[test]
+    fn budget_exhausted() {
+        let budget = RetryBudget::new(Duration::from_secs(60), 0, 1.0);
+        let mut count = 0;
+        let res = retry_with_budget(&budget, Constant::from_millis(10), |_| {
+            count += 1;
+            RetryResult::<(), &str>::Retry()
+        });
+        assert_eq!(count, 2);
+        assert!(matches!(
+            res,
+            Err(RetryErr::BudgetExhausted { tries: 1, .. })
+        ));
+        assert_eq!(budget.balance(), 0);
+    }
+
+    struct Hinted;
+
+    impl RetryStrategy<Duration> for Hinted {
+        fn delay(&mut self, attempt: usize, err: Option<&Duration>) -> Option<Duration> {
+            if attempt >= 2 {
+                None
+            } else {
+                err.copied()
+            }
+        }
+    }
+
+    #[test]
+    fn strategy_uses_err() {
+        let res = retry_strategy(Hinted, |_| {
+            RetryResult::<(), _>::RetryWith(Duration::from_millis(10))
+        });
+        match res {
+            Err(RetryErr::FailedAttempt {
+                tries,
+                total_delay,
+                err,
+            }) => {
+                assert_eq!(tries, 2);
+                assert_eq!(total_delay, Duration::from_millis(20));
+                assert_eq!(err, Duration::from_millis(10));
+            }
+            _ => panic!("expected FailedAttempt"),
+        }
+    }
+
+    #[test]
+    fn strategy_from_iter() {
+        let mut count = 0;
+        let res = retry_strategy(Constant::from_millis(10).take(3), |_| {
+            count += 1;
+            RetryResult::<(), &str>::Retry()
+        });
+        assert_eq!(count, 3);
+        assert!(matches!(res, Err(RetryErr::IteratorEnded { tries: 2, .. })));
+    }
 }
@@ -0,0 +1,132 @@
+//! lock acquisition
+//!
+//! Retry `try_lock`-style acquisitions with a strategy instead of a hand
+//! written busy loop. Only contention is retried, a poisoned lock or any other
+//! error is returned right away, and once the strategy ends the acquisition
+//! gives up with `IteratorEnded`, so limit the strategy to how long you're
+//! willing to wait
+//!
+//! ```rust,no_run
+//! # use std::{sync::Mutex, time::Duration};
+//! use retry_fn::{lock, strategy::{ExponentialBackoff, StrategyExt}};
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let counter = Mutex::new(0);
+//! let strategy = ExponentialBackoff::from_micros(50)
+//!     .max_delay(Duration::from_millis(10))
+//!     .max_total_delay(Duration::from_secs(1));
+//! let mut guard = lock::lock(&counter, strategy).map_err(|e| e.to_string())?;
+//! *guard += 1;
+//! # Ok(())
+//! # }
+//! ```
+use crate::{retry, RetryErr, RetryResult};
+use std::{
+    io,
+    sync::{
+        Mutex, MutexGuard, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard, TryLockError,
+        TryLockResult,
+    },
+    time::Duration,
+};
+
+/// Lock `mutex` with `Mutex::try_lock`, retrying while it's held elsewhere
+pub fn lock<'a, T, I>(
+    mutex: &'a Mutex<T>,
+    iter: I,
+) -> Result<MutexGuard<'a, T>, RetryErr<PoisonError<MutexGuard<'a, T>>>>
+where
+    T: ?Sized,
+    I: IntoIterator<Item = Duration>,
+{
+    retry(iter, |_| contended(mutex.try_lock()))
+}
+
+/// Lock `rwlock` for reading with `RwLock::try_read`, retrying while it's
+/// held for writing elsewhere
+pub fn read<'a, T, I>(
+    rwlock: &'a RwLock<T>,
+    iter: I,
+) -> Result<RwLockReadGuard<'a, T>, RetryErr<PoisonError<RwLockReadGuard<'a, T>>>>
+where
+    T: ?Sized,
+    I: IntoIterator<Item = Duration>,
+{
+    retry(iter, |_| contended(rwlock.try_read()))
+}
+
+/// Lock `rwlock` for writing with `RwLock::try_write`, retrying while it's
+/// held elsewhere
+pub fn write<'a, T, I>(
+    rwlock: &'a RwLock<T>,
+    iter: I,
+) -> Result<RwLockWriteGuard<'a, T>, RetryErr<PoisonError<RwLockWriteGuard<'a, T>>>>
+where
+    T: ?Sized,
+    I: IntoIterator<Item = Duration>,
+{
+    retry(iter, |_| contended(rwlock.try_write()))
+}
+
+/// Acquire a resource with `f`, retrying while it fails with
+/// `io::ErrorKind::WouldBlock`. This is how most file locks report
+/// contention, ex. a closure calling `try_lock_exclusive` on a `File`
+pub fn acquire<I, F, G>(iter: I, mut f: F) -> Result<G, RetryErr<io::Error>>
+where
+    I: IntoIterator<Item = Duration>,
+    F: FnMut() -> io::Result<G>,
+{
+    retry(iter, |_| match f() {
+        Ok(guard) => RetryResult::Ok(guard),
+        Err(err) if err.kind() == io::ErrorKind::WouldBlock => RetryResult::Retry(),
+        Err(err) => RetryResult::Err(err),
+    })
+}
+
+/// retry `WouldBlock`, fail on poison
+fn contended<G>(res: TryLockResult<G>) -> RetryResult<G, PoisonError<G>> {
+    match res {
+        Ok(guard) => RetryResult::Ok(guard),
+        Err(TryLockError::WouldBlock) => RetryResult::Retry(),
+        Err(TryLockError::Poisoned(err)) => RetryResult::Err(err),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::strategy::Constant;
+    use std::{sync::Arc, thread};
+
+    #[test]
+    fn waits_for_release() {
+        let mutex = Arc::new(Mutex::new(0));
+        let guard = mutex.lock().unwrap();
+        let other = mutex.clone();
+        let handle = thread::spawn(move || {
+            *lock(&other, Constant::from_millis(5).take(200)).unwrap() += 1;
+        });
+        thread::sleep(Duration::from_millis(20));
+        drop(guard);
+        handle.join().unwrap();
+        assert_eq!(*mutex.lock().unwrap(), 1);
+    }
+
+    #[test]
+    fn gives_up() {
+        let rwlock = RwLock::new(());
+        let _guard = rwlock.write().unwrap();
+        assert!(matches!(
+            read(&rwlock, Constant::from_millis(1).take(2)),
+            Err(RetryErr::IteratorEnded { tries: 1, .. })
+        ));
+        let mut calls = 0;
+        let res = acquire(Constant::from_millis(1).take(5), || {
+            calls += 1;
+            match calls {
+                1 => Err::<(), _>(io::Error::from(io::ErrorKind::WouldBlock)),
+                _ => Err(io::Error::from(io::ErrorKind::PermissionDenied)),
+            }
+        });
+        assert!(matches!(res, Err(RetryErr::FailedAttempt { tries: 1, .. })));
+    }
+}
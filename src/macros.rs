@@ -1,12 +1,22 @@
 macro_rules! retry_impl {
-    ($time:expr) => {
-        use crate::{RetryErr, RetryOp, RetryResult};
-        use std::{future::Future, time::Duration};
+    ($sleeper:ty) => {
+        use crate::{
+            runtime::{RetryFuture, Runtime},
+            strategy::RetryStrategy,
+            Instant, RetryBudget, RetryErr, RetryOp, RetryResult, RetryStats, Retryable,
+        };
+        use std::{
+            convert::Infallible, error::Error, future::Future, ops::ControlFlow, time::Duration,
+        };
 
         /// Retry a future based on an iterator over Duration. A timer will be run for
-        /// each item in the iterator.
+        /// each item in the iterator. The returned [`RetryFuture`] can be named,
+        /// so it can be stored in a struct without boxing
         ///
-        /// ```rust,no_run
+        // the examples here use `retry_fn::tokio`, so they only build with
+        // `tokio-runtime` and are shown without being tested otherwise
+        #[cfg_attr(feature = "tokio-runtime", doc = "```rust,no_run")]
+        #[cfg_attr(not(feature = "tokio-runtime"), doc = "```rust,ignore")]
         /// # use std::{io, sync::{Arc, Mutex}};
         /// use retry_fn::{strategy::Constant, RetryResult};
         /// # use retry_fn::tokio::retry;
@@ -38,42 +48,487 @@ macro_rules! retry_impl {
         /// # Returns
         /// If successful, return `Ok`, otherwise return `Retry` to try again or `Err`
         /// to exit with an error
-        pub async fn retry<I, F, Fut, T, E>(iter: I, mut f: F) -> Result<T, RetryErr<E>>
-        where
-            I: IntoIterator<Item = Duration>,
-            F: FnMut(RetryOp) -> Fut,
-            Fut: Future<Output = RetryResult<T, E>>,
-        {
-            let mut count = 0;
-            let mut total_delay = Duration::from_millis(0);
-            for dur in iter.into_iter() {
-                match f(RetryOp {
-                    retries: count,
-                    total_delay,
-                })
-                .await
-                {
-                    RetryResult::Retry() => {
-                        $time(dur).await;
-                        total_delay += dur;
-                        count += 1;
-                    }
-                    RetryResult::Err(err) => {
-                        return Err(RetryErr::FailedAttempt {
-                            tries: count,
-                            total_delay,
-                            err,
-                        });
-                    }
-                    RetryResult::Ok(val) => {
-                        return Ok(val);
-                    }
-                }
-            }
-            Err(RetryErr::IteratorEnded {
-                tries: count,
-                total_delay,
-            })
+        pub fn retry<I, F, Fut, T, E>(iter: I, f: F) -> RetryFuture<$sleeper, I::IntoIter, F, Fut>
+        where
+            I: IntoIterator<Item = Duration>,
+            F: FnMut(RetryOp) -> Fut,
+            Fut: Future<Output = RetryResult<T, E>>,
+        {
+            Runtime::<$sleeper>::retry(iter, f)
+        }
+
+        /// Retry a future based on an iterator over Duration as a stream with
+        /// an item for each attempt, so progress can be shown while it runs.
+        /// A failed attempt that will be retried is yielded before its delay
+        /// is waited. Enable the `stream` feature to use this
+        ///
+        #[cfg_attr(feature = "tokio-runtime", doc = "```rust,no_run")]
+        #[cfg_attr(not(feature = "tokio-runtime"), doc = "```rust,ignore")]
+        /// use futures::StreamExt;
+        /// use retry_fn::{strategy::Constant, Attempt, RetryResult};
+        /// # use retry_fn::tokio::retry_stream;
+        /// # async fn run() {
+        /// let attempts = retry_stream(Constant::from_millis(100).take(3), |op| async move {
+        ///     if op.retries >= 2 {
+        ///         RetryResult::Ok(op.retries)
+        ///     } else {
+        ///         RetryResult::RetryWith("busy")
+        ///     }
+        /// });
+        /// futures::pin_mut!(attempts);
+        /// while let Some(attempt) = attempts.next().await {
+        ///     if let Attempt::Retry { op, delay, err } = attempt {
+        ///         println!("attempt {} failed with {:?}, retrying in {:?}", op.retries, err, delay);
+        ///     }
+        /// }
+        /// # }
+        /// ```
+        ///
+        /// # Returns
+        /// A stream ending after the `Ok` or `Err` item
+        #[cfg(feature = "stream")]
+        pub fn retry_stream<I, F, Fut, T, E>(
+            iter: I,
+            f: F,
+        ) -> crate::runtime::AttemptStream<$sleeper, I::IntoIter, F, Fut>
+        where
+            I: IntoIterator<Item = Duration>,
+            F: FnMut(RetryOp) -> Fut,
+            Fut: Future<Output = RetryResult<T, E>>,
+        {
+            Runtime::<$sleeper>::retry_stream(iter, f)
+        }
+
+        /// Rebuild a stream with `factory` whenever it yields an error, waiting
+        /// for the delays of `iter` in between, and keep yielding its items.
+        /// `token` picks a resume token out of an item, and the latest one is
+        /// passed to `factory` so a new subscription can continue where the
+        /// old one stopped. Once the rebuilt stream yields an item the delays
+        /// start over. Enable the `stream` feature to use this
+        ///
+        #[cfg_attr(feature = "tokio-runtime", doc = "```rust,no_run")]
+        #[cfg_attr(not(feature = "tokio-runtime"), doc = "```rust,ignore")]
+        /// use futures::{stream, Stream, StreamExt};
+        /// use retry_fn::strategy::ExponentialBackoff;
+        /// # use retry_fn::tokio::resume_stream;
+        /// struct Event {
+        ///     id: u64,
+        /// }
+        ///
+        /// fn subscribe(after: Option<u64>) -> impl Stream<Item = Result<Event, std::io::Error>> {
+        ///     // connect, asking for the events after `after`
+        /// #   stream::empty()
+        /// }
+        ///
+        /// # async fn run() {
+        /// let events = resume_stream(
+        ///     ExponentialBackoff::from_millis(100).take(5),
+        ///     |event: &Event| Some(event.id),
+        ///     |last_id| subscribe(last_id.copied()),
+        /// );
+        /// futures::pin_mut!(events);
+        /// while let Some(event) = events.next().await {
+        ///     match event {
+        ///         Ok(event) => println!("event {}", event.id),
+        ///         Err(err) => eprintln!("gave up: {}", err),
+        ///     }
+        /// }
+        /// # }
+        /// ```
+        ///
+        /// # Returns
+        /// A stream of the items, ending when the inner stream ends or with the
+        /// last error as a `FailedAttempt` once the delays end
+        #[cfg(feature = "stream")]
+        pub fn resume_stream<I, C, K, F, St, T, E>(
+            iter: I,
+            token: C,
+            factory: F,
+        ) -> crate::runtime::ResumeStream<$sleeper, I::IntoIter, C, K, F, St>
+        where
+            I: IntoIterator<Item = Duration>,
+            I::IntoIter: Clone,
+            C: FnMut(&T) -> Option<K>,
+            F: FnMut(Option<&K>) -> St,
+            St: futures_core::Stream<Item = Result<T, E>>,
+        {
+            Runtime::<$sleeper>::resume_stream(iter, token, factory)
+        }
+
+        /// Wrap the sink built by `factory`, rebuilding it with the delays of
+        /// `iter` in between whenever sending or flushing fails and sending
+        /// the failed item again. Each item is flushed before the next is
+        /// accepted, so there's only ever one item to replay. Once an item
+        /// gets through the delays start over. Enable the `sink` feature to
+        /// use this
+        ///
+        #[cfg_attr(feature = "tokio-runtime", doc = "```rust,no_run")]
+        #[cfg_attr(not(feature = "tokio-runtime"), doc = "```rust,ignore")]
+        /// use futures::{sink, Sink, SinkExt};
+        /// use retry_fn::strategy::ExponentialBackoff;
+        /// # use retry_fn::tokio::retry_sink;
+        /// fn connect() -> impl Sink<String, Error = std::io::Error> {
+        ///     // open a connection to the log collector
+        /// #   sink::drain().sink_map_err(|e| match e {})
+        /// }
+        ///
+        /// # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+        /// let logs = retry_sink(ExponentialBackoff::from_millis(100).take(5), connect);
+        /// futures::pin_mut!(logs);
+        /// logs.send("started".to_owned()).await?;
+        /// # Ok(())
+        /// # }
+        /// ```
+        ///
+        /// # Returns
+        /// A sink failing with the last error as a `FailedAttempt` once the
+        /// delays end
+        #[cfg(feature = "sink")]
+        pub fn retry_sink<I, F, Si, T>(
+            iter: I,
+            factory: F,
+        ) -> crate::runtime::RetrySink<$sleeper, I::IntoIter, F, Si, T>
+        where
+            I: IntoIterator<Item = Duration>,
+            I::IntoIter: Clone,
+            F: FnMut() -> Si,
+            Si: futures_sink::Sink<T>,
+            T: Clone,
+        {
+            Runtime::<$sleeper>::retry_sink(iter, factory)
+        }
+
+        /// Retry a future based on an iterator over Duration, returning how
+        /// many attempts it took along with the value on success
+        ///
+        /// # Returns
+        /// If successful, return `Ok`, otherwise return `Retry` to try again or `Err`
+        /// to exit with an error
+        pub async fn retry_with_stats<I, F, Fut, T, E>(
+            iter: I,
+            f: F,
+        ) -> Result<(T, RetryStats), RetryErr<E>>
+        where
+            I: IntoIterator<Item = Duration>,
+            F: FnMut(RetryOp) -> Fut,
+            Fut: Future<Output = RetryResult<T, E>>,
+        {
+            Runtime::<$sleeper>::retry_with_stats(iter, f).await
+        }
+
+        /// Retry a future based on an iterator over Duration, calling `f` at
+        /// most `times` times however long the iterator is
+        ///
+        /// # Returns
+        /// If successful, return `Ok`, otherwise return `Retry` to try again or `Err`
+        /// to exit with an error
+        pub async fn retry_times<I, F, Fut, T, E>(
+            iter: I,
+            times: usize,
+            f: F,
+        ) -> Result<T, RetryErr<E>>
+        where
+            I: IntoIterator<Item = Duration>,
+            F: FnMut(RetryOp) -> Fut,
+            Fut: Future<Output = RetryResult<T, E>>,
+        {
+            Runtime::<$sleeper>::retry_times(iter, times, f).await
+        }
+
+        /// Retry a future based on an iterator over Duration, giving up on an
+        /// attempt once it has run for `per_attempt`. The attempt's future is
+        /// dropped and it's retried like `Retry`, so an attempt that hangs
+        /// doesn't stall the loop
+        ///
+        #[cfg_attr(feature = "tokio-runtime", doc = "```rust,no_run")]
+        #[cfg_attr(not(feature = "tokio-runtime"), doc = "```rust,ignore")]
+        /// # use std::time::Duration;
+        /// use retry_fn::{strategy::Constant, RetryErr, RetryResult};
+        /// # use retry_fn::tokio::retry_with_timeout;
+        /// # async fn run() {
+        /// let res = retry_with_timeout(
+        ///     Constant::from_millis(100).take(3),
+        ///     Duration::from_secs(1),
+        ///     |_| async {
+        ///         tokio::time::sleep(Duration::from_secs(60)).await;
+        ///         RetryResult::<(), ()>::Ok(())
+        ///     },
+        /// )
+        /// .await;
+        /// assert!(matches!(res, Err(RetryErr::TimedOut { tries: 2, .. })));
+        /// # }
+        /// ```
+        ///
+        /// # Returns
+        /// If successful, return `Ok`, otherwise return `Retry` to try again or `Err`
+        /// to exit with an error. If the last attempt timed out, returns
+        /// `TimedOut`
+        pub async fn retry_with_timeout<I, F, Fut, T, E>(
+            iter: I,
+            per_attempt: Duration,
+            f: F,
+        ) -> Result<T, RetryErr<E>>
+        where
+            I: IntoIterator<Item = Duration>,
+            F: FnMut(RetryOp) -> Fut,
+            Fut: Future<Output = RetryResult<T, E>>,
+        {
+            Runtime::<$sleeper>::retry_with_timeout(iter, per_attempt, f).await
+        }
+
+        /// Retry a future based on an iterator over Duration, starting another
+        /// attempt alongside once the running ones have taken `soft_timeout`
+        /// without finishing. Slow attempts keep running and the first to
+        /// succeed wins, which cuts the tail latency of idempotent calls.
+        /// Each extra attempt uses up a delay of the strategy without
+        /// waiting it, and once every running attempt has failed the loop
+        /// waits for the next delay as usual
+        ///
+        #[cfg_attr(feature = "tokio-runtime", doc = "```rust,no_run")]
+        #[cfg_attr(not(feature = "tokio-runtime"), doc = "```rust,ignore")]
+        /// # use std::time::Duration;
+        /// use retry_fn::{strategy::Constant, RetryResult};
+        /// # use retry_fn::tokio::retry_hedged;
+        /// # async fn call() -> Result<u32, ()> { Ok(1) }
+        /// # async fn run() {
+        /// let res = retry_hedged(
+        ///     Constant::from_millis(100).take(2),
+        ///     Duration::from_millis(50),
+        ///     |_| async { RetryResult::retry_on_err(call().await) },
+        /// )
+        /// .await;
+        /// # }
+        /// ```
+        ///
+        /// # Returns
+        /// If successful, return `Ok`, otherwise return `Retry` to try again or `Err`
+        /// to exit with an error. An `Err` from any attempt ends the loop
+        pub async fn retry_hedged<I, F, Fut, T, E>(
+            iter: I,
+            soft_timeout: Duration,
+            f: F,
+        ) -> Result<T, RetryErr<E>>
+        where
+            I: IntoIterator<Item = Duration>,
+            F: FnMut(RetryOp) -> Fut,
+            Fut: Future<Output = RetryResult<T, E>>,
+        {
+            Runtime::<$sleeper>::retry_hedged(iter, soft_timeout, f).await
+        }
+
+        /// Hedge a future: start another attempt after each delay of `iter`
+        /// without cancelling the ones still running, and finish with the
+        /// first to succeed, dropping the others. An attempt failing when
+        /// none are left running starts the next one right away. The delays
+        /// are how long to wait before each extra attempt, so a tail latency
+        /// like the p95 of the operation makes a good hedging delay
+        ///
+        #[cfg_attr(feature = "tokio-runtime", doc = "```rust,no_run")]
+        #[cfg_attr(not(feature = "tokio-runtime"), doc = "```rust,ignore")]
+        /// use retry_fn::{strategy::Constant, RetryResult};
+        /// # use retry_fn::tokio::hedge;
+        /// # async fn lookup(replica: usize) -> Result<String, std::io::Error> { Ok(String::new()) }
+        /// # async fn run() {
+        /// // ask another replica every 50ms, up to 3 in total
+        /// let res = hedge(Constant::from_millis(50).take(2), |op| async move {
+        ///     RetryResult::retry_on_err(lookup(op.retries).await)
+        /// })
+        /// .await;
+        /// # }
+        /// ```
+        ///
+        /// # Returns
+        /// If successful, return `Ok`, otherwise return `Retry` to try again or `Err`
+        /// to exit with an error. An `Err` from any attempt ends the loop
+        pub async fn hedge<I, F, Fut, T, E>(iter: I, f: F) -> Result<T, RetryErr<E>>
+        where
+            I: IntoIterator<Item = Duration>,
+            F: FnMut(RetryOp) -> Fut,
+            Fut: Future<Output = RetryResult<T, E>>,
+        {
+            Runtime::<$sleeper>::hedge(iter, f).await
+        }
+
+        /// Race up to `n` attempts of a future, starting them staggered by
+        /// the delays of `iter` as in `hedge`, and finish with the first to
+        /// succeed. The errors of every failed attempt are kept, in the order
+        /// the attempts failed. An `n` of 0 still runs one attempt
+        ///
+        #[cfg_attr(feature = "tokio-runtime", doc = "```rust,no_run")]
+        #[cfg_attr(not(feature = "tokio-runtime"), doc = "```rust,ignore")]
+        /// use retry_fn::{strategy::Constant, RetryErr, RetryResult};
+        /// # use retry_fn::tokio::retry_race;
+        /// # async fn lookup(replica: usize) -> Result<String, std::io::Error> { Ok(String::new()) }
+        /// # async fn run() {
+        /// // ask all 3 replicas, 10ms apart
+        /// match retry_race(3, Constant::from_millis(10), |op| async move {
+        ///     RetryResult::retry_on_err(lookup(op.retries).await)
+        /// })
+        /// .await
+        /// {
+        ///     Ok(val) => println!("{}", val),
+        ///     Err(RetryErr::FailedAttempt { err: errors, .. }) => eprintln!("all failed: {:?}", errors),
+        ///     Err(err) => eprintln!("{:?}", err),
+        /// }
+        /// # }
+        /// ```
+        ///
+        /// # Returns
+        /// If successful, return `Ok`, otherwise a `FailedAttempt` with the
+        /// errors of all attempts, or `IteratorEnded` if none of them returned
+        /// one. An `Err` from any attempt ends the race
+        pub async fn retry_race<I, F, Fut, T, E>(
+            n: usize,
+            iter: I,
+            f: F,
+        ) -> Result<T, RetryErr<Vec<E>>>
+        where
+            I: IntoIterator<Item = Duration>,
+            F: FnMut(RetryOp) -> Fut,
+            Fut: Future<Output = RetryResult<T, E>>,
+        {
+            Runtime::<$sleeper>::retry_race(n, iter, f).await
+        }
+
+        /// Retry a future through a shared
+        /// [`CircuitBreaker`](crate::circuit::CircuitBreaker), short-circuiting
+        /// attempts without running them while it's open. Any result other
+        /// than `Ok` is recorded as a failure. See
+        /// [`crate::retry_with_breaker`]
+        ///
+        /// # Returns
+        /// If successful, return `Ok`, otherwise return `Retry` to try again or `Err`
+        /// to exit with an error. If the strategy ends with the last attempt
+        /// short-circuited, returns `CircuitOpen`
+        pub async fn retry_with_breaker<I, F, Fut, T, E>(
+            breaker: &crate::circuit::CircuitBreaker,
+            iter: I,
+            f: F,
+        ) -> Result<T, RetryErr<E>>
+        where
+            I: IntoIterator<Item = Duration>,
+            F: FnMut(RetryOp) -> Fut,
+            Fut: Future<Output = RetryResult<T, E>>,
+        {
+            Runtime::<$sleeper>::retry_with_breaker(breaker, iter, f).await
+        }
+
+        /// Retry a future, running each attempt under a permit of a shared
+        /// [`Bulkhead`](crate::Bulkhead). An attempt that can't get a permit
+        /// isn't run, the loop waits for the next delay and tries again. See
+        /// [`crate::retry_with_bulkhead`]
+        ///
+        /// # Returns
+        /// If successful, return `Ok`, otherwise return `Retry` to try again or `Err`
+        /// to exit with an error. If the strategy ends without a permit for
+        /// the last attempt, returns `BulkheadFull`
+        pub async fn retry_with_bulkhead<I, F, Fut, T, E>(
+            bulkhead: &crate::Bulkhead,
+            iter: I,
+            f: F,
+        ) -> Result<T, RetryErr<E>>
+        where
+            I: IntoIterator<Item = Duration>,
+            F: FnMut(RetryOp) -> Fut,
+            Fut: Future<Output = RetryResult<T, E>>,
+        {
+            Runtime::<$sleeper>::retry_with_bulkhead(bulkhead, iter, f).await
+        }
+
+        /// Retry a future based on an iterator over Duration until `deadline`,
+        /// a hard limit on the whole loop however long the schedule is. A
+        /// delay that would wait past the deadline is cut short, and an
+        /// attempt still running at the deadline is dropped. See
+        /// [`crate::retry_with_deadline`]
+        ///
+        #[cfg_attr(feature = "tokio-runtime", doc = "```rust,no_run")]
+        #[cfg_attr(not(feature = "tokio-runtime"), doc = "```rust,ignore")]
+        /// # use std::time::{Duration, Instant};
+        /// use retry_fn::{strategy::ExponentialBackoff, RetryErr, RetryResult};
+        /// # use retry_fn::tokio::retry_with_deadline;
+        /// # async fn run() {
+        /// let deadline = Instant::now() + Duration::from_secs(5);
+        /// let res = retry_with_deadline(ExponentialBackoff::from_secs(1), deadline, |_| async {
+        ///     RetryResult::<(), &str>::Retry()
+        /// })
+        /// .await;
+        /// assert!(matches!(res, Err(RetryErr::DeadlineExceeded { .. })));
+        /// # }
+        /// ```
+        ///
+        /// # Returns
+        /// If successful, return `Ok`, otherwise return `Retry` to try again or `Err`
+        /// to exit with an error. Once the deadline has passed, returns
+        /// `DeadlineExceeded`
+        pub async fn retry_with_deadline<I, F, Fut, T, E>(
+            iter: I,
+            deadline: Instant,
+            f: F,
+        ) -> Result<T, RetryErr<E>>
+        where
+            I: IntoIterator<Item = Duration>,
+            F: FnMut(RetryOp) -> Fut,
+            Fut: Future<Output = RetryResult<T, E>>,
+        {
+            Runtime::<$sleeper>::retry_with_deadline(iter, deadline, f).await
+        }
+
+        /// Retry a future based on an iterator over Duration until `cancel`
+        /// completes. Each attempt and delay races `cancel`, so any future
+        /// works as a cancellation signal: a shutdown notification,
+        /// `ctrl_c()` or a channel closing. `total_delay` counts only the
+        /// delays that ran to completion
+        ///
+        #[cfg_attr(feature = "tokio-runtime", doc = "```rust,no_run")]
+        #[cfg_attr(not(feature = "tokio-runtime"), doc = "```rust,ignore")]
+        /// use retry_fn::{strategy::Constant, RetryErr, RetryResult};
+        /// # use retry_fn::tokio::retry_until;
+        /// # async fn run() {
+        /// let (tx, rx) = tokio::sync::oneshot::channel::<()>();
+        /// drop(tx);
+        /// let res = retry_until(Constant::from_secs(60), rx, |_| async {
+        ///     RetryResult::<(), &str>::Retry()
+        /// })
+        /// .await;
+        /// assert!(matches!(res, Err(RetryErr::Cancelled { .. })));
+        /// # }
+        /// ```
+        ///
+        /// # Returns
+        /// If successful, return `Ok`, otherwise return `Retry` to try again or `Err`
+        /// to exit with an error. Once `cancel` completes, returns `Cancelled`
+        pub async fn retry_until<I, C, F, Fut, T, E>(
+            iter: I,
+            cancel: C,
+            f: F,
+        ) -> Result<T, RetryErr<E>>
+        where
+            I: IntoIterator<Item = Duration>,
+            C: Future,
+            F: FnMut(RetryOp) -> Fut,
+            Fut: Future<Output = RetryResult<T, E>>,
+        {
+            Runtime::<$sleeper>::retry_until(iter, cancel, f).await
+        }
+
+        /// Retry a future based on an iterator over Duration, waiting for
+        /// `delay` once before the first attempt. The initial delay is counted
+        /// in `total_delay`
+        ///
+        /// # Returns
+        /// If successful, return `Ok`, otherwise return `Retry` to try again or `Err`
+        /// to exit with an error
+        pub async fn retry_delayed<I, F, Fut, T, E>(
+            delay: Duration,
+            iter: I,
+            f: F,
+        ) -> Result<T, RetryErr<E>>
+        where
+            I: IntoIterator<Item = Duration>,
+            F: FnMut(RetryOp) -> Fut,
+            Fut: Future<Output = RetryResult<T, E>>,
+        {
+            Runtime::<$sleeper>::retry_delayed(delay, iter, f).await
         }
 
         /// Retry a future based on an iterator over Duration. A timer will be run for
@@ -81,7 +536,8 @@ macro_rules! retry_impl {
         /// This takes a future that must implement `Unpin`, so it can be repeatedly
         /// called in the loop
         ///
-        /// ```rust,no_run
+        #[cfg_attr(feature = "tokio-runtime", doc = "```rust,no_run")]
+        #[cfg_attr(not(feature = "tokio-runtime"), doc = "```rust,ignore")]
         /// # use std::{io, sync::{Arc, Mutex}};
         /// use retry_fn::{strategy::Constant, RetryResult};
         /// # use retry_fn::tokio::retry_unpin;
@@ -114,39 +570,184 @@ macro_rules! retry_impl {
         /// # Returns
         /// If successful, return `Ok`, otherwise return `Retry` to try again or `Err`
         /// to exit with an error
-        pub async fn retry_unpin<I, Fut, T, E>(iter: I, mut f: Fut) -> Result<T, RetryErr<E>>
+        pub async fn retry_unpin<I, Fut, T, E>(iter: I, f: Fut) -> Result<T, RetryErr<E>>
         where
             I: IntoIterator<Item = Duration>,
             Fut: Future<Output = RetryResult<T, E>> + Unpin,
         {
-            let mut count = 0;
-            let mut total_delay = Duration::from_millis(0);
-            for dur in iter.into_iter() {
-                match (&mut f).await {
-                    RetryResult::Retry() => {
-                        #[cfg(feature = "tokio-runtime")]
-                        tokio::time::sleep(dur).await;
-                        #[cfg(not(feature = "tokio-runtime"))]
-                        async_std::task::sleep(dur).await;
-                        total_delay += dur;
-                        count += 1;
-                    }
-                    RetryResult::Err(err) => {
-                        return Err(RetryErr::FailedAttempt {
-                            tries: count,
-                            total_delay,
-                            err,
-                        });
-                    }
-                    RetryResult::Ok(val) => {
-                        return Ok(val);
-                    }
-                }
-            }
-            Err(RetryErr::IteratorEnded {
-                tries: count,
-                total_delay,
-            })
+            Runtime::<$sleeper>::retry_unpin(iter, f).await
+        }
+
+        /// Retry a future at a fixed rate: each delay is measured between the
+        /// starts of two attempts, so the time the future took is subtracted
+        /// from the sleep. Wake-ups are instants counted from the first
+        /// attempt, so the schedule doesn't drift. See
+        /// [`crate::retry_fixed_rate`]
+        ///
+        /// # Returns
+        /// If successful, return `Ok`, otherwise return `Retry` to try again or `Err`
+        /// to exit with an error
+        pub async fn retry_fixed_rate<I, F, Fut, T, E>(iter: I, f: F) -> Result<T, RetryErr<E>>
+        where
+            I: IntoIterator<Item = Duration>,
+            F: FnMut(RetryOp) -> Fut,
+            Fut: Future<Output = RetryResult<T, E>>,
+        {
+            Runtime::<$sleeper>::retry_fixed_rate(iter, f).await
+        }
+
+        /// Retry a future returning `ControlFlow` based on an iterator over
+        /// Duration. `Continue` tries again, `Break` returns its `Result`. See
+        /// [`crate::retry_flow`]
+        ///
+        /// # Returns
+        /// If successful, return `Break(Ok)`, otherwise return `Continue` to try
+        /// again or `Break(Err)` to exit with an error
+        pub async fn retry_flow<I, F, Fut, T, E>(iter: I, f: F) -> Result<T, RetryErr<E>>
+        where
+            I: IntoIterator<Item = Duration>,
+            F: FnMut(RetryOp) -> Fut,
+            Fut: Future<Output = ControlFlow<Result<T, E>>>,
+        {
+            Runtime::<$sleeper>::retry_flow(iter, f).await
+        }
+
+        /// Retry a future based on an iterator over Duration, taking each retry
+        /// from a shared [`RetryBudget`]. The call is recorded as a request in
+        /// the budget, and retrying stops with `BudgetExhausted` once the
+        /// budget has none left
+        ///
+        /// # Returns
+        /// If successful, return `Ok`, otherwise return `Retry` to try again or `Err`
+        /// to exit with an error
+        pub async fn retry_with_budget<I, F, Fut, T, E>(
+            budget: &RetryBudget,
+            iter: I,
+            f: F,
+        ) -> Result<T, RetryErr<E>>
+        where
+            I: IntoIterator<Item = Duration>,
+            F: FnMut(RetryOp) -> Fut,
+            Fut: Future<Output = RetryResult<T, E>>,
+        {
+            Runtime::<$sleeper>::retry_with_budget(budget, iter, f).await
+        }
+
+        /// Retry a future, using the error of each failed attempt to pick the
+        /// next delay. Return `RetryWith` from the future to pass the error to
+        /// the strategy, any `Iterator<Item = Duration>` can also be used and
+        /// will ignore it. See [`crate::retry_strategy`] for an example
+        ///
+        /// # Returns
+        /// If successful, return `Ok`, otherwise return `Retry` or `RetryWith`
+        /// to try again or `Err` to exit with an error. When the strategy ends
+        /// after a `RetryWith`, that error is returned as a `FailedAttempt`
+        pub async fn retry_strategy<S, F, Fut, T, E>(strategy: S, f: F) -> Result<T, RetryErr<E>>
+        where
+            S: RetryStrategy<E>,
+            F: FnMut(RetryOp) -> Fut,
+            Fut: Future<Output = RetryResult<T, E>>,
+        {
+            Runtime::<$sleeper>::retry_strategy(strategy, f).await
+        }
+
+        /// Retry a future returning a plain `Result`, using `predicate` to
+        /// decide which errors are worth retrying. See [`crate::retry_if`]
+        ///
+        /// # Returns
+        /// The first `Ok`, otherwise the last error as a `FailedAttempt` once
+        /// an error isn't retryable or the strategy ends
+        pub async fn retry_if<S, F, Fut, P, T, E>(
+            strategy: S,
+            f: F,
+            predicate: P,
+        ) -> Result<T, RetryErr<E>>
+        where
+            S: RetryStrategy<E>,
+            F: FnMut(RetryOp) -> Fut,
+            Fut: Future<Output = Result<T, E>>,
+            P: Fn(&E) -> bool,
+        {
+            Runtime::<$sleeper>::retry_if(strategy, f, predicate).await
+        }
+
+        /// Retry a future returning a plain `Result`, retrying on any error
+        /// until the strategy ends. See [`crate::retry_result`]
+        ///
+        /// # Returns
+        /// The first `Ok`, otherwise the last error as a `FailedAttempt` once
+        /// the strategy ends
+        pub async fn retry_result<S, F, Fut, T, E>(strategy: S, f: F) -> Result<T, RetryErr<E>>
+        where
+            S: RetryStrategy<E>,
+            F: FnMut(RetryOp) -> Fut,
+            Fut: Future<Output = Result<T, E>>,
+        {
+            Runtime::<$sleeper>::retry_result(strategy, f).await
+        }
+
+        /// Retry a future returning a plain `Result` with the process-wide
+        /// default policy. See [`crate::retry_default`]
+        ///
+        /// # Returns
+        /// The first `Ok`, otherwise the last error as a `FailedAttempt` once
+        /// an error isn't retryable or the strategy ends
+        pub async fn retry_default<F, Fut, T, E>(f: F) -> Result<T, RetryErr<E>>
+        where
+            F: FnMut(RetryOp) -> Fut,
+            Fut: Future<Output = Result<T, E>>,
+            E: Error + 'static,
+        {
+            Runtime::<$sleeper>::retry_default(f).await
+        }
+
+        /// Poll the future returned by `condition` until it resolves to
+        /// `true`. See [`crate::wait_until`]
+        ///
+        /// # Returns
+        /// `Ok` once `condition` is `true`, otherwise `IteratorEnded` once
+        /// the strategy ends
+        pub async fn wait_until<I, F, Fut>(
+            iter: I,
+            condition: F,
+        ) -> Result<(), RetryErr<Infallible>>
+        where
+            I: IntoIterator<Item = Duration>,
+            F: FnMut() -> Fut,
+            Fut: Future<Output = bool>,
+        {
+            Runtime::<$sleeper>::wait_until(iter, condition).await
+        }
+
+        /// Poll the future returned by `f` until it resolves to `Some`. See
+        /// [`crate::poll_until`]
+        ///
+        /// # Returns
+        /// The first `Some` value, otherwise `IteratorEnded` once the strategy
+        /// ends
+        pub async fn poll_until<I, F, Fut, T>(iter: I, f: F) -> Result<T, RetryErr<Infallible>>
+        where
+            I: IntoIterator<Item = Duration>,
+            F: FnMut() -> Fut,
+            Fut: Future<Output = Option<T>>,
+        {
+            Runtime::<$sleeper>::poll_until(iter, f).await
+        }
+
+        /// Retry a future returning a plain `Result` whose error implements
+        /// [`Retryable`]. See [`crate::retry_transient`]
+        ///
+        /// # Returns
+        /// The first `Ok`, otherwise the last error as a `FailedAttempt` once
+        /// an error isn't retryable or the strategy ends
+        pub async fn retry_transient<S, F, Fut, T, E>(strategy: S, f: F) -> Result<T, RetryErr<E>>
+        where
+            S: RetryStrategy<E>,
+            F: FnMut(RetryOp) -> Fut,
+            Fut: Future<Output = Result<T, E>>,
+            E: Retryable,
+        {
+            Runtime::<$sleeper>::retry_transient(strategy, f).await
         }
     };
 }
@@ -0,0 +1,69 @@
+//! retry impls for monoio
+//!
+//! Enable the `monoio` feature to get access to these functions. The runtime
+//! must be built with its timer enabled, and since monoio runs one thread per
+//! core the futures don't need to be `Send`
+//!
+//! ```rust,no_run
+//! use retry_fn::{monoio::retry, strategy::Constant, RetryResult};
+//! use std::rc::Rc;
+//! # async fn run() {
+//! let shared = Rc::new(3);
+//! let res = retry(Constant::from_millis(100).take(5), |op| {
+//!     let shared = shared.clone();
+//!     async move {
+//!         if op.retries >= *shared {
+//!             RetryResult::<_, ()>::Ok(op.retries)
+//!         } else {
+//!             RetryResult::Retry()
+//!         }
+//!     }
+//! })
+//! .await;
+//! assert_eq!(res.ok(), Some(3));
+//! # }
+//! ```
+
+/// monoio's timer
+#[derive(Debug, Clone, Copy)]
+pub struct Timer;
+
+impl crate::runtime::Sleeper for Timer {
+    type Sleep = ::monoio::time::Sleep;
+
+    fn sleep(dur: Duration) -> Self::Sleep {
+        ::monoio::time::sleep(dur)
+    }
+}
+
+retry_impl!(Timer);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::strategy::Constant;
+    use std::rc::Rc;
+
+    #[test]
+    fn not_send() {
+        let mut rt = ::monoio::RuntimeBuilder::<::monoio::LegacyDriver>::new()
+            .enable_timer()
+            .build()
+            .unwrap();
+        let res = rt.block_on(async {
+            let shared = Rc::new(2);
+            retry(Constant::from_millis(1).take(3), |op| {
+                let shared = shared.clone();
+                async move {
+                    if op.retries >= *shared {
+                        RetryResult::<_, ()>::Ok(op.total_delay)
+                    } else {
+                        RetryResult::Retry()
+                    }
+                }
+            })
+            .await
+        });
+        assert!(matches!(res, Ok(d) if d == Duration::from_millis(2)));
+    }
+}
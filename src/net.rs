@@ -0,0 +1,53 @@
+//! networking
+//!
+//! Connecting is the most common thing to retry. [`connect_with_retry`]
+//! retries `TcpStream::connect` on transient errors (see
+//! [`io::is_transient`](crate::io::is_transient)), and with the
+//! `tokio-net` feature `tokio::connect_with_retry` does the same for
+//! tokio's `TcpStream`
+//!
+//! ```rust,no_run
+//! use retry_fn::{net::connect_with_retry, strategy::ExponentialBackoff};
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let strategy = ExponentialBackoff::from_millis(100).take(5);
+//! let stream = connect_with_retry("localhost:8080", strategy)?;
+//! # Ok(())
+//! # }
+//! ```
+use crate::{retry_transient, strategy::RetryStrategy, RetryErr};
+use std::{
+    io,
+    net::{TcpStream, ToSocketAddrs},
+};
+
+/// Connect to `addr`, retrying transient errors with the delays of
+/// `strategy`
+///
+/// # Returns
+/// The stream, otherwise the last error as a `FailedAttempt` once an error
+/// isn't transient or the strategy ends
+pub fn connect_with_retry<A, S>(addr: A, strategy: S) -> Result<TcpStream, RetryErr<io::Error>>
+where
+    A: ToSocketAddrs,
+    S: RetryStrategy<io::Error>,
+{
+    retry_transient(strategy, |_| TcpStream::connect(&addr))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::strategy::Constant;
+    use std::net::TcpListener;
+
+    #[test]
+    fn connects() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        assert!(connect_with_retry(addr, Constant::from_millis(1).take(2)).is_ok());
+        drop(listener);
+        // nothing listening, refused on every attempt
+        let res = connect_with_retry(addr, Constant::from_millis(1).take(2));
+        assert!(matches!(res, Err(RetryErr::FailedAttempt { tries: 1, .. })));
+    }
+}
@@ -0,0 +1,77 @@
+//! operations
+//!
+//! An `Operation` is anything that can be attempted by a retry loop. Closures
+//! taking a `RetryOp` are operations already, implement it on a struct when
+//! the operation carries state of its own between attempts, then run it with
+//! [`retry_operation`](crate::retry_operation)
+//!
+//! ```rust,no_run
+//! use retry_fn::{retry_operation, strategy::Constant, Operation, RetryOp, RetryResult};
+//!
+//! /// fetch every page, resuming from the last one fetched after a failure
+//! struct Pages {
+//!     next: usize,
+//!     items: Vec<String>,
+//! }
+//!
+//! impl Operation<Vec<String>, String> for Pages {
+//!     fn run(&mut self, _op: RetryOp) -> RetryResult<Vec<String>, String> {
+//!         while self.next < 3 {
+//!             // fetch page `self.next`
+//!             self.items.push(format!("page {}", self.next));
+//!             self.next += 1;
+//!         }
+//!         RetryResult::Ok(std::mem::take(&mut self.items))
+//!     }
+//! }
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let pages = Pages { next: 0, items: Vec::new() };
+//! let items = retry_operation(Constant::from_millis(100).take(3), pages)?;
+//! assert_eq!(items.len(), 3);
+//! # Ok(())
+//! # }
+//! ```
+use crate::{RetryOp, RetryResult};
+
+/// An operation a retry loop can attempt
+pub trait Operation<T, E> {
+    /// Make an attempt
+    fn run(&mut self, op: RetryOp) -> RetryResult<T, E>;
+}
+
+impl<F, T, E> Operation<T, E> for F
+where
+    F: FnMut(RetryOp) -> RetryResult<T, E>,
+{
+    fn run(&mut self, op: RetryOp) -> RetryResult<T, E> {
+        self(op)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use super::*;
+    use crate::{retry_operation, strategy::Constant, RetryErr};
+
+    struct Countdown(usize);
+
+    impl Operation<&'static str, usize> for Countdown {
+        fn run(&mut self, _op: RetryOp) -> RetryResult<&'static str, usize> {
+            if self.0 == 0 {
+                RetryResult::Ok("done")
+            } else {
+                self.0 -= 1;
+                RetryResult::RetryWith(self.0)
+            }
+        }
+    }
+
+    #[test]
+    fn stateful() {
+        let res = retry_operation(Constant::from_millis(1).take(3), Countdown(2));
+        assert_eq!(res.ok(), Some("done"));
+        let res = retry_operation(Constant::from_millis(1).take(1), Countdown(5));
+        assert!(matches!(res, Err(RetryErr::FailedAttempt { err: 4, .. })));
+    }
+}
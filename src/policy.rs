@@ -0,0 +1,540 @@
+//! retry policy
+//!
+//! A `RetryPolicy` bundles how to retry (a function making a new strategy for
+//! each run), which errors to retry, an optional [`RetryBudget`], a hook
+//! called on every retry and an async hook called once a run gives up. It can be cloned cheaply and shared, then applied to
+//! many different operations returning a plain `Result`
+//!
+//! ```rust,no_run
+//! # use std::io;
+//! use retry_fn::{strategy::ExponentialBackoff, RetryPolicy};
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let policy = RetryPolicy::new(|| ExponentialBackoff::from_millis(100).take(3))
+//!     .classify(|e: &io::Error| e.kind() == io::ErrorKind::TimedOut)
+//!     .on_retry(|op, e, delay| eprintln!("try {} failed with {}, waiting {:?}", op.retries, e, delay));
+//!
+//! let mut count = 0;
+//! let res = policy.retry(|_| {
+//!     count += 1;
+//!     if count < 3 {
+//!         Err(io::Error::new(io::ErrorKind::TimedOut, "timed out"))
+//!     } else {
+//!         Ok(count)
+//!     }
+//! });
+//! assert_eq!(res.ok(), Some(3));
+//!
+//! // not retried, the error is returned right away
+//! let res = policy.retry(|_| Err::<(), _>(io::Error::new(io::ErrorKind::NotFound, "gone")));
+//! assert!(res.is_err());
+//! Ok(())
+//! # }
+//! ```
+//!
+//! A [`DefaultPolicy`] can also be registered once for the whole process
+//! with [`set_default_policy`], it's used by [`retry_default`](crate::retry_default)
+//! so code retrying an operation doesn't have to pick a strategy itself
+#[cfg(any(feature = "tokio-runtime", feature = "async-runtime"))]
+use crate::runtime::{DefaultSleeper, Sleeper};
+use crate::{strategy::ExponentialBackoff, RetryBudget, RetryErr, RetryOp, Stopwatch};
+use std::{
+    error::Error,
+    fmt,
+    sync::{Arc, PoisonError, RwLock},
+    thread,
+    time::Duration,
+};
+#[cfg(any(feature = "tokio-runtime", feature = "async-runtime"))]
+use std::{future::Future, pin::Pin};
+
+type Classify<E> = Arc<dyn Fn(&E) -> bool + Send + Sync>;
+type OnRetry<E> = Arc<dyn Fn(RetryOp, &E, Duration) + Send + Sync>;
+#[cfg(any(feature = "tokio-runtime", feature = "async-runtime"))]
+type OnGiveUp<E> =
+    Arc<dyn Fn(RetryOp, &[E]) -> Pin<Box<dyn Future<Output = ()> + Send>> + Send + Sync>;
+type ClassifyAny = Arc<dyn Fn(&(dyn Error + 'static)) -> bool + Send + Sync>;
+type Delays = Box<dyn Iterator<Item = Duration> + Send>;
+
+static DEFAULT: RwLock<Option<DefaultPolicy>> = RwLock::new(None);
+
+/// A reusable retry policy, shared by cloning
+pub struct RetryPolicy<S, E> {
+    strategy: Arc<dyn Fn() -> S + Send + Sync>,
+    classify: Option<Classify<E>>,
+    budget: Option<Arc<RetryBudget>>,
+    on_retry: Option<OnRetry<E>>,
+    #[cfg(any(feature = "tokio-runtime", feature = "async-runtime"))]
+    on_give_up: Option<OnGiveUp<E>>,
+}
+
+/// why a run stopped retrying
+enum Stop {
+    Failed,
+    BudgetExhausted,
+}
+
+impl Stop {
+    fn into_err<E>(self, op: RetryOp, err: E) -> RetryErr<E> {
+        match self {
+            Stop::Failed => RetryErr::FailedAttempt {
+                tries: op.retries,
+                total_delay: op.total_delay,
+                err,
+            },
+            Stop::BudgetExhausted => RetryErr::BudgetExhausted {
+                tries: op.retries,
+                total_delay: op.total_delay,
+            },
+        }
+    }
+}
+
+impl<S, E> Clone for RetryPolicy<S, E> {
+    fn clone(&self) -> Self {
+        Self {
+            strategy: self.strategy.clone(),
+            classify: self.classify.clone(),
+            budget: self.budget.clone(),
+            on_retry: self.on_retry.clone(),
+            #[cfg(any(feature = "tokio-runtime", feature = "async-runtime"))]
+            on_give_up: self.on_give_up.clone(),
+        }
+    }
+}
+
+impl<S, E> fmt::Debug for RetryPolicy<S, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut s = f.debug_struct("RetryPolicy");
+        s.field("classify", &self.classify.is_some())
+            .field("budget", &self.budget)
+            .field("on_retry", &self.on_retry.is_some());
+        #[cfg(any(feature = "tokio-runtime", feature = "async-runtime"))]
+        s.field("on_give_up", &self.on_give_up.is_some());
+        s.finish()
+    }
+}
+
+impl<S, E> RetryPolicy<S, E>
+where
+    S: IntoIterator<Item = Duration>,
+{
+    /// Create a new policy, calling `strategy` for the delays of each run.
+    /// Every error is retried until the strategy ends
+    pub fn new<F>(strategy: F) -> Self
+    where
+        F: Fn() -> S + Send + Sync + 'static,
+    {
+        Self {
+            strategy: Arc::new(strategy),
+            classify: None,
+            budget: None,
+            on_retry: None,
+            #[cfg(any(feature = "tokio-runtime", feature = "async-runtime"))]
+            on_give_up: None,
+        }
+    }
+
+    /// Only retry errors for which `f` returns `true`, any other error is
+    /// returned right away
+    pub fn classify<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&E) -> bool + Send + Sync + 'static,
+    {
+        self.classify = Some(Arc::new(f));
+        self
+    }
+
+    /// Only retry while `budget` has retries left, it's shared with every
+    /// clone of the policy. Each run deposits into it, and once a retry can't
+    /// be withdrawn the run ends with `BudgetExhausted`
+    pub fn budget(mut self, budget: Arc<RetryBudget>) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    /// Call `f` before each retry with the failed attempt, its error and the
+    /// delay about to be waited
+    pub fn on_retry<F>(mut self, f: F) -> Self
+    where
+        F: Fn(RetryOp, &E, Duration) + Send + Sync + 'static,
+    {
+        self.on_retry = Some(Arc::new(f));
+        self
+    }
+
+    /// Await `f` exactly once when a [`retry_async`](Self::retry_async) run
+    /// gives up, with the last attempt and the errors of every attempt in
+    /// order. Useful to send the failed work to a dead-letter queue or store
+    /// it for a later replay. The returned future can't borrow the errors, so
+    /// copy out what it needs first
+    ///
+    /// ```rust,no_run
+    /// # use std::io;
+    /// use retry_fn::{strategy::Constant, RetryPolicy};
+    /// # async fn dead_letter(_: Vec<String>) {}
+    /// # async fn run() {
+    /// let policy = RetryPolicy::new(|| Constant::from_millis(100).take(3))
+    ///     .on_give_up(|op, errs: &[io::Error]| {
+    ///         let errs = errs.iter().map(|e| e.to_string()).collect::<Vec<_>>();
+    ///         eprintln!("giving up after {} retries", op.retries);
+    ///         dead_letter(errs)
+    ///     });
+    /// let res = policy
+    ///     .retry_async(|_| async { Err::<(), _>(io::Error::new(io::ErrorKind::Other, "down")) })
+    ///     .await;
+    /// assert!(res.is_err());
+    /// # }
+    /// ```
+    #[cfg(any(feature = "tokio-runtime", feature = "async-runtime"))]
+    pub fn on_give_up<F, Fut>(mut self, f: F) -> Self
+    where
+        F: Fn(RetryOp, &[E]) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.on_give_up = Some(Arc::new(move |op, errs| Box::pin(f(op, errs))));
+        self
+    }
+
+    /// the delay before retrying after `err`, or why to stop
+    fn next_delay(&self, delays: &mut S::IntoIter, op: RetryOp, err: &E) -> Result<Duration, Stop> {
+        let retryable = match &self.classify {
+            Some(classify) => classify(err),
+            None => true,
+        };
+        if !retryable {
+            return Err(Stop::Failed);
+        }
+        let dur = match crate::next_delay(delays) {
+            Some(dur) => dur,
+            None => return Err(Stop::Failed),
+        };
+        if let Some(budget) = &self.budget {
+            if !budget.try_withdraw() {
+                return Err(Stop::BudgetExhausted);
+            }
+        }
+        if let Some(on_retry) = &self.on_retry {
+            on_retry(op, err, dur);
+        }
+        Ok(dur)
+    }
+
+    /// the delays for one run, depositing into the budget
+    fn start(&self) -> S::IntoIter {
+        if let Some(budget) = &self.budget {
+            budget.deposit();
+        }
+        (self.strategy)().into_iter()
+    }
+
+    /// Retry `f` under this policy, sleeping the thread between attempts
+    ///
+    /// # Returns
+    /// The first `Ok`, or the last error as a `FailedAttempt` once an error
+    /// isn't retryable or the strategy ends. `IteratorEnded` if the strategy
+    /// has no delays at all
+    pub fn retry<F, T>(&self, mut f: F) -> Result<T, RetryErr<E>>
+    where
+        F: FnMut(RetryOp) -> Result<T, E>,
+    {
+        let mut delays = self.start();
+        let mut watch = Stopwatch::new();
+        let mut count = 0;
+        let mut total_delay = Duration::from_millis(0);
+        if crate::exhausted(&delays) {
+            return Err(RetryErr::IteratorEnded {
+                tries: 0,
+                total_delay,
+            });
+        }
+        loop {
+            let op = watch.op(count, total_delay);
+            let err = match f(op) {
+                Ok(val) => return Ok(val),
+                Err(err) => err,
+            };
+            watch.done();
+            let dur = match self.next_delay(&mut delays, op, &err) {
+                Ok(dur) => dur,
+                Err(stop) => return Err(stop.into_err(op, err)),
+            };
+            thread::sleep(dur);
+            total_delay = total_delay.saturating_add(dur);
+            count += 1;
+        }
+    }
+
+    /// Retry the future returned by `f` under this policy, using tokio's
+    /// timer if the `tokio-runtime` feature is enabled and async-std's
+    /// otherwise
+    ///
+    /// # Returns
+    /// The first `Ok`, or the last error as a `FailedAttempt` once an error
+    /// isn't retryable or the strategy ends, `IteratorEnded` if the strategy
+    /// has no delays at all. The
+    /// [`on_give_up`](Self::on_give_up) hook is awaited before returning an
+    /// error
+    #[cfg(any(feature = "tokio-runtime", feature = "async-runtime"))]
+    pub async fn retry_async<F, Fut, T>(&self, mut f: F) -> Result<T, RetryErr<E>>
+    where
+        F: FnMut(RetryOp) -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        let mut delays = self.start();
+        let mut watch = Stopwatch::timed(DefaultSleeper::now);
+        let mut count = 0;
+        let mut total_delay = Duration::from_millis(0);
+        if crate::exhausted(&delays) {
+            return Err(RetryErr::IteratorEnded {
+                tries: 0,
+                total_delay,
+            });
+        }
+        // only kept for the give up hook
+        let mut errs = Vec::new();
+        loop {
+            let op = watch.op(count, total_delay);
+            let err = match f(op).await {
+                Ok(val) => return Ok(val),
+                Err(err) => err,
+            };
+            watch.done();
+            let dur = match self.next_delay(&mut delays, op, &err) {
+                Ok(dur) => dur,
+                Err(stop) => {
+                    let err = match &self.on_give_up {
+                        Some(on_give_up) => {
+                            errs.push(err);
+                            on_give_up(op, &errs).await;
+                            errs.pop().expect("just pushed")
+                        }
+                        None => err,
+                    };
+                    return Err(stop.into_err(op, err));
+                }
+            };
+            if self.on_give_up.is_some() {
+                errs.push(err);
+            }
+            DefaultSleeper::sleep(dur).await;
+            total_delay = total_delay.saturating_add(dur);
+            count += 1;
+        }
+    }
+}
+
+/// A policy for any error type, registered for the whole process with
+/// [`set_default_policy`]. Until one is registered the default retries up to
+/// 3 times with exponential backoff from 100ms, and retries every error
+#[derive(Clone)]
+pub struct DefaultPolicy {
+    strategy: Arc<dyn Fn() -> Delays + Send + Sync>,
+    classify: Option<ClassifyAny>,
+}
+
+impl fmt::Debug for DefaultPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DefaultPolicy")
+            .field("classify", &self.classify.is_some())
+            .finish()
+    }
+}
+
+impl Default for DefaultPolicy {
+    fn default() -> Self {
+        Self::new(|| ExponentialBackoff::from_millis(100).take(3))
+    }
+}
+
+impl DefaultPolicy {
+    /// Create a new default policy, calling `strategy` for the delays of each
+    /// run. Every error is retried until the strategy ends
+    pub fn new<F, S>(strategy: F) -> Self
+    where
+        F: Fn() -> S + Send + Sync + 'static,
+        S: IntoIterator<Item = Duration>,
+        S::IntoIter: Send + 'static,
+    {
+        Self {
+            strategy: Arc::new(move || Box::new(strategy().into_iter()) as Delays),
+            classify: None,
+        }
+    }
+
+    /// Only retry errors for which `f` returns `true`, any other error is
+    /// returned right away. Errors can be downcast to check for a concrete
+    /// type
+    pub fn classify<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&(dyn Error + 'static)) -> bool + Send + Sync + 'static,
+    {
+        self.classify = Some(Arc::new(f));
+        self
+    }
+
+    /// a new strategy for one run
+    pub(crate) fn delays(&self) -> Delays {
+        (self.strategy)()
+    }
+
+    /// whether `err` should be retried
+    pub(crate) fn is_retryable(&self, err: &(dyn Error + 'static)) -> bool {
+        match &self.classify {
+            Some(classify) => classify(err),
+            None => true,
+        }
+    }
+}
+
+/// Register `policy` as the default for the whole process, replacing the
+/// previous one
+pub fn set_default_policy(policy: DefaultPolicy) {
+    *DEFAULT.write().unwrap_or_else(PoisonError::into_inner) = Some(policy);
+}
+
+/// The policy registered with [`set_default_policy`], or
+/// `DefaultPolicy::default()` if there is none
+pub fn default_policy() -> DefaultPolicy {
+    DEFAULT
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::strategy::Constant;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn policy() -> RetryPolicy<std::iter::Take<Constant>, &'static str> {
+        RetryPolicy::new(|| Constant::from_millis(1).take(3)).classify(|e| *e == "busy")
+    }
+
+    #[test]
+    fn retries_until_ended() {
+        let policy = policy();
+        let mut calls = 0;
+        let res = policy.retry(|_| {
+            calls += 1;
+            Err::<(), _>("busy")
+        });
+        assert_eq!(calls, 3);
+        assert!(matches!(
+            res,
+            Err(RetryErr::FailedAttempt {
+                tries: 2,
+                err: "busy",
+                ..
+            })
+        ));
+        // a new strategy for every run
+        let res = policy.retry(|op| {
+            if op.retries < 2 {
+                Err("busy")
+            } else {
+                Ok(op.retries)
+            }
+        });
+        assert_eq!(res.ok(), Some(2));
+    }
+
+    #[test]
+    fn not_retryable() {
+        let mut calls = 0;
+        let res = policy().retry(|_| {
+            calls += 1;
+            Err::<(), _>("fatal")
+        });
+        assert_eq!(calls, 1);
+        assert!(matches!(res, Err(RetryErr::FailedAttempt { tries: 0, .. })));
+    }
+
+    #[test]
+    fn shared_hook() {
+        let seen = Arc::new(AtomicUsize::new(0));
+        let counter = seen.clone();
+        let policy = policy().on_retry(move |_, _, _| {
+            counter.fetch_add(1, Ordering::Relaxed);
+        });
+        let other = policy.clone();
+        let _ = policy.retry(|_| Err::<(), _>("busy"));
+        let _ = other.retry(|op| if op.retries < 1 { Err("busy") } else { Ok(()) });
+        assert_eq!(seen.load(Ordering::Relaxed), 3);
+    }
+
+    #[cfg(feature = "tokio-runtime")]
+    #[tokio::test(start_paused = true)]
+    async fn retry_async() {
+        let res = policy()
+            .retry_async(|op| async move {
+                if op.retries >= 1 {
+                    Ok(op.total_delay)
+                } else {
+                    Err("busy")
+                }
+            })
+            .await;
+        assert!(matches!(res, Ok(d) if d == Duration::from_millis(1)));
+    }
+
+    #[cfg(feature = "tokio-runtime")]
+    #[tokio::test(start_paused = true)]
+    async fn give_up_hook() {
+        let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let dead = seen.clone();
+        let policy = policy().on_give_up(move |op, errs| {
+            dead.lock().unwrap().push((op.retries, errs.to_vec()));
+            async {}
+        });
+        let res = policy.retry_async(|_| async { Err::<(), _>("busy") }).await;
+        assert!(matches!(
+            res,
+            Err(RetryErr::FailedAttempt { err: "busy", .. })
+        ));
+        let res = policy
+            .retry_async(|op| async move {
+                if op.retries < 1 {
+                    Err("busy")
+                } else {
+                    Ok(())
+                }
+            })
+            .await;
+        assert!(res.is_ok());
+        assert_eq!(*seen.lock().unwrap(), vec![(2, vec!["busy"; 3])]);
+    }
+
+    #[test]
+    fn shared_budget() {
+        let budget = Arc::new(RetryBudget::new(Duration::from_secs(10), 0, 1.0));
+        let policy = policy().budget(budget.clone());
+        // one run deposits one retry
+        let res = policy.retry(|_| Err::<(), _>("busy"));
+        assert!(matches!(
+            res,
+            Err(RetryErr::BudgetExhausted { tries: 1, .. })
+        ));
+        assert_eq!(budget.balance(), 0);
+    }
+
+    #[test]
+    fn default_policy() {
+        use std::io;
+        set_default_policy(
+            DefaultPolicy::new(|| Constant::from_millis(1).take(3)).classify(|e| {
+                matches!(e.downcast_ref::<io::Error>(), Some(e) if e.kind() == io::ErrorKind::TimedOut)
+            }),
+        );
+        let mut calls = 0;
+        let res = crate::retry_default(|_| {
+            calls += 1;
+            Err::<(), _>(io::Error::from(io::ErrorKind::TimedOut))
+        });
+        assert_eq!(calls, 3);
+        assert!(matches!(res, Err(RetryErr::FailedAttempt { tries: 2, .. })));
+        let res = crate::retry_default(|_| Err::<(), _>(io::Error::from(io::ErrorKind::NotFound)));
+        assert!(matches!(res, Err(RetryErr::FailedAttempt { tries: 0, .. })));
+    }
+}
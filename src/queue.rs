@@ -0,0 +1,264 @@
+//! retry job queue
+//!
+//! A [`RetryQueue`] runs jobs on a fixed number of tokio tasks, retrying each
+//! one under the [`RetryPolicy`] it was submitted with. Jobs start in the
+//! order they were submitted, and submitting returns a [`JobHandle`] that
+//! completes with the job's result. Enable the `queue` feature to use this
+//!
+//! ```rust,no_run
+//! # use std::io;
+//! use retry_fn::{queue::RetryQueue, strategy::ExponentialBackoff, RetryPolicy};
+//! # async fn deliver(_: &str) -> io::Result<()> { Ok(()) }
+//! # async fn run() {
+//! let policy = RetryPolicy::new(|| ExponentialBackoff::from_millis(100).take(5));
+//! let queue = RetryQueue::new(4);
+//! let handles = ["a", "b", "c"]
+//!     .iter()
+//!     .map(|hook| queue.submit(&policy, move |_| deliver(hook)))
+//!     .collect::<Vec<_>>();
+//! for handle in handles {
+//!     if let Err(err) = handle.await {
+//!         eprintln!("delivery failed: {}", err);
+//!     }
+//! }
+//! queue.shutdown().await;
+//! # }
+//! ```
+use crate::{RetryErr, RetryOp, RetryPolicy};
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, PoisonError,
+    },
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::{
+    sync::{mpsc, oneshot, Mutex},
+    task::JoinHandle,
+};
+
+type Job = Pin<Box<dyn Future<Output = ()> + Send>>;
+type LastOp = Arc<std::sync::Mutex<Option<RetryOp>>>;
+
+/// A pool of workers retrying submitted jobs, see the [module docs](self)
+pub struct RetryQueue {
+    tx: mpsc::UnboundedSender<Job>,
+    workers: Vec<JoinHandle<()>>,
+    pending: Arc<AtomicUsize>,
+}
+
+impl fmt::Debug for RetryQueue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RetryQueue")
+            .field("workers", &self.workers.len())
+            .field("pending", &self.pending())
+            .finish()
+    }
+}
+
+impl RetryQueue {
+    /// Create a queue running at most `workers` jobs at once, on tasks
+    /// spawned on the current runtime
+    ///
+    /// # Panics
+    /// If `workers` is zero, or if called outside of a tokio runtime
+    pub fn new(workers: usize) -> Self {
+        assert!(workers > 0, "a queue needs at least one worker");
+        let (tx, rx) = mpsc::unbounded_channel::<Job>();
+        let rx = Arc::new(Mutex::new(rx));
+        let workers = (0..workers)
+            .map(|_| {
+                let rx = rx.clone();
+                tokio::spawn(async move {
+                    loop {
+                        let job = rx.lock().await.recv().await;
+                        match job {
+                            // a panicking job only fails its own handle
+                            Some(job) => {
+                                let _ = tokio::spawn(job).await;
+                            }
+                            None => break,
+                        }
+                    }
+                })
+            })
+            .collect();
+        Self {
+            tx,
+            workers,
+            pending: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Queue `f` to be retried under `policy` once a worker is free
+    ///
+    /// # Returns
+    /// A [`JobHandle`] completing with the result of
+    /// [`RetryPolicy::retry_async`]. Dropping it doesn't stop the job
+    pub fn submit<S, E, F, Fut, T>(&self, policy: &RetryPolicy<S, E>, mut f: F) -> JobHandle<T, E>
+    where
+        S: IntoIterator<Item = Duration> + 'static,
+        S::IntoIter: Send,
+        F: FnMut(RetryOp) -> Fut + Send + 'static,
+        Fut: Future<Output = Result<T, E>> + Send + 'static,
+        T: Send + 'static,
+        E: Send + 'static,
+    {
+        let policy = policy.clone();
+        let (tx, rx) = oneshot::channel();
+        let last = LastOp::default();
+        let op = last.clone();
+        self.pending.fetch_add(1, Ordering::AcqRel);
+        let pending = Pending(self.pending.clone());
+        let job = async move {
+            let _pending = pending;
+            let res = policy
+                .retry_async(|attempt| {
+                    *op.lock().unwrap_or_else(PoisonError::into_inner) = Some(attempt);
+                    f(attempt)
+                })
+                .await;
+            let _ = tx.send(res);
+        };
+        // the workers only stop once `tx` is dropped, if the job is lost
+        // anyway its handle returns `Cancelled`
+        let _ = self.tx.send(Box::pin(job));
+        JobHandle { rx, last }
+    }
+
+    /// the number of jobs queued or running
+    pub fn pending(&self) -> usize {
+        self.pending.load(Ordering::Acquire)
+    }
+
+    /// Stop taking jobs and wait for every submitted job to finish. Dropping
+    /// the queue instead lets the workers finish in the background
+    pub async fn shutdown(self) {
+        drop(self.tx);
+        for worker in self.workers {
+            let _ = worker.await;
+        }
+    }
+}
+
+/// counts a job as pending until it's dropped, even if it panics
+struct Pending(Arc<AtomicUsize>);
+
+impl Drop for Pending {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// The result of a job submitted to a [`RetryQueue`]
+pub struct JobHandle<T, E> {
+    rx: oneshot::Receiver<Result<T, RetryErr<E>>>,
+    last: LastOp,
+}
+
+impl<T, E> fmt::Debug for JobHandle<T, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JobHandle")
+            .field("attempts", &self.attempts())
+            .finish()
+    }
+}
+
+impl<T, E> JobHandle<T, E> {
+    /// the number of attempts started so far, zero while the job is queued
+    pub fn attempts(&self) -> usize {
+        match self.op() {
+            Some(op) => op.retries + 1,
+            None => 0,
+        }
+    }
+
+    fn op(&self) -> Option<RetryOp> {
+        *self.last.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<T, E> Future for JobHandle<T, E> {
+    /// the job's result, or `Cancelled` if it panicked or the runtime shut
+    /// down before it finished
+    type Output = Result<T, RetryErr<E>>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let res = match Pin::new(&mut self.rx).poll(cx) {
+            Poll::Ready(res) => res,
+            Poll::Pending => return Poll::Pending,
+        };
+        Poll::Ready(match res {
+            Ok(res) => res,
+            Err(_) => {
+                let (tries, total_delay) = match self.op() {
+                    Some(op) => (op.retries, op.total_delay),
+                    None => (0, Duration::from_millis(0)),
+                };
+                Err(RetryErr::Cancelled { tries, total_delay })
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::strategy::Constant;
+
+    #[tokio::test(start_paused = true)]
+    async fn runs_jobs() {
+        let policy = RetryPolicy::new(|| Constant::from_secs(1).take(3));
+        let running = Arc::new(AtomicUsize::new(0));
+        let most = Arc::new(AtomicUsize::new(0));
+        let queue = RetryQueue::new(2);
+        let handles = (0..4)
+            .map(|job| {
+                let running = running.clone();
+                let most = most.clone();
+                queue.submit(&policy, move |op| {
+                    let running = running.clone();
+                    let most = most.clone();
+                    async move {
+                        most.fetch_max(
+                            running.fetch_add(1, Ordering::SeqCst) + 1,
+                            Ordering::SeqCst,
+                        );
+                        tokio::time::sleep(Duration::from_millis(10)).await;
+                        running.fetch_sub(1, Ordering::SeqCst);
+                        if job % 2 == 0 && op.retries < 1 {
+                            Err("busy")
+                        } else if job == 3 {
+                            Err("down")
+                        } else {
+                            Ok(job)
+                        }
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(queue.pending(), 4);
+        let mut results = Vec::new();
+        for handle in handles {
+            results.push(handle.await);
+        }
+        assert!(matches!(results[0], Ok(0)));
+        assert!(matches!(results[1], Ok(1)));
+        assert!(matches!(results[2], Ok(2)));
+        assert!(matches!(
+            results[3],
+            Err(RetryErr::FailedAttempt {
+                tries: 2,
+                err: "down",
+                ..
+            })
+        ));
+        assert_eq!(most.load(Ordering::SeqCst), 2);
+        assert_eq!(queue.pending(), 0);
+        queue.shutdown().await;
+    }
+}
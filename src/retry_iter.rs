@@ -0,0 +1,168 @@
+//! retrying iterators
+//!
+//! `RetryIter` wraps an iterator of `Result`s built by a factory. When the
+//! source yields `Err` it waits for the next delay, builds a new source and
+//! carries on, so a stream like a paginated API can fail part way through
+//! without starting over. The factory is passed the number of items yielded
+//! so far so the new source can resume where the last one failed
+//!
+//! ```rust,no_run
+//! use retry_fn::{retry_iter, strategy::Constant};
+//! # fn fetch_page(page: usize) -> Result<Option<Vec<u32>>, String> { Ok(None) }
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! const PAGE: usize = 50;
+//! let items = retry_iter(Constant::from_millis(100).take(5), |skip| {
+//!     // restart from the page holding the first item not yet seen
+//!     (skip / PAGE..)
+//!         .map(fetch_page)
+//!         .take_while(|page| !matches!(page, Ok(None)))
+//!         .flat_map(|page| match page {
+//!             Ok(items) => items.unwrap_or_default().into_iter().map(Ok).collect(),
+//!             Err(err) => vec![Err(err)],
+//!         })
+//!         .skip(skip % PAGE)
+//! });
+//! for item in items {
+//!     let item = item?;
+//!     // ...
+//! }
+//! # Ok(())
+//! # }
+//! ```
+use crate::RetryErr;
+use std::{fmt, iter::FusedIterator, thread, time::Duration};
+
+/// Define a type for an iterator re-creating its source on error, see
+/// [`retry_iter`]
+pub struct RetryIter<D, F, I> {
+    delays: D,
+    factory: F,
+    source: Option<I>,
+    yielded: usize,
+    count: usize,
+    total_delay: Duration,
+    done: bool,
+}
+
+impl<D, F, I> fmt::Debug for RetryIter<D, F, I> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RetryIter")
+            .field("yielded", &self.yielded)
+            .field("count", &self.count)
+            .field("total_delay", &self.total_delay)
+            .field("done", &self.done)
+            .finish()
+    }
+}
+
+/// Iterate over the `Ok` items of the source `factory` builds, re-creating
+/// it after the next delay of `iter` whenever it yields `Err`. `factory` is
+/// given the number of items yielded so far.
+///
+/// The delays are shared by the whole stream, they aren't reset by a
+/// successful item. Once they run out the error is yielded as
+/// `RetryErr::FailedAttempt` and the iterator ends. The source isn't built
+/// until the first call to `next`
+pub fn retry_iter<D, F, I, T, E>(iter: D, factory: F) -> RetryIter<D::IntoIter, F, I>
+where
+    D: IntoIterator<Item = Duration>,
+    F: FnMut(usize) -> I,
+    I: Iterator<Item = Result<T, E>>,
+{
+    RetryIter {
+        delays: iter.into_iter(),
+        factory,
+        source: None,
+        yielded: 0,
+        count: 0,
+        total_delay: Duration::from_millis(0),
+        done: false,
+    }
+}
+
+impl<D, F, I, T, E> Iterator for RetryIter<D, F, I>
+where
+    D: Iterator<Item = Duration>,
+    F: FnMut(usize) -> I,
+    I: Iterator<Item = Result<T, E>>,
+{
+    type Item = Result<T, RetryErr<E>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            let yielded = self.yielded;
+            let factory = &mut self.factory;
+            let source = self.source.get_or_insert_with(|| factory(yielded));
+            let err = match source.next() {
+                Some(Ok(item)) => {
+                    self.yielded += 1;
+                    return Some(Ok(item));
+                }
+                Some(Err(err)) => err,
+                None => {
+                    self.done = true;
+                    return None;
+                }
+            };
+            self.source = None;
+            match self.delays.next() {
+                Some(dur) => {
+                    thread::sleep(dur);
+                    self.total_delay = self.total_delay.saturating_add(dur);
+                    self.count += 1;
+                }
+                None => {
+                    self.done = true;
+                    return Some(Err(RetryErr::FailedAttempt {
+                        tries: self.count,
+                        total_delay: self.total_delay,
+                        err,
+                    }));
+                }
+            }
+        }
+        None
+    }
+}
+
+impl<D, F, I, T, E> FusedIterator for RetryIter<D, F, I>
+where
+    D: Iterator<Item = Duration>,
+    F: FnMut(usize) -> I,
+    I: Iterator<Item = Result<T, E>>,
+{
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::strategy::Immediate;
+
+    #[test]
+    fn resumes_after_error() {
+        let mut fail_at = vec![4, 2];
+        let items = retry_iter(Immediate.take(2), |skip| {
+            let fail = fail_at.pop();
+            (skip..6).map(move |n| if Some(n) == fail { Err(n) } else { Ok(n) })
+        })
+        .collect::<Result<Vec<_>, _>>();
+        assert_eq!(items.unwrap(), vec![0, 1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn ends_with_error() {
+        let mut it = retry_iter(Immediate.take(1), |skip| {
+            (skip..).map(|n| if n == 1 { Err("down") } else { Ok(n) })
+        });
+        assert!(matches!(it.next(), Some(Ok(0))));
+        assert!(matches!(
+            it.next(),
+            Some(Err(RetryErr::FailedAttempt {
+                tries: 1,
+                err: "down",
+                ..
+            }))
+        ));
+        assert!(it.next().is_none());
+    }
+}
@@ -0,0 +1,88 @@
+//! retryable errors
+//!
+//! Implement `Retryable` on an error type to mark which of its errors are
+//! transient once, then use [`retry_transient`](crate::retry_transient) to
+//! retry them without classifying the error at every call site
+//!
+//! ```rust
+//! # use std::time::Duration;
+//! use retry_fn::Retryable;
+//!
+//! enum ApiError {
+//!     RateLimited { retry_after: Duration },
+//!     Unavailable,
+//!     NotFound,
+//! }
+//!
+//! impl Retryable for ApiError {
+//!     fn is_retryable(&self) -> bool {
+//!         !matches!(self, ApiError::NotFound)
+//!     }
+//!
+//!     fn retry_hint(&self) -> Option<Duration> {
+//!         match self {
+//!             ApiError::RateLimited { retry_after } => Some(*retry_after),
+//!             _ => None,
+//!         }
+//!     }
+//! }
+//! ```
+#[cfg(feature = "std")]
+use crate::strategy::RetryStrategy;
+use core::time::Duration;
+
+/// An error that knows whether the operation that failed with it should be
+/// retried
+pub trait Retryable {
+    /// Whether the operation should be retried
+    fn is_retryable(&self) -> bool;
+
+    /// How long to wait before retrying, used instead of the strategy's delay.
+    /// `None` is the default
+    fn retry_hint(&self) -> Option<Duration> {
+        None
+    }
+}
+
+impl<E> Retryable for &E
+where
+    E: Retryable + ?Sized,
+{
+    fn is_retryable(&self) -> bool {
+        (**self).is_retryable()
+    }
+
+    fn retry_hint(&self) -> Option<Duration> {
+        (**self).retry_hint()
+    }
+}
+
+/// Interruptions, timeouts and dropped connections are retryable, see
+/// [`io::TRANSIENT_KINDS`](crate::io::TRANSIENT_KINDS)
+#[cfg(feature = "std")]
+impl Retryable for std::io::Error {
+    fn is_retryable(&self) -> bool {
+        crate::io::is_transient(self)
+    }
+}
+
+/// a strategy that still decides when to stop, but defers to the error's
+/// `retry_hint` for how long to wait
+#[cfg(feature = "std")]
+pub(crate) struct Hinted<S>(pub(crate) S);
+
+#[cfg(feature = "std")]
+impl<S, E> RetryStrategy<E> for Hinted<S>
+where
+    S: RetryStrategy<E>,
+    E: Retryable,
+{
+    fn delay(&mut self, attempt: usize, err: Option<&E>) -> Option<Duration> {
+        let delay = self.0.delay(attempt, err)?;
+        Some(err.and_then(Retryable::retry_hint).unwrap_or(delay))
+    }
+
+    fn remaining(&self) -> Option<usize> {
+        self.0.remaining()
+    }
+}
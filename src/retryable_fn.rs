@@ -0,0 +1,206 @@
+//! closure extensions
+//!
+//! `RetryableFn` gives any `FnMut() -> Result<T, E>` methods to retry itself,
+//! so the operation reads first at the call site. With the `tokio-runtime` or
+//! `async-runtime` feature, `AsyncRetryExt` does the same for a function
+//! returning a future
+//!
+//! ```rust,no_run
+//! # use std::{fs, io};
+//! use retry_fn::{strategy::ExponentialBackoff, RetryableFn};
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let text = (|| fs::read_to_string("status"))
+//!     .retry_if(ExponentialBackoff::from_millis(100).take(3), |e: &io::Error| {
+//!         e.kind() != io::ErrorKind::NotFound
+//!     })?;
+//! # Ok(())
+//! # }
+//! ```
+#[cfg(any(feature = "tokio-runtime", feature = "async-runtime"))]
+use crate::runtime::{DefaultSleeper, Runtime};
+#[cfg(any(feature = "tokio-runtime", feature = "async-runtime"))]
+use crate::RetryResult;
+use crate::{strategy::RetryStrategy, RetryErr};
+#[cfg(any(feature = "tokio-runtime", feature = "async-runtime"))]
+use std::{future::Future, time::Duration};
+
+/// Extension methods to retry a function returning a plain `Result`
+pub trait RetryableFn<T, E>: FnMut() -> Result<T, E> + Sized {
+    /// Retry every error until the strategy ends, see
+    /// [`retry_result`](crate::retry_result)
+    fn retry<S>(mut self, strategy: S) -> Result<T, RetryErr<E>>
+    where
+        S: RetryStrategy<E>,
+    {
+        crate::retry_result(strategy, |_| self())
+    }
+
+    /// Retry errors `predicate` returns `true` for until the strategy ends,
+    /// see [`retry_if`](crate::retry_if)
+    fn retry_if<S, P>(mut self, strategy: S, predicate: P) -> Result<T, RetryErr<E>>
+    where
+        S: RetryStrategy<E>,
+        P: FnMut(&E) -> bool,
+    {
+        crate::retry_if(strategy, |_| self(), predicate)
+    }
+}
+
+impl<F, T, E> RetryableFn<T, E> for F where F: FnMut() -> Result<T, E> {}
+
+/// Extension methods to retry a function returning a future of a plain
+/// `Result`, waiting with the timer of the enabled runtime
+///
+/// ```rust,no_run
+/// use retry_fn::{strategy::ExponentialBackoff, AsyncRetryExt};
+/// # async fn fetch() -> Result<String, std::io::Error> { Ok(String::new()) }
+/// # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+/// let body = fetch.retry(ExponentialBackoff::from_millis(100).take(3)).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(any(feature = "tokio-runtime", feature = "async-runtime"))]
+pub trait AsyncRetryExt<Fut, T, E>: FnMut() -> Fut + Sized
+where
+    Fut: Future<Output = Result<T, E>>,
+{
+    /// Retry every error until the strategy ends, see
+    /// [`Runtime::retry_result`]
+    fn retry<S>(mut self, strategy: S) -> impl Future<Output = Result<T, RetryErr<E>>>
+    where
+        S: RetryStrategy<E>,
+    {
+        Runtime::<DefaultSleeper>::retry_result(strategy, move |_| self())
+    }
+
+    /// Retry errors `predicate` returns `true` for until the strategy ends,
+    /// see [`Runtime::retry_if`]
+    fn retry_if<S, P>(
+        mut self,
+        strategy: S,
+        predicate: P,
+    ) -> impl Future<Output = Result<T, RetryErr<E>>>
+    where
+        S: RetryStrategy<E>,
+        P: Fn(&E) -> bool,
+    {
+        Runtime::<DefaultSleeper>::retry_if(strategy, move |_| self(), predicate)
+    }
+
+    /// Retry every error with the delays of `iter`, giving up on an attempt
+    /// that takes longer than `per_attempt`, see
+    /// [`Runtime::retry_with_timeout`]
+    fn with_timeout<I>(
+        mut self,
+        iter: I,
+        per_attempt: Duration,
+    ) -> impl Future<Output = Result<T, RetryErr<E>>>
+    where
+        I: IntoIterator<Item = Duration>,
+    {
+        Runtime::<DefaultSleeper>::retry_with_timeout(iter, per_attempt, move |_| {
+            let fut = self();
+            async move {
+                match fut.await {
+                    Ok(val) => RetryResult::Ok(val),
+                    Err(err) => RetryResult::RetryWith(err),
+                }
+            }
+        })
+    }
+}
+
+#[cfg(any(feature = "tokio-runtime", feature = "async-runtime"))]
+impl<F, Fut, T, E> AsyncRetryExt<Fut, T, E> for F
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::strategy::Constant;
+    #[cfg(feature = "tokio-runtime")]
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    #[test]
+    fn postfix() {
+        let mut n = 0;
+        let res = (|| {
+            n += 1;
+            if n < 3 {
+                Err("busy")
+            } else {
+                Ok(n)
+            }
+        })
+        .retry(Constant::from_millis(1).take(5));
+        assert_eq!(res.ok(), Some(3));
+
+        let mut n = 0;
+        let res = (|| {
+            n += 1;
+            Err::<(), _>(if n < 2 { "busy" } else { "fatal" })
+        })
+        .retry_if(Constant::from_millis(1).take(5), |e| *e == "busy");
+        assert!(matches!(
+            res,
+            Err(RetryErr::FailedAttempt {
+                tries: 1,
+                err: "fatal",
+                ..
+            })
+        ));
+    }
+
+    #[cfg(feature = "tokio-runtime")]
+    #[tokio::test(start_paused = true)]
+    async fn postfix_async() {
+        let n = Arc::new(AtomicUsize::new(0));
+        let fetch = {
+            let n = n.clone();
+            move || {
+                let n = n.clone();
+                async move {
+                    match n.fetch_add(1, Ordering::SeqCst) {
+                        0 => Err("busy"),
+                        1 => Err("fatal"),
+                        n => Ok(n),
+                    }
+                }
+            }
+        };
+        // the future stays `Send`, so it can be spawned
+        let res = tokio::spawn(fetch.clone().retry(Constant::from_millis(1).take(5)))
+            .await
+            .unwrap();
+        assert_eq!(res.ok(), Some(2));
+
+        n.store(0, Ordering::SeqCst);
+        let res = fetch
+            .clone()
+            .retry_if(Constant::from_millis(1).take(5), |e| *e == "busy")
+            .await;
+        assert!(matches!(
+            res,
+            Err(RetryErr::FailedAttempt {
+                tries: 1,
+                err: "fatal",
+                ..
+            })
+        ));
+
+        let res = (|| async {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            Ok::<_, ()>(())
+        })
+        .with_timeout(Constant::from_millis(1).take(3), Duration::from_millis(10))
+        .await;
+        assert!(matches!(res, Err(RetryErr::TimedOut { tries: 2, .. })));
+    }
+}
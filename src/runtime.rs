@@ -0,0 +1,1806 @@
+//! runtime-agnostic async retries
+//!
+//! The async retry loops are written once here, generic over a [`Sleeper`]
+//! that provides the timer. The `tokio` and `async_std` modules are this with
+//! their runtime's timer, and any other runtime can be used by implementing
+//! `Sleeper` for it and calling the functions on [`Runtime`], or by passing
+//! its sleep function to [`retry_with_sleep`]
+//!
+//! ```rust,no_run
+//! # mod my_runtime { pub async fn sleep(_: std::time::Duration) {} }
+//! use retry_fn::{
+//!     runtime::{Runtime, Sleeper},
+//!     strategy::Constant,
+//!     RetryResult,
+//! };
+//! use std::{future::Future, pin::Pin, time::Duration};
+//!
+//! struct MyTimer;
+//!
+//! impl Sleeper for MyTimer {
+//!     type Sleep = Pin<Box<dyn Future<Output = ()> + Send>>;
+//!
+//!     fn sleep(dur: Duration) -> Self::Sleep {
+//!         Box::pin(my_runtime::sleep(dur))
+//!     }
+//! }
+//!
+//! # async fn run() {
+//! let res = Runtime::<MyTimer>::retry(Constant::from_millis(100).take(5), |op| async move {
+//!     if op.retries >= 2 {
+//!         RetryResult::<_, ()>::Ok(op.retries)
+//!     } else {
+//!         RetryResult::Retry()
+//!     }
+//! })
+//! .await;
+//! assert_eq!(res.ok(), Some(2));
+//! # }
+//! ```
+#[cfg(feature = "stream")]
+use crate::Attempt;
+use crate::{
+    circuit::CircuitBreaker,
+    exhausted, next_delay,
+    retryable::Hinted,
+    strategy::{MaxRetries, RetryStrategy},
+    Bulkhead, Instant, RetryBudget, RetryErr, RetryOp, RetryResult, RetryStats, Retryable,
+    Stopwatch,
+};
+use std::{
+    convert::Infallible,
+    error::Error,
+    fmt,
+    future::{poll_fn, Future},
+    marker::PhantomData,
+    mem,
+    ops::ControlFlow,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex, PoisonError,
+    },
+    task::{Context, Poll},
+    time::Duration,
+};
+
+/// The timer of an async runtime
+pub trait Sleeper {
+    /// the future returned by `sleep`
+    type Sleep: Future<Output = ()>;
+
+    /// A future completing once `dur` has passed
+    fn sleep(dur: Duration) -> Self::Sleep;
+
+    /// A future completing at `deadline`, right away if it has passed. By
+    /// default this sleeps for the time left, override it if the timer can
+    /// wait for an instant itself
+    fn sleep_until(deadline: Instant) -> Self::Sleep {
+        Self::sleep(deadline.saturating_duration_since(Self::now()))
+    }
+
+    /// The current time of the timer. By default this is `Instant::now()`,
+    /// override it if the timer keeps its own clock, so deadlines and
+    /// elapsed times agree with when it wakes
+    fn now() -> Instant {
+        Instant::now()
+    }
+}
+
+/// the timer of the enabled runtime, tokio's if both are
+#[cfg(feature = "tokio-runtime")]
+pub(crate) type DefaultSleeper = crate::tokio::Timer;
+#[cfg(all(feature = "async-runtime", not(feature = "tokio-runtime")))]
+pub(crate) type DefaultSleeper = crate::async_std::Timer;
+
+/// Async retries waiting with the timer of `Z`, see the [module docs](self)
+pub struct Runtime<Z> {
+    _sleeper: PhantomData<fn() -> Z>,
+}
+
+impl<Z> fmt::Debug for Runtime<Z> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Runtime").finish()
+    }
+}
+
+impl<Z> Runtime<Z>
+where
+    Z: Sleeper,
+{
+    /// Retry a future based on an iterator over Duration. A timer will be run for
+    /// each item in the iterator. The loop is a [`RetryFuture`], so it can be
+    /// stored without boxing
+    ///
+    /// # Returns
+    /// If successful, return `Ok`, otherwise return `Retry` to try again or `Err`
+    /// to exit with an error
+    pub fn retry<I, F, Fut, T, E>(iter: I, f: F) -> RetryFuture<Z, I::IntoIter, F, Fut>
+    where
+        I: IntoIterator<Item = Duration>,
+        F: FnMut(RetryOp) -> Fut,
+        Fut: Future<Output = RetryResult<T, E>>,
+    {
+        RetryFuture {
+            delays: iter.into_iter(),
+            f,
+            watch: Stopwatch::timed(Z::now),
+            count: 0,
+            total_delay: Duration::from_millis(0),
+            state: State::Idle,
+        }
+    }
+
+    /// Retry a future based on an iterator over Duration as a stream with an
+    /// item for each attempt, so progress can be shown while it runs. A failed
+    /// attempt that will be retried is yielded before its delay is waited.
+    /// Enable the `stream` feature to use this
+    ///
+    /// # Returns
+    /// A stream ending after the `Ok` or `Err` item
+    #[cfg(feature = "stream")]
+    pub fn retry_stream<I, F, Fut, T, E>(iter: I, f: F) -> AttemptStream<Z, I::IntoIter, F, Fut>
+    where
+        I: IntoIterator<Item = Duration>,
+        F: FnMut(RetryOp) -> Fut,
+        Fut: Future<Output = RetryResult<T, E>>,
+    {
+        AttemptStream {
+            delays: iter.into_iter(),
+            f,
+            watch: Stopwatch::timed(Z::now),
+            op: None,
+            count: 0,
+            total_delay: Duration::from_millis(0),
+            state: State::Idle,
+        }
+    }
+
+    /// Rebuild a stream with `factory` whenever it yields an error, waiting
+    /// for the delays of `iter` in between, and keep yielding its items.
+    /// `token` picks a resume token out of an item, and the latest one is
+    /// passed to `factory` so the new stream can continue where the old one
+    /// stopped. Once the rebuilt stream yields an item the delays start over
+    /// from a clone of `iter`. Enable the `stream` feature to use this
+    ///
+    /// # Returns
+    /// A stream of the items, ending when the inner stream ends or with the
+    /// last error as a `FailedAttempt` once the delays end
+    #[cfg(feature = "stream")]
+    pub fn resume_stream<I, C, K, F, St, T, E>(
+        iter: I,
+        token: C,
+        factory: F,
+    ) -> ResumeStream<Z, I::IntoIter, C, K, F, St>
+    where
+        I: IntoIterator<Item = Duration>,
+        I::IntoIter: Clone,
+        C: FnMut(&T) -> Option<K>,
+        F: FnMut(Option<&K>) -> St,
+        St: futures_core::Stream<Item = Result<T, E>>,
+    {
+        let strategy = iter.into_iter();
+        ResumeStream {
+            delays: strategy.clone(),
+            strategy,
+            token,
+            last_token: None,
+            factory,
+            count: 0,
+            total_delay: Duration::from_millis(0),
+            state: State::Idle,
+        }
+    }
+
+    /// Wrap the sink built by `factory`, rebuilding it with the delays of
+    /// `iter` in between whenever sending or flushing fails and sending the
+    /// failed item again. Each item is flushed before the next is accepted,
+    /// so there's only ever one item to replay. Once an item gets through
+    /// the delays start over from a clone of `iter`. Enable the `sink`
+    /// feature to use this
+    ///
+    /// # Returns
+    /// A sink failing with the last error as a `FailedAttempt` once the
+    /// delays end
+    #[cfg(feature = "sink")]
+    pub fn retry_sink<I, F, Si, T>(iter: I, factory: F) -> RetrySink<Z, I::IntoIter, F, Si, T>
+    where
+        I: IntoIterator<Item = Duration>,
+        I::IntoIter: Clone,
+        F: FnMut() -> Si,
+        Si: futures_sink::Sink<T>,
+        T: Clone,
+    {
+        let strategy = iter.into_iter();
+        RetrySink {
+            delays: strategy.clone(),
+            strategy,
+            factory,
+            pending: None,
+            sent: false,
+            count: 0,
+            total_delay: Duration::from_millis(0),
+            state: State::Idle,
+        }
+    }
+
+    /// Retry a future based on an iterator over Duration, returning how
+    /// many attempts it took along with the value on success
+    ///
+    /// # Returns
+    /// If successful, return `Ok`, otherwise return `Retry` to try again or `Err`
+    /// to exit with an error
+    pub async fn retry_with_stats<I, F, Fut, T, E>(
+        iter: I,
+        mut f: F,
+    ) -> Result<(T, RetryStats), RetryErr<E>>
+    where
+        I: IntoIterator<Item = Duration>,
+        F: FnMut(RetryOp) -> Fut,
+        Fut: Future<Output = RetryResult<T, E>>,
+    {
+        Self::retry(iter, |op| {
+            let fut = f(op);
+            async move { fut.await.map(|val| (val, op)) }
+        })
+        .await
+        .map(|(val, op)| (val, RetryStats::finish(op)))
+    }
+
+    /// Retry a future based on an iterator over Duration, calling `f` at
+    /// most `times` times however long the iterator is
+    ///
+    /// # Returns
+    /// If successful, return `Ok`, otherwise return `Retry` to try again or `Err`
+    /// to exit with an error
+    pub async fn retry_times<I, F, Fut, T, E>(iter: I, times: usize, f: F) -> Result<T, RetryErr<E>>
+    where
+        I: IntoIterator<Item = Duration>,
+        F: FnMut(RetryOp) -> Fut,
+        Fut: Future<Output = RetryResult<T, E>>,
+    {
+        Self::retry(MaxRetries::new(iter.into_iter(), times), f).await
+    }
+
+    /// Retry a future based on an iterator over Duration, giving up on an
+    /// attempt once it has run for `per_attempt`. The attempt's future is
+    /// dropped and it's retried like `Retry`
+    ///
+    /// # Returns
+    /// If successful, return `Ok`, otherwise return `Retry` to try again or `Err`
+    /// to exit with an error. If the last attempt timed out, returns
+    /// `TimedOut`
+    pub async fn retry_with_timeout<I, F, Fut, T, E>(
+        iter: I,
+        per_attempt: Duration,
+        mut f: F,
+    ) -> Result<T, RetryErr<E>>
+    where
+        I: IntoIterator<Item = Duration>,
+        F: FnMut(RetryOp) -> Fut,
+        Fut: Future<Output = RetryResult<T, E>>,
+    {
+        // atomic rather than a `Cell` so the future stays `Send`
+        let timed_out = &AtomicBool::new(false);
+        let res = Self::retry(iter, |op| {
+            let fut = Timeout {
+                fut: Box::pin(f(op)),
+                sleep: Box::pin(Z::sleep(per_attempt)),
+            };
+            async move {
+                let res = fut.await;
+                timed_out.store(res.is_none(), Ordering::Relaxed);
+                res.unwrap_or(RetryResult::Retry())
+            }
+        })
+        .await;
+        match res {
+            Err(RetryErr::IteratorEnded { tries, total_delay })
+                if timed_out.load(Ordering::Relaxed) =>
+            {
+                Err(RetryErr::TimedOut { tries, total_delay })
+            }
+            res => res,
+        }
+    }
+
+    /// Retry a future based on an iterator over Duration, starting another
+    /// attempt alongside once the running ones have taken `soft_timeout`
+    /// without finishing. Slow attempts keep running and the first to
+    /// succeed wins, the others are dropped. Each extra attempt uses up a
+    /// delay of the strategy without waiting it, which limits how many can
+    /// run. Once every running attempt has failed, the loop waits for the
+    /// next delay as usual
+    ///
+    /// # Returns
+    /// If successful, return `Ok`, otherwise return `Retry` to try again or `Err`
+    /// to exit with an error. An `Err` from any attempt ends the loop
+    pub async fn retry_hedged<I, F, Fut, T, E>(
+        iter: I,
+        soft_timeout: Duration,
+        mut f: F,
+    ) -> Result<T, RetryErr<E>>
+    where
+        I: IntoIterator<Item = Duration>,
+        F: FnMut(RetryOp) -> Fut,
+        Fut: Future<Output = RetryResult<T, E>>,
+    {
+        let mut watch = Stopwatch::timed(Z::now);
+        let mut count = 0;
+        let mut total_delay = Duration::from_millis(0);
+        let mut delays = iter.into_iter();
+        let mut running = vec![Box::pin(f(watch.op(count, total_delay)))];
+        let mut last_err = None;
+        let mut ended = false;
+        // the soft deadline of the newest attempt, none once the strategy
+        // can't start another one
+        let mut soft = Some(Box::pin(Z::sleep(soft_timeout)));
+        loop {
+            let finished = poll_fn(|cx| {
+                for (i, fut) in running.iter_mut().enumerate() {
+                    if let Poll::Ready(res) = fut.as_mut().poll(cx) {
+                        return Poll::Ready(Some((i, res)));
+                    }
+                }
+                match &mut soft {
+                    Some(soft) => soft.as_mut().poll(cx).map(|()| None),
+                    None => Poll::Pending,
+                }
+            })
+            .await;
+            let res = match finished {
+                Some((i, res)) => {
+                    drop(running.swap_remove(i));
+                    res
+                }
+                None => {
+                    match delays.next() {
+                        Some(_) => {
+                            count += 1;
+                            running.push(Box::pin(f(watch.op(count, total_delay))));
+                            soft = Some(Box::pin(Z::sleep(soft_timeout)));
+                        }
+                        None => {
+                            ended = true;
+                            soft = None;
+                        }
+                    }
+                    continue;
+                }
+            };
+            match res.step(count, total_delay) {
+                ControlFlow::Continue(err) => last_err = err.or(last_err),
+                ControlFlow::Break(res) => return res,
+            }
+            if !running.is_empty() {
+                continue;
+            }
+            watch.done();
+            let dur = match if ended { None } else { delays.next() } {
+                Some(dur) => dur,
+                None => return Err(RetryErr::ended(count, total_delay, last_err)),
+            };
+            Z::sleep(dur).await;
+            total_delay = total_delay.saturating_add(dur);
+            count += 1;
+            running.push(Box::pin(f(watch.op(count, total_delay))));
+            soft = Some(Box::pin(Z::sleep(soft_timeout)));
+        }
+    }
+
+    /// Hedge a future: start another attempt after each delay of `iter`
+    /// without cancelling the ones still running, and finish with the first
+    /// to succeed, dropping the others. An attempt failing when none are left
+    /// running starts the next one right away, using up its delay. Unlike
+    /// [`Runtime::retry_hedged`], the delays are how long to wait before each
+    /// extra attempt, not backoff between failures
+    ///
+    /// # Returns
+    /// If successful, return `Ok`, otherwise return `Retry` to try again or `Err`
+    /// to exit with an error. An `Err` from any attempt ends the loop
+    pub async fn hedge<I, F, Fut, T, E>(iter: I, mut f: F) -> Result<T, RetryErr<E>>
+    where
+        I: IntoIterator<Item = Duration>,
+        F: FnMut(RetryOp) -> Fut,
+        Fut: Future<Output = RetryResult<T, E>>,
+    {
+        let mut watch = Stopwatch::timed(Z::now);
+        let mut count = 0;
+        let mut total_delay = Duration::from_millis(0);
+        let mut delays = iter.into_iter();
+        let mut running = vec![Box::pin(f(watch.op(count, total_delay)))];
+        // the delay before the next extra attempt, taken as each one starts
+        let mut hedge = delays.next().map(|dur| (dur, Box::pin(Z::sleep(dur))));
+        let mut last_err = None;
+        loop {
+            let finished = poll_fn(|cx| {
+                for (i, fut) in running.iter_mut().enumerate() {
+                    if let Poll::Ready(res) = fut.as_mut().poll(cx) {
+                        return Poll::Ready(Some((i, res)));
+                    }
+                }
+                match &mut hedge {
+                    Some((_, hedge)) => hedge.as_mut().poll(cx).map(|()| None),
+                    None => Poll::Pending,
+                }
+            })
+            .await;
+            match finished {
+                Some((i, res)) => {
+                    drop(running.swap_remove(i));
+                    match res.step(count, total_delay) {
+                        ControlFlow::Continue(err) => last_err = err.or(last_err),
+                        ControlFlow::Break(res) => return res,
+                    }
+                    if !running.is_empty() {
+                        continue;
+                    }
+                    watch.done();
+                    if hedge.take().is_none() {
+                        return Err(RetryErr::ended(count, total_delay, last_err));
+                    }
+                }
+                None => {
+                    if let Some((dur, _)) = hedge.take() {
+                        total_delay = total_delay.saturating_add(dur);
+                    }
+                }
+            }
+            count += 1;
+            running.push(Box::pin(f(watch.op(count, total_delay))));
+            hedge = delays.next().map(|dur| (dur, Box::pin(Z::sleep(dur))));
+        }
+    }
+
+    /// Race up to `n` attempts of a future, starting them staggered by the
+    /// delays of `iter` as in [`Runtime::hedge`], and finish with the first
+    /// to succeed. The errors of every failed attempt are kept, in the order
+    /// the attempts failed. An `n` of 0 still runs one attempt
+    ///
+    /// # Returns
+    /// If successful, return `Ok`, otherwise a `FailedAttempt` with the errors
+    /// of all attempts, or `IteratorEnded` if none of them returned one. An
+    /// `Err` from any attempt ends the race
+    pub async fn retry_race<I, F, Fut, T, E>(
+        n: usize,
+        iter: I,
+        mut f: F,
+    ) -> Result<T, RetryErr<Vec<E>>>
+    where
+        I: IntoIterator<Item = Duration>,
+        F: FnMut(RetryOp) -> Fut,
+        Fut: Future<Output = RetryResult<T, E>>,
+    {
+        let errors = &Mutex::new(Vec::new());
+        let failed = |err| {
+            errors
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .push(err)
+        };
+        let res = Self::hedge(iter.into_iter().take(n.saturating_sub(1)), |op| {
+            let fut = f(op);
+            async move {
+                match fut.await {
+                    RetryResult::Ok(val) => RetryResult::Ok(val),
+                    RetryResult::Retry() => RetryResult::Retry(),
+                    RetryResult::RetryWith(err) => {
+                        failed(err);
+                        RetryResult::Retry()
+                    }
+                    RetryResult::Err(err) => {
+                        failed(err);
+                        RetryResult::Err(())
+                    }
+                }
+            }
+        })
+        .await;
+        let errors = mem::take(&mut *errors.lock().unwrap_or_else(PoisonError::into_inner));
+        match res {
+            Ok(val) => Ok(val),
+            Err(RetryErr::IteratorEnded { tries, total_delay }) if errors.is_empty() => {
+                Err(RetryErr::IteratorEnded { tries, total_delay })
+            }
+            Err(RetryErr::FailedAttempt {
+                tries, total_delay, ..
+            })
+            | Err(RetryErr::IteratorEnded { tries, total_delay }) => Err(RetryErr::FailedAttempt {
+                tries,
+                total_delay,
+                err: errors,
+            }),
+            // the rest carry no error, they're passed through as they are
+            Err(RetryErr::BudgetExhausted { tries, total_delay }) => {
+                Err(RetryErr::BudgetExhausted { tries, total_delay })
+            }
+            Err(RetryErr::Cancelled { tries, total_delay }) => {
+                Err(RetryErr::Cancelled { tries, total_delay })
+            }
+            Err(RetryErr::DeadlineExceeded { tries, total_delay }) => {
+                Err(RetryErr::DeadlineExceeded { tries, total_delay })
+            }
+            Err(RetryErr::CircuitOpen { tries, total_delay }) => {
+                Err(RetryErr::CircuitOpen { tries, total_delay })
+            }
+            Err(RetryErr::BulkheadFull { tries, total_delay }) => {
+                Err(RetryErr::BulkheadFull { tries, total_delay })
+            }
+            Err(RetryErr::TimedOut { tries, total_delay }) => {
+                Err(RetryErr::TimedOut { tries, total_delay })
+            }
+            Err(RetryErr::Panicked {
+                tries,
+                total_delay,
+                msg,
+            }) => Err(RetryErr::Panicked {
+                tries,
+                total_delay,
+                msg,
+            }),
+        }
+    }
+
+    /// Retry a future based on an iterator over Duration until `deadline`.
+    /// A delay that would wait past the deadline is cut short, and an
+    /// attempt still running at the deadline is dropped. See
+    /// [`crate::retry_with_deadline`]
+    ///
+    /// # Returns
+    /// If successful, return `Ok`, otherwise return `Retry` to try again or `Err`
+    /// to exit with an error. Once the deadline has passed, returns
+    /// `DeadlineExceeded`
+    pub async fn retry_with_deadline<I, F, Fut, T, E>(
+        iter: I,
+        deadline: Instant,
+        mut f: F,
+    ) -> Result<T, RetryErr<E>>
+    where
+        I: IntoIterator<Item = Duration>,
+        F: FnMut(RetryOp) -> Fut,
+        Fut: Future<Output = RetryResult<T, E>>,
+    {
+        let mut watch = Stopwatch::timed(Z::now);
+        let mut count = 0;
+        let mut total_delay = Duration::from_millis(0);
+        let mut delays = iter.into_iter();
+        if exhausted(&delays) {
+            return Err(RetryErr::IteratorEnded {
+                tries: 0,
+                total_delay,
+            });
+        }
+        loop {
+            let attempt = Timeout {
+                fut: Box::pin(f(watch.op(count, total_delay))),
+                sleep: Box::pin(Z::sleep(deadline.saturating_duration_since(Z::now()))),
+            };
+            let res = match attempt.await {
+                Some(res) => res,
+                None => {
+                    return Err(RetryErr::DeadlineExceeded {
+                        tries: count,
+                        total_delay,
+                    })
+                }
+            };
+            let err = match res.step(count, total_delay) {
+                ControlFlow::Continue(err) => err,
+                ControlFlow::Break(res) => return res,
+            };
+            watch.done();
+            let dur = match next_delay(&mut delays) {
+                Some(dur) => dur,
+                None => return Err(RetryErr::ended(count, total_delay, err)),
+            };
+            let left = deadline.saturating_duration_since(Z::now());
+            if left == Duration::from_millis(0) {
+                return Err(RetryErr::DeadlineExceeded {
+                    tries: count,
+                    total_delay,
+                });
+            }
+            let dur = dur.min(left);
+            Z::sleep(dur).await;
+            total_delay = total_delay.saturating_add(dur);
+            count += 1;
+        }
+    }
+
+    /// Retry a future based on an iterator over Duration until `cancel`
+    /// completes. Each attempt and delay races `cancel`, so it can be any
+    /// future, like a shutdown signal or a closed channel. `total_delay`
+    /// counts only the delays that ran to completion
+    ///
+    /// # Returns
+    /// If successful, return `Ok`, otherwise return `Retry` to try again or `Err`
+    /// to exit with an error. Once `cancel` completes, returns `Cancelled`
+    pub async fn retry_until<I, C, F, Fut, T, E>(
+        iter: I,
+        cancel: C,
+        mut f: F,
+    ) -> Result<T, RetryErr<E>>
+    where
+        I: IntoIterator<Item = Duration>,
+        C: Future,
+        F: FnMut(RetryOp) -> Fut,
+        Fut: Future<Output = RetryResult<T, E>>,
+    {
+        let mut cancel = Box::pin(cancel);
+        let mut watch = Stopwatch::timed(Z::now);
+        let mut count = 0;
+        let mut total_delay = Duration::from_millis(0);
+        let mut delays = iter.into_iter();
+        if exhausted(&delays) {
+            return Err(RetryErr::IteratorEnded {
+                tries: 0,
+                total_delay,
+            });
+        }
+        loop {
+            let attempt = f(watch.op(count, total_delay));
+            let res = match or_cancel(attempt, cancel.as_mut()).await {
+                Some(res) => res,
+                None => {
+                    return Err(RetryErr::Cancelled {
+                        tries: count,
+                        total_delay,
+                    })
+                }
+            };
+            let err = match res.step(count, total_delay) {
+                ControlFlow::Continue(err) => err,
+                ControlFlow::Break(res) => return res,
+            };
+            watch.done();
+            let dur = match next_delay(&mut delays) {
+                Some(dur) => dur,
+                None => return Err(RetryErr::ended(count, total_delay, err)),
+            };
+            if or_cancel(Z::sleep(dur), cancel.as_mut()).await.is_none() {
+                return Err(RetryErr::Cancelled {
+                    tries: count,
+                    total_delay,
+                });
+            }
+            total_delay = total_delay.saturating_add(dur);
+            count += 1;
+        }
+    }
+
+    /// Retry a future based on an iterator over Duration, waiting for
+    /// `delay` once before the first attempt. The initial delay is counted
+    /// in `total_delay`
+    ///
+    /// # Returns
+    /// If successful, return `Ok`, otherwise return `Retry` to try again or `Err`
+    /// to exit with an error
+    pub async fn retry_delayed<I, F, Fut, T, E>(
+        delay: Duration,
+        iter: I,
+        f: F,
+    ) -> Result<T, RetryErr<E>>
+    where
+        I: IntoIterator<Item = Duration>,
+        F: FnMut(RetryOp) -> Fut,
+        Fut: Future<Output = RetryResult<T, E>>,
+    {
+        delayed(Stopwatch::timed(Z::now), delay, iter, f, Z::sleep).await
+    }
+
+    /// Retry a future based on an iterator over Duration. A timer will be run for
+    /// each item in the iterator.
+    /// This takes a future that must implement `Unpin`, so it can be repeatedly
+    /// called in the loop
+    ///
+    /// # Returns
+    /// If successful, return `Ok`, otherwise return `Retry` to try again or `Err`
+    /// to exit with an error
+    pub async fn retry_unpin<I, Fut, T, E>(iter: I, mut f: Fut) -> Result<T, RetryErr<E>>
+    where
+        I: IntoIterator<Item = Duration>,
+        Fut: Future<Output = RetryResult<T, E>> + Unpin,
+    {
+        let mut count = 0;
+        let mut total_delay = Duration::from_millis(0);
+        let mut delays = iter.into_iter();
+        if exhausted(&delays) {
+            return Err(RetryErr::IteratorEnded {
+                tries: 0,
+                total_delay,
+            });
+        }
+        loop {
+            let err = match (&mut f).await.step(count, total_delay) {
+                ControlFlow::Continue(err) => err,
+                ControlFlow::Break(res) => return res,
+            };
+            let dur = match next_delay(&mut delays) {
+                Some(dur) => dur,
+                None => return Err(RetryErr::ended(count, total_delay, err)),
+            };
+            Z::sleep(dur).await;
+            total_delay = total_delay.saturating_add(dur);
+            count += 1;
+        }
+    }
+
+    /// Retry a future at a fixed rate: each delay is measured between the
+    /// starts of two attempts, so the time the future took is subtracted
+    /// from the sleep. Wake-ups are instants counted from the first attempt
+    /// and waited with [`Sleeper::sleep_until`], so the schedule doesn't
+    /// drift. See [`crate::retry_fixed_rate`]
+    ///
+    /// # Returns
+    /// If successful, return `Ok`, otherwise return `Retry` to try again or `Err`
+    /// to exit with an error
+    pub async fn retry_fixed_rate<I, F, Fut, T, E>(iter: I, mut f: F) -> Result<T, RetryErr<E>>
+    where
+        I: IntoIterator<Item = Duration>,
+        F: FnMut(RetryOp) -> Fut,
+        Fut: Future<Output = RetryResult<T, E>>,
+    {
+        let mut wake = Z::now();
+        let mut watch = Stopwatch::at(wake);
+        let mut count = 0;
+        let mut total_delay = Duration::from_millis(0);
+        let mut delays = iter.into_iter();
+        if exhausted(&delays) {
+            return Err(RetryErr::IteratorEnded {
+                tries: 0,
+                total_delay,
+            });
+        }
+        loop {
+            let op = watch.op_at(Z::now(), count, total_delay);
+            let err = match f(op).await.step(count, total_delay) {
+                ControlFlow::Continue(err) => err,
+                ControlFlow::Break(res) => return res,
+            };
+            watch.done_at(Z::now());
+            let dur = match next_delay(&mut delays) {
+                Some(dur) => dur,
+                None => return Err(RetryErr::ended(count, total_delay, err)),
+            };
+            let dur = match wake.checked_add(dur) {
+                Some(at) => {
+                    wake = at;
+                    let left = at.saturating_duration_since(Z::now());
+                    Z::sleep_until(at).await;
+                    left
+                }
+                // too far off to be an instant, so it won't wake anyway
+                None => {
+                    Z::sleep(dur).await;
+                    dur
+                }
+            };
+            total_delay = total_delay.saturating_add(dur);
+            count += 1;
+        }
+    }
+
+    /// Retry a future returning `ControlFlow` based on an iterator over
+    /// Duration. `Continue` tries again, `Break` returns its `Result`. See
+    /// [`crate::retry_flow`]
+    ///
+    /// # Returns
+    /// If successful, return `Break(Ok)`, otherwise return `Continue` to try
+    /// again or `Break(Err)` to exit with an error
+    pub async fn retry_flow<I, F, Fut, T, E>(iter: I, mut f: F) -> Result<T, RetryErr<E>>
+    where
+        I: IntoIterator<Item = Duration>,
+        F: FnMut(RetryOp) -> Fut,
+        Fut: Future<Output = ControlFlow<Result<T, E>>>,
+    {
+        Self::retry(iter, |op| {
+            let fut = f(op);
+            async move { fut.await.into() }
+        })
+        .await
+    }
+
+    /// Retry a future based on an iterator over Duration, taking each retry
+    /// from a shared [`RetryBudget`]. The call is recorded as a request in
+    /// the budget, and retrying stops with `BudgetExhausted` once the
+    /// budget has none left
+    ///
+    /// # Returns
+    /// If successful, return `Ok`, otherwise return `Retry` to try again or `Err`
+    /// to exit with an error
+    pub async fn retry_with_budget<I, F, Fut, T, E>(
+        budget: &RetryBudget,
+        iter: I,
+        mut f: F,
+    ) -> Result<T, RetryErr<E>>
+    where
+        I: IntoIterator<Item = Duration>,
+        F: FnMut(RetryOp) -> Fut,
+        Fut: Future<Output = RetryResult<T, E>>,
+    {
+        budget.deposit();
+        let mut watch = Stopwatch::timed(Z::now);
+        let mut count = 0;
+        let mut total_delay = Duration::from_millis(0);
+        let mut delays = iter.into_iter();
+        if exhausted(&delays) {
+            return Err(RetryErr::IteratorEnded {
+                tries: 0,
+                total_delay,
+            });
+        }
+        loop {
+            let err = match f(watch.op(count, total_delay))
+                .await
+                .step(count, total_delay)
+            {
+                ControlFlow::Continue(err) => err,
+                ControlFlow::Break(res) => return res,
+            };
+            watch.done();
+            let dur = match next_delay(&mut delays) {
+                Some(dur) => dur,
+                None => return Err(RetryErr::ended(count, total_delay, err)),
+            };
+            if !budget.try_withdraw() {
+                return Err(RetryErr::BudgetExhausted {
+                    tries: count,
+                    total_delay,
+                });
+            }
+            Z::sleep(dur).await;
+            total_delay = total_delay.saturating_add(dur);
+            count += 1;
+        }
+    }
+
+    /// Retry a future through a shared [`CircuitBreaker`], short-circuiting
+    /// attempts while it's open. See [`crate::retry_with_breaker`]
+    ///
+    /// # Returns
+    /// If successful, return `Ok`, otherwise return `Retry` to try again or `Err`
+    /// to exit with an error. If the strategy ends with the last attempt
+    /// short-circuited, returns `CircuitOpen`
+    pub async fn retry_with_breaker<I, F, Fut, T, E>(
+        breaker: &CircuitBreaker,
+        iter: I,
+        mut f: F,
+    ) -> Result<T, RetryErr<E>>
+    where
+        I: IntoIterator<Item = Duration>,
+        F: FnMut(RetryOp) -> Fut,
+        Fut: Future<Output = RetryResult<T, E>>,
+    {
+        // an atomic rather than a `Cell`, so the future stays `Send`
+        let open = AtomicBool::new(false);
+        let res = Self::retry(iter, |op| {
+            let fut = if breaker.try_acquire() {
+                Some(f(op))
+            } else {
+                None
+            };
+            open.store(fut.is_none(), Ordering::Relaxed);
+            async move {
+                let res = match fut {
+                    Some(fut) => fut.await,
+                    None => return RetryResult::Retry(),
+                };
+                if res.is_ok() {
+                    breaker.record_success();
+                } else {
+                    breaker.record_failure();
+                }
+                res
+            }
+        })
+        .await;
+        match res {
+            Err(RetryErr::IteratorEnded { tries, total_delay }) if open.load(Ordering::Relaxed) => {
+                Err(RetryErr::CircuitOpen { tries, total_delay })
+            }
+            res => res,
+        }
+    }
+
+    /// Retry a future, running each attempt under a permit of a shared
+    /// [`Bulkhead`]. See [`crate::retry_with_bulkhead`]
+    ///
+    /// # Returns
+    /// If successful, return `Ok`, otherwise return `Retry` to try again or `Err`
+    /// to exit with an error. If the strategy ends without a permit for the
+    /// last attempt, returns `BulkheadFull`
+    pub async fn retry_with_bulkhead<I, F, Fut, T, E>(
+        bulkhead: &Bulkhead,
+        iter: I,
+        mut f: F,
+    ) -> Result<T, RetryErr<E>>
+    where
+        I: IntoIterator<Item = Duration>,
+        F: FnMut(RetryOp) -> Fut,
+        Fut: Future<Output = RetryResult<T, E>>,
+    {
+        let full = AtomicBool::new(false);
+        let res = Self::retry(iter, |op| {
+            let permit = bulkhead.try_acquire();
+            full.store(permit.is_none(), Ordering::Relaxed);
+            let fut = permit.as_ref().map(|_| f(op));
+            async move {
+                // held until the attempt finishes
+                let _permit = permit;
+                match fut {
+                    Some(fut) => fut.await,
+                    None => RetryResult::Retry(),
+                }
+            }
+        })
+        .await;
+        match res {
+            Err(RetryErr::IteratorEnded { tries, total_delay }) if full.load(Ordering::Relaxed) => {
+                Err(RetryErr::BulkheadFull { tries, total_delay })
+            }
+            res => res,
+        }
+    }
+
+    /// Retry a future, using the error of each failed attempt to pick the
+    /// next delay. Return `RetryWith` from the future to pass the error to
+    /// the strategy, any `Iterator<Item = Duration>` can also be used and
+    /// will ignore it. See [`crate::retry_strategy`] for an example
+    ///
+    /// # Returns
+    /// If successful, return `Ok`, otherwise return `Retry` or `RetryWith`
+    /// to try again or `Err` to exit with an error. When the strategy ends
+    /// after a `RetryWith`, that error is returned as a `FailedAttempt`
+    pub async fn retry_strategy<S, F, Fut, T, E>(
+        mut strategy: S,
+        mut f: F,
+    ) -> Result<T, RetryErr<E>>
+    where
+        S: RetryStrategy<E>,
+        F: FnMut(RetryOp) -> Fut,
+        Fut: Future<Output = RetryResult<T, E>>,
+    {
+        let mut watch = Stopwatch::timed(Z::now);
+        let mut count = 0;
+        let mut total_delay = Duration::from_millis(0);
+        if strategy.remaining() == Some(0) {
+            return Err(RetryErr::IteratorEnded {
+                tries: 0,
+                total_delay,
+            });
+        }
+        loop {
+            let err = match f(watch.op(count, total_delay))
+                .await
+                .step(count, total_delay)
+            {
+                ControlFlow::Continue(err) => err,
+                ControlFlow::Break(res) => return res,
+            };
+            watch.done();
+            let dur = match strategy
+                .delay(count, err.as_ref())
+                .filter(|_| strategy.remaining() != Some(0))
+            {
+                Some(dur) => dur,
+                None => return Err(RetryErr::ended(count, total_delay, err)),
+            };
+            Z::sleep(dur).await;
+            total_delay = total_delay.saturating_add(dur);
+            count += 1;
+        }
+    }
+
+    /// Retry a future returning a plain `Result`, using `predicate` to
+    /// decide which errors are worth retrying. See [`crate::retry_if`]
+    ///
+    /// # Returns
+    /// The first `Ok`, otherwise the last error as a `FailedAttempt` once
+    /// an error isn't retryable or the strategy ends
+    pub async fn retry_if<S, F, Fut, P, T, E>(
+        strategy: S,
+        mut f: F,
+        predicate: P,
+    ) -> Result<T, RetryErr<E>>
+    where
+        S: RetryStrategy<E>,
+        F: FnMut(RetryOp) -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+        P: Fn(&E) -> bool,
+    {
+        let predicate = &predicate;
+        Self::retry_strategy(strategy, |op| {
+            let fut = f(op);
+            async move {
+                match fut.await {
+                    Ok(val) => RetryResult::Ok(val),
+                    Err(err) if predicate(&err) => RetryResult::RetryWith(err),
+                    Err(err) => RetryResult::Err(err),
+                }
+            }
+        })
+        .await
+    }
+
+    /// Retry a future returning a plain `Result`, retrying on any error
+    /// until the strategy ends. See [`crate::retry_result`]
+    ///
+    /// # Returns
+    /// The first `Ok`, otherwise the last error as a `FailedAttempt` once
+    /// the strategy ends
+    pub async fn retry_result<S, F, Fut, T, E>(strategy: S, f: F) -> Result<T, RetryErr<E>>
+    where
+        S: RetryStrategy<E>,
+        F: FnMut(RetryOp) -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        Self::retry_if(strategy, f, |_| true).await
+    }
+
+    /// Retry a future returning a plain `Result` with the process-wide
+    /// default policy. See [`crate::retry_default`]
+    ///
+    /// # Returns
+    /// The first `Ok`, otherwise the last error as a `FailedAttempt` once
+    /// an error isn't retryable or the strategy ends
+    pub async fn retry_default<F, Fut, T, E>(f: F) -> Result<T, RetryErr<E>>
+    where
+        F: FnMut(RetryOp) -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+        E: Error + 'static,
+    {
+        let policy = crate::default_policy();
+        Self::retry_if(policy.delays(), f, |e| policy.is_retryable(e)).await
+    }
+
+    /// Poll the future returned by `condition` until it resolves to
+    /// `true`. See [`crate::wait_until`]
+    ///
+    /// # Returns
+    /// `Ok` once `condition` is `true`, otherwise `IteratorEnded` once
+    /// the strategy ends
+    pub async fn wait_until<I, F, Fut>(
+        iter: I,
+        mut condition: F,
+    ) -> Result<(), RetryErr<Infallible>>
+    where
+        I: IntoIterator<Item = Duration>,
+        F: FnMut() -> Fut,
+        Fut: Future<Output = bool>,
+    {
+        Self::retry(iter, |_| {
+            let fut = condition();
+            async move {
+                if fut.await {
+                    RetryResult::Ok(())
+                } else {
+                    RetryResult::Retry()
+                }
+            }
+        })
+        .await
+    }
+
+    /// Poll the future returned by `f` until it resolves to `Some`. See
+    /// [`crate::poll_until`]
+    ///
+    /// # Returns
+    /// The first `Some` value, otherwise `IteratorEnded` once the strategy
+    /// ends
+    pub async fn poll_until<I, F, Fut, T>(iter: I, mut f: F) -> Result<T, RetryErr<Infallible>>
+    where
+        I: IntoIterator<Item = Duration>,
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Option<T>>,
+    {
+        Self::retry(iter, |_| {
+            let fut = f();
+            async move { RetryResult::ok_or_retry(fut.await) }
+        })
+        .await
+    }
+
+    /// Retry a future returning a plain `Result` whose error implements
+    /// [`Retryable`]. See [`crate::retry_transient`]
+    ///
+    /// # Returns
+    /// The first `Ok`, otherwise the last error as a `FailedAttempt` once
+    /// an error isn't retryable or the strategy ends
+    pub async fn retry_transient<S, F, Fut, T, E>(strategy: S, mut f: F) -> Result<T, RetryErr<E>>
+    where
+        S: RetryStrategy<E>,
+        F: FnMut(RetryOp) -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+        E: Retryable,
+    {
+        Self::retry_strategy(Hinted(strategy), |op| {
+            let fut = f(op);
+            async move {
+                match fut.await {
+                    Ok(val) => RetryResult::Ok(val),
+                    Err(err) if err.is_retryable() => RetryResult::RetryWith(err),
+                    Err(err) => RetryResult::Err(err),
+                }
+            }
+        })
+        .await
+    }
+}
+
+/// Retry a future based on an iterator over Duration, waiting between
+/// attempts with the future returned by `sleep`. No runtime feature is
+/// needed, so a library can use whichever timer its caller provides
+///
+/// ```rust,no_run
+/// # mod my_runtime { pub async fn sleep(_: std::time::Duration) {} }
+/// use retry_fn::{runtime::retry_with_sleep, strategy::Constant, RetryResult};
+/// # async fn run() {
+/// let res = retry_with_sleep(Constant::from_millis(100).take(5), my_runtime::sleep, |op| async move {
+///     if op.retries >= 2 {
+///         RetryResult::<_, ()>::Ok(op.retries)
+///     } else {
+///         RetryResult::Retry()
+///     }
+/// })
+/// .await;
+/// assert_eq!(res.ok(), Some(2));
+/// # }
+/// ```
+///
+/// # Returns
+/// If successful, return `Ok`, otherwise return `Retry` to try again or `Err`
+/// to exit with an error
+pub async fn retry_with_sleep<I, S, Sl, F, Fut, T, E>(
+    iter: I,
+    sleep: S,
+    f: F,
+) -> Result<T, RetryErr<E>>
+where
+    I: IntoIterator<Item = Duration>,
+    S: Fn(Duration) -> Sl,
+    Sl: Future<Output = ()>,
+    F: FnMut(RetryOp) -> Fut,
+    Fut: Future<Output = RetryResult<T, E>>,
+{
+    delayed(Stopwatch::new(), Duration::from_millis(0), iter, f, sleep).await
+}
+
+/// The future returned by [`Runtime::retry`], a retry loop that can be named
+/// and stored in a struct without boxing
+pub struct RetryFuture<Z, I, F, Fut>
+where
+    Z: Sleeper,
+    I: Iterator,
+{
+    delays: I,
+    f: F,
+    watch: Stopwatch,
+    count: usize,
+    total_delay: Duration,
+    state: State<Fut, Z::Sleep>,
+}
+
+/// where a `RetryFuture` is in its loop
+enum State<Fut, Sl> {
+    /// about to start an attempt
+    Idle,
+    Attempt(Fut),
+    /// waiting out the delay before the next attempt
+    Sleep(Sl, Duration),
+    Done,
+}
+
+impl<Z, I, F, Fut> fmt::Debug for RetryFuture<Z, I, F, Fut>
+where
+    Z: Sleeper,
+    I: Iterator,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RetryFuture")
+            .field("count", &self.count)
+            .field("total_delay", &self.total_delay)
+            .finish()
+    }
+}
+
+impl<Z, I, F, Fut, T, E> Future for RetryFuture<Z, I, F, Fut>
+where
+    Z: Sleeper,
+    I: Iterator<Item = Duration>,
+    F: FnMut(RetryOp) -> Fut,
+    Fut: Future<Output = RetryResult<T, E>>,
+{
+    type Output = Result<T, RetryErr<E>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // SAFETY: only `state` is pinned, it's never moved out of and is
+        // dropped in place when replaced
+        let this = unsafe { self.get_unchecked_mut() };
+        loop {
+            match &mut this.state {
+                State::Idle => {
+                    if exhausted(&this.delays) {
+                        this.state = State::Done;
+                        return Poll::Ready(Err(RetryErr::IteratorEnded {
+                            tries: this.count,
+                            total_delay: this.total_delay,
+                        }));
+                    }
+                    let op = this.watch.op(this.count, this.total_delay);
+                    this.state = State::Attempt((this.f)(op));
+                }
+                State::Attempt(fut) => {
+                    // SAFETY: see above
+                    let res = match unsafe { Pin::new_unchecked(fut) }.poll(cx) {
+                        Poll::Ready(res) => res,
+                        Poll::Pending => return Poll::Pending,
+                    };
+                    let err = match res.step(this.count, this.total_delay) {
+                        ControlFlow::Continue(err) => err,
+                        ControlFlow::Break(res) => {
+                            this.state = State::Done;
+                            return Poll::Ready(res);
+                        }
+                    };
+                    this.watch.done();
+                    match next_delay(&mut this.delays) {
+                        Some(dur) => this.state = State::Sleep(Z::sleep(dur), dur),
+                        None => {
+                            this.state = State::Done;
+                            return Poll::Ready(Err(RetryErr::ended(
+                                this.count,
+                                this.total_delay,
+                                err,
+                            )));
+                        }
+                    }
+                }
+                State::Sleep(sleep, dur) => {
+                    let dur = *dur;
+                    // SAFETY: see above
+                    if unsafe { Pin::new_unchecked(sleep) }.poll(cx).is_pending() {
+                        return Poll::Pending;
+                    }
+                    this.total_delay = this.total_delay.saturating_add(dur);
+                    this.count += 1;
+                    this.state = State::Idle;
+                }
+                State::Done => panic!("`RetryFuture` polled after completion"),
+            }
+        }
+    }
+}
+
+/// The stream returned by [`Runtime::retry_stream`]
+#[cfg(feature = "stream")]
+pub struct AttemptStream<Z, I, F, Fut>
+where
+    Z: Sleeper,
+    I: Iterator,
+{
+    delays: I,
+    f: F,
+    watch: Stopwatch,
+    /// the running attempt
+    op: Option<RetryOp>,
+    count: usize,
+    total_delay: Duration,
+    state: State<Fut, Z::Sleep>,
+}
+
+#[cfg(feature = "stream")]
+impl<Z, I, F, Fut> fmt::Debug for AttemptStream<Z, I, F, Fut>
+where
+    Z: Sleeper,
+    I: Iterator,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AttemptStream")
+            .field("count", &self.count)
+            .field("total_delay", &self.total_delay)
+            .finish()
+    }
+}
+
+#[cfg(feature = "stream")]
+impl<Z, I, F, Fut, T, E> futures_core::Stream for AttemptStream<Z, I, F, Fut>
+where
+    Z: Sleeper,
+    I: Iterator<Item = Duration>,
+    F: FnMut(RetryOp) -> Fut,
+    Fut: Future<Output = RetryResult<T, E>>,
+{
+    type Item = Attempt<T, E>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        // SAFETY: only `state` is pinned, as in `RetryFuture`
+        let this = unsafe { self.get_unchecked_mut() };
+        loop {
+            match &mut this.state {
+                State::Idle => {
+                    if exhausted(&this.delays) {
+                        this.state = State::Done;
+                        return Poll::Ready(Some(Attempt::Err(RetryErr::IteratorEnded {
+                            tries: this.count,
+                            total_delay: this.total_delay,
+                        })));
+                    }
+                    let op = this.watch.op(this.count, this.total_delay);
+                    this.op = Some(op);
+                    this.state = State::Attempt((this.f)(op));
+                }
+                State::Attempt(fut) => {
+                    // SAFETY: see above
+                    let res = match unsafe { Pin::new_unchecked(fut) }.poll(cx) {
+                        Poll::Ready(res) => res,
+                        Poll::Pending => return Poll::Pending,
+                    };
+                    let err = match res.step(this.count, this.total_delay) {
+                        ControlFlow::Continue(err) => err,
+                        ControlFlow::Break(res) => {
+                            this.state = State::Done;
+                            return Poll::Ready(Some(match res {
+                                Ok(val) => Attempt::Ok(val),
+                                Err(err) => Attempt::Err(err),
+                            }));
+                        }
+                    };
+                    this.watch.done();
+                    let delay = match next_delay(&mut this.delays) {
+                        Some(delay) => delay,
+                        None => {
+                            this.state = State::Done;
+                            return Poll::Ready(Some(Attempt::Err(RetryErr::ended(
+                                this.count,
+                                this.total_delay,
+                                err,
+                            ))));
+                        }
+                    };
+                    this.state = State::Sleep(Z::sleep(delay), delay);
+                    let op = this.op.expect("`op` is set when an attempt starts");
+                    return Poll::Ready(Some(Attempt::Retry { op, delay, err }));
+                }
+                State::Sleep(sleep, dur) => {
+                    let dur = *dur;
+                    // SAFETY: see above
+                    if unsafe { Pin::new_unchecked(sleep) }.poll(cx).is_pending() {
+                        return Poll::Pending;
+                    }
+                    this.total_delay = this.total_delay.saturating_add(dur);
+                    this.count += 1;
+                    this.state = State::Idle;
+                }
+                State::Done => return Poll::Ready(None),
+            }
+        }
+    }
+}
+
+#[cfg(feature = "stream")]
+impl<Z, I, F, Fut, T, E> futures_core::FusedStream for AttemptStream<Z, I, F, Fut>
+where
+    Z: Sleeper,
+    I: Iterator<Item = Duration>,
+    F: FnMut(RetryOp) -> Fut,
+    Fut: Future<Output = RetryResult<T, E>>,
+{
+    fn is_terminated(&self) -> bool {
+        matches!(self.state, State::Done)
+    }
+}
+
+/// The stream returned by [`Runtime::resume_stream`]
+#[cfg(feature = "stream")]
+pub struct ResumeStream<Z, I, C, K, F, St>
+where
+    Z: Sleeper,
+{
+    strategy: I,
+    delays: I,
+    token: C,
+    last_token: Option<K>,
+    factory: F,
+    /// rebuilds since the last item
+    count: usize,
+    total_delay: Duration,
+    state: State<St, Z::Sleep>,
+}
+
+#[cfg(feature = "stream")]
+impl<Z, I, C, K, F, St> fmt::Debug for ResumeStream<Z, I, C, K, F, St>
+where
+    Z: Sleeper,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResumeStream")
+            .field("count", &self.count)
+            .field("total_delay", &self.total_delay)
+            .finish()
+    }
+}
+
+#[cfg(feature = "stream")]
+impl<Z, I, C, K, F, St, T, E> futures_core::Stream for ResumeStream<Z, I, C, K, F, St>
+where
+    Z: Sleeper,
+    I: Iterator<Item = Duration> + Clone,
+    C: FnMut(&T) -> Option<K>,
+    F: FnMut(Option<&K>) -> St,
+    St: futures_core::Stream<Item = Result<T, E>>,
+{
+    type Item = Result<T, RetryErr<E>>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        // SAFETY: only `state` is pinned, as in `RetryFuture`
+        let this = unsafe { self.get_unchecked_mut() };
+        loop {
+            match &mut this.state {
+                State::Idle => {
+                    this.state = State::Attempt((this.factory)(this.last_token.as_ref()));
+                }
+                State::Attempt(stream) => {
+                    // SAFETY: see above
+                    let err = match unsafe { Pin::new_unchecked(stream) }.poll_next(cx) {
+                        Poll::Pending => return Poll::Pending,
+                        Poll::Ready(None) => {
+                            this.state = State::Done;
+                            return Poll::Ready(None);
+                        }
+                        Poll::Ready(Some(Ok(item))) => {
+                            if let Some(token) = (this.token)(&item) {
+                                this.last_token = Some(token);
+                            }
+                            if this.count > 0 {
+                                this.delays = this.strategy.clone();
+                                this.count = 0;
+                                this.total_delay = Duration::from_millis(0);
+                            }
+                            return Poll::Ready(Some(Ok(item)));
+                        }
+                        Poll::Ready(Some(Err(err))) => err,
+                    };
+                    match this.delays.next() {
+                        Some(dur) => this.state = State::Sleep(Z::sleep(dur), dur),
+                        None => {
+                            this.state = State::Done;
+                            return Poll::Ready(Some(Err(RetryErr::FailedAttempt {
+                                tries: this.count,
+                                total_delay: this.total_delay,
+                                err,
+                            })));
+                        }
+                    }
+                }
+                State::Sleep(sleep, dur) => {
+                    let dur = *dur;
+                    // SAFETY: see above
+                    if unsafe { Pin::new_unchecked(sleep) }.poll(cx).is_pending() {
+                        return Poll::Pending;
+                    }
+                    this.total_delay = this.total_delay.saturating_add(dur);
+                    this.count += 1;
+                    this.state = State::Idle;
+                }
+                State::Done => return Poll::Ready(None),
+            }
+        }
+    }
+}
+
+/// The sink returned by [`Runtime::retry_sink`]
+#[cfg(feature = "sink")]
+pub struct RetrySink<Z, I, F, Si, T>
+where
+    Z: Sleeper,
+{
+    strategy: I,
+    delays: I,
+    factory: F,
+    /// the item not flushed yet
+    pending: Option<T>,
+    /// whether `pending` was handed to the current sink
+    sent: bool,
+    /// rebuilds since the last item got through
+    count: usize,
+    total_delay: Duration,
+    state: State<Si, Z::Sleep>,
+}
+
+#[cfg(feature = "sink")]
+impl<Z, I, F, Si, T> fmt::Debug for RetrySink<Z, I, F, Si, T>
+where
+    Z: Sleeper,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RetrySink")
+            .field("count", &self.count)
+            .field("total_delay", &self.total_delay)
+            .field("pending", &self.pending.is_some())
+            .finish()
+    }
+}
+
+#[cfg(feature = "sink")]
+impl<Z, I, F, Si, T> RetrySink<Z, I, F, Si, T>
+where
+    Z: Sleeper,
+    I: Iterator<Item = Duration> + Clone,
+    F: FnMut() -> Si,
+    Si: futures_sink::Sink<T>,
+    T: Clone,
+{
+    /// connect and flush the pending item, rebuilding the sink on errors
+    fn poll_pending(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), RetryErr<Si::Error>>> {
+        loop {
+            let err = match &mut self.state {
+                State::Idle => {
+                    self.state = State::Attempt((self.factory)());
+                    self.sent = false;
+                    continue;
+                }
+                State::Sleep(sleep, dur) => {
+                    let dur = *dur;
+                    // SAFETY: `state` is pinned as in `RetryFuture`, the caller
+                    // only has `&mut self` from a pinned `RetrySink`
+                    if unsafe { Pin::new_unchecked(sleep) }.poll(cx).is_pending() {
+                        return Poll::Pending;
+                    }
+                    self.total_delay = self.total_delay.saturating_add(dur);
+                    self.count += 1;
+                    self.state = State::Idle;
+                    continue;
+                }
+                State::Done => {
+                    return Poll::Ready(Err(RetryErr::IteratorEnded {
+                        tries: self.count,
+                        total_delay: self.total_delay,
+                    }))
+                }
+                State::Attempt(sink) => {
+                    // SAFETY: see above
+                    let mut sink = unsafe { Pin::new_unchecked(sink) };
+                    let item = match &self.pending {
+                        Some(item) => item,
+                        None => return Poll::Ready(Ok(())),
+                    };
+                    if !self.sent {
+                        match sink.as_mut().poll_ready(cx) {
+                            Poll::Pending => return Poll::Pending,
+                            Poll::Ready(Err(err)) => err,
+                            Poll::Ready(Ok(())) => match sink.as_mut().start_send(item.clone()) {
+                                Err(err) => err,
+                                Ok(()) => {
+                                    self.sent = true;
+                                    continue;
+                                }
+                            },
+                        }
+                    } else {
+                        match sink.as_mut().poll_flush(cx) {
+                            Poll::Pending => return Poll::Pending,
+                            Poll::Ready(Err(err)) => err,
+                            Poll::Ready(Ok(())) => {
+                                self.pending = None;
+                                self.sent = false;
+                                if self.count > 0 {
+                                    self.delays = self.strategy.clone();
+                                    self.count = 0;
+                                    self.total_delay = Duration::from_millis(0);
+                                }
+                                return Poll::Ready(Ok(()));
+                            }
+                        }
+                    }
+                }
+            };
+            match self.delays.next() {
+                Some(dur) => self.state = State::Sleep(Z::sleep(dur), dur),
+                None => {
+                    self.state = State::Done;
+                    return Poll::Ready(Err(RetryErr::FailedAttempt {
+                        tries: self.count,
+                        total_delay: self.total_delay,
+                        err,
+                    }));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "sink")]
+impl<Z, I, F, Si, T> futures_sink::Sink<T> for RetrySink<Z, I, F, Si, T>
+where
+    Z: Sleeper,
+    I: Iterator<Item = Duration> + Clone,
+    F: FnMut() -> Si,
+    Si: futures_sink::Sink<T>,
+    T: Clone,
+{
+    type Error = RetryErr<Si::Error>;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        // SAFETY: only `state` is pinned, as in `RetryFuture`
+        unsafe { self.get_unchecked_mut() }.poll_pending(cx)
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: T) -> Result<(), Self::Error> {
+        // SAFETY: see above
+        let this = unsafe { self.get_unchecked_mut() };
+        this.pending = Some(item);
+        this.sent = false;
+        Ok(())
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        // SAFETY: see above
+        unsafe { self.get_unchecked_mut() }.poll_pending(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        // SAFETY: see above
+        let this = unsafe { self.get_unchecked_mut() };
+        match this.poll_pending(cx) {
+            Poll::Ready(Ok(())) => {}
+            res => return res,
+        }
+        match &mut this.state {
+            // SAFETY: see above
+            State::Attempt(sink) => {
+                unsafe { Pin::new_unchecked(sink) }
+                    .poll_close(cx)
+                    .map_err(|err| RetryErr::FailedAttempt {
+                        tries: this.count,
+                        total_delay: this.total_delay,
+                        err,
+                    })
+            }
+            _ => Poll::Ready(Ok(())),
+        }
+    }
+}
+
+/// a future resolving to `None` if `sleep` finishes before `fut`
+pub(crate) struct Timeout<F, S> {
+    pub(crate) fut: Pin<Box<F>>,
+    pub(crate) sleep: Pin<Box<S>>,
+}
+
+impl<F, S> Future for Timeout<F, S>
+where
+    F: Future,
+    S: Future<Output = ()>,
+{
+    type Output = Option<F::Output>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if let Poll::Ready(out) = self.fut.as_mut().poll(cx) {
+            return Poll::Ready(Some(out));
+        }
+        self.sleep.as_mut().poll(cx).map(|()| None)
+    }
+}
+
+/// run `fut` unless `cancel` completes first
+async fn or_cancel<F, C>(fut: F, mut cancel: Pin<&mut C>) -> Option<F::Output>
+where
+    F: Future,
+    C: Future,
+{
+    let mut fut = Box::pin(fut);
+    poll_fn(|cx| {
+        if let Poll::Ready(out) = fut.as_mut().poll(cx) {
+            return Poll::Ready(Some(out));
+        }
+        cancel.as_mut().poll(cx).map(|_| None)
+    })
+    .await
+}
+
+/// the loop shared by every timer, waiting `delay` before the first attempt
+async fn delayed<I, S, Sl, F, Fut, T, E>(
+    mut watch: Stopwatch,
+    delay: Duration,
+    iter: I,
+    mut f: F,
+    sleep: S,
+) -> Result<T, RetryErr<E>>
+where
+    I: IntoIterator<Item = Duration>,
+    S: Fn(Duration) -> Sl,
+    Sl: Future<Output = ()>,
+    F: FnMut(RetryOp) -> Fut,
+    Fut: Future<Output = RetryResult<T, E>>,
+{
+    let mut delays = iter.into_iter();
+    if exhausted(&delays) {
+        return Err(RetryErr::IteratorEnded {
+            tries: 0,
+            total_delay: Duration::from_millis(0),
+        });
+    }
+    if delay > Duration::from_millis(0) {
+        sleep(delay).await;
+    }
+    let mut count = 0;
+    let mut total_delay = delay;
+    loop {
+        let err = match f(watch.op(count, total_delay))
+            .await
+            .step(count, total_delay)
+        {
+            ControlFlow::Continue(err) => err,
+            ControlFlow::Break(res) => return res,
+        };
+        watch.done();
+        let dur = match next_delay(&mut delays) {
+            Some(dur) => dur,
+            None => return Err(RetryErr::ended(count, total_delay, err)),
+        };
+        sleep(dur).await;
+        total_delay = total_delay.saturating_add(dur);
+        count += 1;
+    }
+}
+
+#[cfg(all(test, feature = "tokio-runtime"))]
+mod test {
+    use super::*;
+    use crate::strategy::Constant;
+    use std::{
+        future::{ready, Ready},
+        sync::atomic::{AtomicU64, Ordering},
+    };
+
+    static SLEPT: AtomicU64 = AtomicU64::new(0);
+
+    /// a timer that only records how long it was asked to wait
+    struct Recorder;
+
+    impl Sleeper for Recorder {
+        type Sleep = Ready<()>;
+
+        fn sleep(dur: Duration) -> Self::Sleep {
+            SLEPT.fetch_add(dur.as_millis() as u64, Ordering::Relaxed);
+            ready(())
+        }
+    }
+
+    #[tokio::test]
+    async fn sleep_fn() {
+        let slept = std::cell::Cell::new(Duration::from_millis(0));
+        let res = retry_with_sleep(
+            Constant::from_millis(10).take(3),
+            |dur| {
+                slept.set(slept.get() + dur);
+                ready(())
+            },
+            |_| async { RetryResult::<(), _>::RetryWith("busy") },
+        )
+        .await;
+        assert!(matches!(
+            res,
+            Err(RetryErr::FailedAttempt {
+                tries: 2,
+                err: "busy",
+                ..
+            })
+        ));
+        assert_eq!(slept.get(), Duration::from_millis(20));
+    }
+
+    #[tokio::test]
+    async fn custom_sleeper() {
+        let res = Runtime::<Recorder>::retry(Constant::from_millis(10).take(5), |op| async move {
+            if op.retries >= 3 {
+                RetryResult::<_, ()>::Ok(op.total_delay)
+            } else {
+                RetryResult::Retry()
+            }
+        })
+        .await;
+        assert!(matches!(res, Ok(d) if d == Duration::from_millis(30)));
+        assert_eq!(SLEPT.load(Ordering::Relaxed), 30);
+    }
+}
@@ -0,0 +1,220 @@
+//! sans-IO retry loop
+//!
+//! `RetryState` is the decision part of a retry loop with no clock, sleeping
+//! or futures. Run an attempt, hand its result to `next_action`, and it says
+//! what to do next, so a custom event loop, GUI or game loop can own the timer.
+//! Its progress can be saved as a [`RetrySnapshot`] and resumed later, which
+//! can be serialized with the `serde` feature
+use crate::{RetryErr, RetryResult};
+use core::{ops::ControlFlow, time::Duration};
+
+/// What to do after an attempt, see [`RetryState::next_action`]
+#[derive(Debug)]
+pub enum Action<T, E> {
+    /// run the next attempt right away
+    Attempt,
+    /// wait this long, then run the next attempt
+    SleepFor(Duration),
+    /// the attempt succeeded
+    Done(T),
+    /// stop retrying
+    GiveUp(RetryErr<E>),
+}
+
+/// The progress of a [`RetryState`], to resume it later, e.g. after a restart.
+/// The strategy itself isn't saved, only how many delays were taken from it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RetrySnapshot {
+    /// number of retries handed out, also the number of delays taken from
+    /// the strategy
+    pub retries: usize,
+    /// sum of the delays handed out
+    pub total_delay: Duration,
+}
+
+/// The state of a retry loop that doesn't run anything itself. Run an
+/// attempt, hand its result to [`next_action`](Self::next_action) and do
+/// what the returned [`Action`] says
+///
+/// ```rust,no_run
+/// # use std::thread;
+/// use retry_fn::{strategy::Constant, Action, RetryResult, RetryState};
+/// # fn attempt() -> RetryResult<u32, &'static str> { RetryResult::Ok(1) }
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut state = RetryState::new(Constant::from_millis(100).take(3));
+/// let val = loop {
+///     match state.next_action(attempt()) {
+///         Action::Attempt => {}
+///         Action::SleepFor(delay) => thread::sleep(delay),
+///         Action::Done(val) => break val,
+///         Action::GiveUp(err) => return Err(err.into()),
+///     }
+/// };
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct RetryState<I> {
+    delays: I,
+    count: usize,
+    total_delay: Duration,
+}
+
+impl<I> RetryState<I>
+where
+    I: Iterator<Item = Duration>,
+{
+    /// Create a new `RetryState` using the delays of `iter`. The first
+    /// attempt runs right away
+    pub fn new<S>(iter: S) -> Self
+    where
+        S: IntoIterator<IntoIter = I>,
+    {
+        Self {
+            delays: iter.into_iter(),
+            count: 0,
+            total_delay: Duration::from_millis(0),
+        }
+    }
+
+    /// Continue a loop saved with [`snapshot`](Self::snapshot). `iter` must
+    /// make the same delays as the original strategy, the ones already
+    /// handed out are skipped
+    ///
+    /// ```rust,no_run
+    /// use retry_fn::{strategy::ExponentialBackoff, RetrySnapshot, RetryState};
+    /// # fn load() -> RetrySnapshot { RetrySnapshot { retries: 0, total_delay: Default::default() } }
+    /// let strategy = || ExponentialBackoff::from_millis(60_000).take(10);
+    /// let state = RetryState::resume(strategy(), load());
+    /// # let _ = state;
+    /// ```
+    pub fn resume<S>(iter: S, snapshot: RetrySnapshot) -> Self
+    where
+        S: IntoIterator<IntoIter = I>,
+    {
+        let mut delays = iter.into_iter();
+        if snapshot.retries > 0 {
+            delays.nth(snapshot.retries - 1);
+        }
+        Self {
+            delays,
+            count: snapshot.retries,
+            total_delay: snapshot.total_delay,
+        }
+    }
+
+    /// Save the loop's progress. Taken after `SleepFor`, it already counts
+    /// that delay, so store when the sleep ends along with it
+    pub fn snapshot(&self) -> RetrySnapshot {
+        RetrySnapshot {
+            retries: self.count,
+            total_delay: self.total_delay,
+        }
+    }
+
+    /// Decide what to do after an attempt returned `outcome`. A delay is
+    /// counted in `total_delay` as soon as it's handed out
+    ///
+    /// # Returns
+    /// `Done` or `GiveUp` when the loop is over, otherwise `SleepFor` with
+    /// the delay before the next attempt, or `Attempt` if the delay is zero
+    pub fn next_action<T, E>(&mut self, outcome: RetryResult<T, E>) -> Action<T, E> {
+        let err = match outcome.step(self.count, self.total_delay) {
+            ControlFlow::Continue(err) => err,
+            ControlFlow::Break(Ok(val)) => return Action::Done(val),
+            ControlFlow::Break(Err(err)) => return Action::GiveUp(err),
+        };
+        match self.delays.next() {
+            Some(dur) => {
+                self.total_delay = self.total_delay.saturating_add(dur);
+                self.count += 1;
+                if dur == Duration::from_millis(0) {
+                    Action::Attempt
+                } else {
+                    Action::SleepFor(dur)
+                }
+            }
+            None => Action::GiveUp(RetryErr::ended(self.count, self.total_delay, err)),
+        }
+    }
+
+    /// number of retries handed out so far
+    pub fn retries(&self) -> usize {
+        self.count
+    }
+
+    /// sum of the delays handed out so far
+    pub fn total_delay(&self) -> Duration {
+        self.total_delay
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::strategy::{Constant, Immediate};
+
+    #[test]
+    fn actions() {
+        let mut state = RetryState::new(Constant::from_millis(10).take(1));
+        assert!(matches!(
+            state.next_action(RetryResult::<(), _>::RetryWith("busy")),
+            Action::SleepFor(dur) if dur == Duration::from_millis(10)
+        ));
+        assert!(matches!(
+            state.next_action(RetryResult::<(), _>::RetryWith("busy")),
+            Action::GiveUp(RetryErr::FailedAttempt {
+                tries: 1,
+                err: "busy",
+                ..
+            })
+        ));
+        assert_eq!(state.retries(), 1);
+
+        let mut state = RetryState::new(Immediate);
+        assert!(matches!(
+            state.next_action(RetryResult::<(), ()>::Retry()),
+            Action::Attempt
+        ));
+        assert!(matches!(
+            state.next_action(RetryResult::<_, ()>::Ok(1)),
+            Action::Done(1)
+        ));
+    }
+
+    #[test]
+    fn resumes() {
+        let strategy = || (1..=3).map(Duration::from_secs);
+        let mut state = RetryState::new(strategy());
+        let _ = state.next_action(RetryResult::<(), ()>::Retry());
+        let _ = state.next_action(RetryResult::<(), ()>::Retry());
+        let snapshot = state.snapshot();
+
+        let mut state = RetryState::resume(strategy(), snapshot);
+        assert_eq!(state.retries(), 2);
+        assert!(matches!(
+            state.next_action(RetryResult::<(), ()>::Retry()),
+            Action::SleepFor(dur) if dur == Duration::from_secs(3)
+        ));
+        assert_eq!(state.total_delay(), Duration::from_secs(6));
+        assert!(matches!(
+            state.next_action(RetryResult::<(), ()>::Retry()),
+            Action::GiveUp(RetryErr::IteratorEnded { tries: 3, .. })
+        ));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serializes() {
+        let snapshot = RetrySnapshot {
+            retries: 4,
+            total_delay: Duration::from_millis(1500),
+        };
+        let json = serde_json::to_string(&snapshot).unwrap();
+        assert_eq!(
+            serde_json::from_str::<RetrySnapshot>(&json).unwrap(),
+            snapshot
+        );
+    }
+}
@@ -0,0 +1,140 @@
+//! adaptive backoff
+//!
+//! A backoff whose delay is learned from feedback and shared between every
+//! clone. Each delay taken is treated as a failure and multiplies the delay by
+//! some factor up to a maximum, while calling `success` divides it back down
+//! toward the minimum. Clones share the same delay, so one instance can be
+//! stored and cloned into every call to a service
+//!
+//! ```rust
+//! # use retry_fn::strategy::AdaptiveBackoff;
+//! # use std::time::Duration;
+//! let s = AdaptiveBackoff::new(Duration::from_millis(100), Duration::from_secs(10));
+//! let mut call = s.clone();
+//! assert_eq!(call.next(), Some(Duration::from_millis(100)));
+//! assert_eq!(call.next(), Some(Duration::from_millis(200)));
+//! // the next call starts where the last left off
+//! assert_eq!(s.clone().next(), Some(Duration::from_millis(400)));
+//! s.success();
+//! assert_eq!(s.current(), Duration::from_millis(400));
+//! ```
+use super::ResetStrategy;
+use alloc::sync::Arc;
+use core::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+/// Define a type for the adaptive backoff iterator
+#[derive(Debug, Clone)]
+pub struct AdaptiveBackoff {
+    min: Duration,
+    max: Duration,
+    factor: u32,
+    current: Arc<AtomicU64>,
+}
+
+impl AdaptiveBackoff {
+    /// Create a new `AdaptiveBackoff` with delays between `min` and `max`,
+    /// starting at `min`
+    pub fn new(min: Duration, max: Duration) -> Self {
+        Self {
+            min,
+            max,
+            factor: 2,
+            current: Arc::new(AtomicU64::new(nanos(min))),
+        }
+    }
+
+    /// Set the factor delays grow by on failure and shrink by on success
+    /// 2 is the default, a factor of 0 is treated as 1
+    pub fn factor(mut self, factor: u32) -> Self {
+        self.factor = factor.max(1);
+        self
+    }
+
+    /// create a new type using a range of milliseconds
+    pub fn from_millis(min: u64, max: u64) -> Self {
+        Self::new(Duration::from_millis(min), Duration::from_millis(max))
+    }
+
+    /// The delay the next attempt will use
+    pub fn current(&self) -> Duration {
+        Duration::from_nanos(self.current.load(Ordering::Relaxed))
+    }
+
+    /// Record a success, shortening the delay for every clone
+    pub fn success(&self) {
+        let (min, factor) = (nanos(self.min), u64::from(self.factor));
+        self.update(|c| (c / factor).max(min));
+    }
+
+    /// Record a failure, lengthening the delay for every clone. Returns the
+    /// delay from before it was lengthened
+    pub fn failure(&self) -> Duration {
+        let (max, factor) = (nanos(self.max), u64::from(self.factor));
+        Duration::from_nanos(self.update(|c| c.saturating_mul(factor).min(max)))
+    }
+
+    /// apply `f` to the shared delay, returning the delay before the update
+    fn update(&self, f: impl Fn(u64) -> u64) -> u64 {
+        self.current
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |c| Some(f(c)))
+            .unwrap_or_else(|c| c)
+    }
+}
+
+fn nanos(d: Duration) -> u64 {
+    d.as_nanos().min(u128::from(u64::MAX)) as u64
+}
+
+impl Iterator for AdaptiveBackoff {
+    type Item = Duration;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.failure())
+    }
+}
+
+/// The learned delay is shared by every clone, so it is not reset
+impl ResetStrategy for AdaptiveBackoff {
+    fn reset(&mut self) {}
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn grows_to_max() {
+        let mut s = AdaptiveBackoff::from_millis(100, 500);
+        assert_eq!(s.next(), Some(Duration::from_millis(100)));
+        assert_eq!(s.next(), Some(Duration::from_millis(200)));
+        assert_eq!(s.next(), Some(Duration::from_millis(400)));
+        assert_eq!(s.next(), Some(Duration::from_millis(500)));
+        assert_eq!(s.next(), Some(Duration::from_millis(500)));
+    }
+
+    #[test]
+    fn shrinks_to_min() {
+        let s = AdaptiveBackoff::from_millis(100, 10_000).factor(10);
+        s.failure();
+        s.failure();
+        assert_eq!(s.current(), Duration::from_millis(10_000));
+        s.success();
+        assert_eq!(s.current(), Duration::from_millis(1000));
+        s.success();
+        s.success();
+        assert_eq!(s.current(), Duration::from_millis(100));
+    }
+
+    #[test]
+    fn shared_between_clones() {
+        let s = AdaptiveBackoff::from_millis(100, 10_000);
+        let mut a = s.clone();
+        let mut b = s.clone();
+        a.next();
+        b.next();
+        assert_eq!(s.current(), Duration::from_millis(400));
+    }
+}
@@ -0,0 +1,113 @@
+//! array schedule
+//!
+//! Like [`Schedule`], a fixed list of delays yielded in order, but stored
+//! inline in an array so it needs no allocation and can be declared `const`
+//!
+//! [`Schedule`]: crate::strategy::Schedule
+//!
+//! ```rust
+//! # use retry_fn::strategy::ArraySchedule;
+//! # use std::time::Duration;
+//! const PLAN: ArraySchedule<3> = ArraySchedule::from_millis([100, 500, 2000]);
+//! let mut s = PLAN;
+//! assert_eq!(s.next(), Some(Duration::from_millis(100)));
+//! assert_eq!(s.next(), Some(Duration::from_millis(500)));
+//! assert_eq!(s.next(), Some(Duration::from_millis(2000)));
+//! assert_eq!(s.next(), None);
+//! ```
+use super::ResetStrategy;
+use core::time::Duration;
+
+/// Define a type for a fixed, array backed, schedule of delays
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ArraySchedule<const N: usize> {
+    delays: [Duration; N],
+    pos: usize,
+}
+
+impl<const N: usize> From<[Duration; N]> for ArraySchedule<N> {
+    fn from(delays: [Duration; N]) -> Self {
+        Self::new(delays)
+    }
+}
+
+impl<const N: usize> ArraySchedule<N> {
+    /// Create a new `ArraySchedule` from an array of delays
+    pub const fn new(delays: [Duration; N]) -> Self {
+        Self { delays, pos: 0 }
+    }
+
+    /// create a new type from an array of millisecond delays
+    pub const fn from_millis(millis: [u64; N]) -> Self {
+        let mut delays = [Duration::from_millis(0); N];
+        let mut i = 0;
+        while i < N {
+            delays[i] = Duration::from_millis(millis[i]);
+            i += 1;
+        }
+        Self::new(delays)
+    }
+
+    /// create a new type from an array of second delays
+    pub const fn from_secs(secs: [u64; N]) -> Self {
+        let mut delays = [Duration::from_millis(0); N];
+        let mut i = 0;
+        while i < N {
+            delays[i] = Duration::from_secs(secs[i]);
+            i += 1;
+        }
+        Self::new(delays)
+    }
+
+    /// The full list of delays in this schedule
+    pub fn delays(&self) -> &[Duration] {
+        &self.delays
+    }
+}
+
+impl<const N: usize> Iterator for ArraySchedule<N> {
+    type Item = Duration;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let next = self.delays.get(self.pos).copied()?;
+        self.pos += 1;
+        Some(next)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = N - self.pos;
+        (len, Some(len))
+    }
+}
+
+impl<const N: usize> ExactSizeIterator for ArraySchedule<N> {}
+
+impl<const N: usize> ResetStrategy for ArraySchedule<N> {
+    fn reset(&mut self) {
+        self.pos = 0;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    static PLAN: ArraySchedule<2> = ArraySchedule::from_secs([1, 2]);
+
+    #[test]
+    fn yields_in_order() {
+        let mut s = PLAN;
+        assert_eq!(s.len(), 2);
+        assert_eq!(s.next(), Some(Duration::from_secs(1)));
+        assert_eq!(s.next(), Some(Duration::from_secs(2)));
+        assert_eq!(s.next(), None);
+        s.reset();
+        assert_eq!(s.next(), Some(Duration::from_secs(1)));
+    }
+
+    #[test]
+    fn empty() {
+        let mut s = ArraySchedule::new([]);
+        assert_eq!(s.next(), None);
+    }
+}
@@ -0,0 +1,109 @@
+//! clamp
+//!
+//! Wraps another strategy and bounds each delay it yields to `min..=max`
+//!
+//! ```rust
+//! # use retry_fn::strategy::{Clamp, Schedule};
+//! # use std::time::Duration;
+//! let mut s = Clamp::new(
+//!     Schedule::from_millis(&[10, 200, 5000]),
+//!     Duration::from_millis(50),
+//!     Duration::from_millis(1000),
+//! );
+//! assert_eq!(s.next(), Some(Duration::from_millis(50)));
+//! assert_eq!(s.next(), Some(Duration::from_millis(200)));
+//! assert_eq!(s.next(), Some(Duration::from_millis(1000)));
+//! ```
+use super::ResetStrategy;
+use core::time::Duration;
+
+/// Define a type that bounds the delays of some inner strategy
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Clamp<S> {
+    inner: S,
+    min: Duration,
+    max: Duration,
+}
+
+impl<S> Clamp<S> {
+    /// Create a new `Clamp` wrapping `inner`, never yielding less than `min`
+    /// or more than `max`
+    ///
+    /// # Panics
+    /// Panics if `min > max`
+    pub const fn new(inner: S, min: Duration, max: Duration) -> Self {
+        assert!(
+            min.as_nanos() <= max.as_nanos(),
+            "clamp min must not be greater than max"
+        );
+        Self { inner, min, max }
+    }
+
+    /// The smallest delay that will be yielded
+    pub fn min_delay(&self) -> Duration {
+        self.min
+    }
+
+    /// The largest delay that will be yielded
+    pub fn max_delay(&self) -> Duration {
+        self.max
+    }
+
+    /// Consume the `Clamp`, returning the wrapped strategy
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S> Iterator for Clamp<S>
+where
+    S: Iterator<Item = Duration>,
+{
+    type Item = Duration;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|d| d.max(self.min).min(self.max))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<S> ResetStrategy for Clamp<S>
+where
+    S: ResetStrategy,
+{
+    fn reset(&mut self) {
+        self.inner.reset();
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use super::*;
+    use crate::strategy::{Constant, Jitter};
+
+    #[test]
+    fn floors_jitter() {
+        let mut s = Clamp::new(
+            Jitter::new(Constant::from_millis(100)),
+            Duration::from_millis(20),
+            Duration::from_millis(100),
+        );
+        for _ in 0..100 {
+            let d = s.next().unwrap();
+            assert!(d >= Duration::from_millis(20) && d <= Duration::from_millis(100));
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn min_over_max() {
+        Clamp::new(
+            Constant::from_millis(100),
+            Duration::from_millis(2),
+            Duration::from_millis(1),
+        );
+    }
+}
@@ -11,7 +11,8 @@
 //! assert_eq!(s.next(), Some(Duration::from_millis(100)));
 //! assert_eq!(s.next(), Some(Duration::from_millis(100)));
 //! ```
-use std::time::Duration;
+use super::ResetStrategy;
+use core::time::Duration;
 
 /// Create a new type representing a constant time iterator
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -27,27 +28,27 @@ impl From<Duration> for Constant {
 
 impl Constant {
     /// Create a new `Constant`
-    pub fn new(duration: Duration) -> Self {
-        duration.into()
+    pub const fn new(duration: Duration) -> Self {
+        Self { duration }
     }
 
     /// create a new type using n milliseconds as the start value
-    pub fn from_millis(millis: u64) -> Self {
+    pub const fn from_millis(millis: u64) -> Self {
         Self::new(Duration::from_millis(millis))
     }
 
     /// create a new type using n seconds as the start value
-    pub fn from_secs(secs: u64) -> Self {
+    pub const fn from_secs(secs: u64) -> Self {
         Self::new(Duration::from_secs(secs))
     }
 
     /// create a new type using n microseconds as the start value
-    pub fn from_micros(micros: u64) -> Self {
+    pub const fn from_micros(micros: u64) -> Self {
         Self::new(Duration::from_micros(micros))
     }
 
     /// create a new type using n nanoseconds as the start value
-    pub fn from_nanos(nanos: u64) -> Self {
+    pub const fn from_nanos(nanos: u64) -> Self {
         Self::new(Duration::from_nanos(nanos))
     }
 }
@@ -60,6 +61,10 @@ impl Iterator for Constant {
     }
 }
 
+impl ResetStrategy for Constant {
+    fn reset(&mut self) {}
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -0,0 +1,126 @@
+//! deadline
+//!
+//! Wraps another strategy and ends the series once some `Instant` has passed.
+//! A delay that would sleep past the deadline is cut short so it ends right
+//! at the deadline
+//!
+//! ```rust
+//! # use retry_fn::strategy::{Constant, Deadline};
+//! # use std::time::Duration;
+//! let mut s = Deadline::from_now(Constant::from_secs(10), Duration::from_millis(100));
+//! assert!(s.next().unwrap() <= Duration::from_millis(100));
+//! ```
+use super::ResetStrategy;
+use crate::Instant;
+use std::time::Duration;
+
+/// Define a type that stops some inner strategy at a deadline
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Deadline<S> {
+    inner: S,
+    deadline: Instant,
+    done: bool,
+}
+
+impl<S> Deadline<S> {
+    /// Create a new `Deadline` wrapping `inner`, ending once `deadline` has
+    /// passed
+    pub fn new(inner: S, deadline: Instant) -> Self {
+        Self {
+            inner,
+            deadline,
+            done: false,
+        }
+    }
+
+    /// Create a new `Deadline` wrapping `inner`, ending once `timeout` has
+    /// elapsed from now
+    pub fn from_now(inner: S, timeout: Duration) -> Self {
+        Self::new(inner, Instant::now() + timeout)
+    }
+
+    /// The instant after which no more delays are yielded
+    pub fn deadline(&self) -> Instant {
+        self.deadline
+    }
+
+    /// Consume the `Deadline`, returning the wrapped strategy
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S> Iterator for Deadline<S>
+where
+    S: Iterator<Item = Duration>,
+{
+    type Item = Duration;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let left = self.deadline.saturating_duration_since(Instant::now());
+        match self.inner.next() {
+            Some(d) if left > Duration::from_millis(0) => Some(d.min(left)),
+            _ => {
+                self.done = true;
+                None
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.done {
+            (0, Some(0))
+        } else {
+            (0, self.inner.size_hint().1)
+        }
+    }
+}
+
+/// Resets the inner strategy, the deadline itself is left unchanged
+impl<S> ResetStrategy for Deadline<S>
+where
+    S: ResetStrategy,
+{
+    fn reset(&mut self) {
+        self.inner.reset();
+        self.done = false;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::strategy::{Constant, Schedule};
+
+    #[test]
+    fn truncates_to_deadline() {
+        let mut s = Deadline::from_now(Constant::from_secs(60), Duration::from_secs(1));
+        let d = s.next().unwrap();
+        assert!(d <= Duration::from_secs(1));
+        assert!(d > Duration::from_millis(0));
+    }
+
+    #[test]
+    fn passes_short_delays() {
+        let mut s = Deadline::from_now(Constant::from_millis(10), Duration::from_secs(60));
+        assert_eq!(s.next(), Some(Duration::from_millis(10)));
+        assert_eq!(s.next(), Some(Duration::from_millis(10)));
+    }
+
+    #[test]
+    fn past_deadline() {
+        let mut s = Deadline::new(Constant::from_millis(10), Instant::now());
+        assert_eq!(s.next(), None);
+        assert_eq!(s.next(), None);
+    }
+
+    #[test]
+    fn inner_ends_first() {
+        let mut s = Deadline::from_now(Schedule::from_millis(&[10]), Duration::from_secs(60));
+        assert_eq!(s.next(), Some(Duration::from_millis(10)));
+        assert_eq!(s.next(), None);
+    }
+}
@@ -0,0 +1,107 @@
+//! decay backoff
+//!
+//! The opposite of exponential backoff, starting at some time and dividing by
+//! some base each attempt, down to an optional floor. Useful when early
+//! attempts are known to fail while a dependency warms up
+//! ex. base = 2, start = 16ms |----------------|--------|----|--|-|...
+//!
+//! ```rust
+//! # use retry_fn::strategy::DecayBackoff;
+//! # use std::time::Duration;
+//! let mut s = DecayBackoff::from_millis(800).floor(Duration::from_millis(150));
+//! assert_eq!(s.next(), Some(Duration::from_millis(800)));
+//! assert_eq!(s.next(), Some(Duration::from_millis(400)));
+//! assert_eq!(s.next(), Some(Duration::from_millis(200)));
+//! assert_eq!(s.next(), Some(Duration::from_millis(150)));
+//! assert_eq!(s.next(), Some(Duration::from_millis(150)));
+//! ```
+use super::ResetStrategy;
+use core::time::Duration;
+
+/// Define a type for the decay backoff iterator
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct DecayBackoff {
+    start: Duration,
+    current: Duration,
+    base: u32,
+    floor: Duration,
+}
+
+impl DecayBackoff {
+    /// Create a new decay type with a starting duration
+    pub const fn new(start: Duration) -> Self {
+        Self {
+            start,
+            current: start,
+            base: 2,
+            floor: Duration::from_millis(0),
+        }
+    }
+
+    /// Set the base that we will divide the series by
+    /// base 2 is the default, a base of 0 is treated as 1
+    pub const fn base(mut self, base: u32) -> Self {
+        self.base = if base == 0 { 1 } else { base };
+        self
+    }
+
+    /// The minimum time the series will allow
+    pub const fn floor(mut self, floor: Duration) -> Self {
+        self.floor = floor;
+        self
+    }
+
+    /// create a new type using n milliseconds as the start value
+    pub const fn from_millis(millis: u64) -> Self {
+        Self::new(Duration::from_millis(millis))
+    }
+
+    /// create a new type using n seconds as the start value
+    pub const fn from_secs(secs: u64) -> Self {
+        Self::new(Duration::from_secs(secs))
+    }
+}
+
+impl Iterator for DecayBackoff {
+    type Item = Duration;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let next = self.current.max(self.floor);
+        self.current /= self.base;
+        Some(next)
+    }
+}
+
+impl ResetStrategy for DecayBackoff {
+    fn reset(&mut self) {
+        self.current = self.start;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn halves() {
+        let mut s = DecayBackoff::from_secs(4);
+        assert_eq!(s.next(), Some(Duration::from_secs(4)));
+        assert_eq!(s.next(), Some(Duration::from_secs(2)));
+        assert_eq!(s.next(), Some(Duration::from_secs(1)));
+        assert_eq!(s.next(), Some(Duration::from_millis(500)));
+    }
+
+    #[test]
+    fn reaches_zero() {
+        let mut s = DecayBackoff::from_millis(100).base(10);
+        assert_eq!(s.nth(20), Some(Duration::from_millis(0)));
+    }
+
+    #[test]
+    fn reset() {
+        let mut s = DecayBackoff::from_millis(100).base(0);
+        assert_eq!(s.next(), Some(Duration::from_millis(100)));
+        s.reset();
+        assert_eq!(s.next(), Some(Duration::from_millis(100)));
+    }
+}
@@ -0,0 +1,153 @@
+//! decorrelated jitter
+//!
+//! Each delay is picked at random between the base and three times the
+//! previous delay, up to some optional maximum duration. See "decorrelated
+//! jitter" in
+//! <https://aws.amazon.com/blogs/architecture/exponential-backoff-and-jitter/>
+//!
+//! ```rust
+//! # use retry_fn::strategy::DecorrelatedJitter;
+//! # use std::time::Duration;
+//! let mut s = DecorrelatedJitter::from_millis(100).max(Duration::from_secs(2));
+//! let first = s.next().unwrap();
+//! assert!(first >= Duration::from_millis(100) && first <= Duration::from_millis(300));
+//! assert!(s.next().unwrap() <= Duration::from_secs(2));
+//! ```
+use super::{RandomSource, ResetStrategy};
+use core::time::Duration;
+
+/// Define a type for the decorrelated jitter iterator
+#[derive(Debug, Clone)]
+pub struct DecorrelatedJitter<R = fastrand::Rng> {
+    base: Duration,
+    current: Duration,
+    max: Option<Duration>,
+    rng: R,
+}
+
+#[cfg(feature = "std")]
+impl DecorrelatedJitter {
+    /// Create a new decorrelated jitter type, `base` is the smallest delay
+    /// that will be yielded
+    pub fn new(base: Duration) -> Self {
+        Self::with_rng(base, fastrand::Rng::new())
+    }
+
+    /// create a new type using n milliseconds as the base value
+    pub fn from_millis(millis: u64) -> Self {
+        Self::new(Duration::from_millis(millis))
+    }
+
+    /// create a new type using n seconds as the base value
+    pub fn from_secs(secs: u64) -> Self {
+        Self::new(Duration::from_secs(secs))
+    }
+
+    /// create a new type using n microseconds as the base value
+    pub fn from_micros(micros: u64) -> Self {
+        Self::new(Duration::from_micros(micros))
+    }
+
+    /// create a new type using n nanoseconds as the base value
+    pub fn from_nanos(nanos: u64) -> Self {
+        Self::new(Duration::from_nanos(nanos))
+    }
+}
+
+impl DecorrelatedJitter {
+    /// Seed the random source, for a reproducible series
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.rng = fastrand::Rng::with_seed(seed);
+        self
+    }
+}
+
+impl<R> DecorrelatedJitter<R> {
+    /// Create a new decorrelated jitter type using `rng` as the random
+    /// source, `base` is the smallest delay that will be yielded
+    pub const fn with_rng(base: Duration, rng: R) -> Self {
+        Self {
+            base,
+            current: base,
+            max: None,
+            rng,
+        }
+    }
+
+    /// Set the smallest delay the series will yield
+    pub fn base(mut self, base: Duration) -> Self {
+        self.base = base;
+        self.current = base;
+        self
+    }
+
+    /// The maximum time the series will allow
+    pub fn max(mut self, max: Duration) -> Self {
+        self.max = Some(max);
+        self
+    }
+}
+
+impl<R> Iterator for DecorrelatedJitter<R>
+where
+    R: RandomSource,
+{
+    type Item = Duration;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let upper = self.current.checked_mul(3).unwrap_or(Duration::MAX);
+        let mut next = super::rand_between(&mut self.rng, self.base, upper);
+        if let Some(m) = self.max {
+            next = next.min(m);
+        }
+        self.current = next;
+        Some(next)
+    }
+}
+
+impl<R> ResetStrategy for DecorrelatedJitter<R> {
+    fn reset(&mut self) {
+        self.current = self.base;
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn within_bounds() {
+        let mut s = DecorrelatedJitter::from_millis(100);
+        let mut prev = Duration::from_millis(100);
+        for _ in 0..20 {
+            let d = s.next().unwrap();
+            assert!(d >= Duration::from_millis(100));
+            assert!(d <= prev * 3);
+            prev = d;
+        }
+    }
+
+    #[test]
+    fn hits_max() {
+        let mut s = DecorrelatedJitter::from_millis(100).max(Duration::from_millis(500));
+        for _ in 0..100 {
+            let d = s.next().unwrap();
+            assert!(d >= Duration::from_millis(100));
+            assert!(d <= Duration::from_millis(500));
+        }
+    }
+
+    #[test]
+    fn saturates() {
+        let mut s = DecorrelatedJitter::new(Duration::MAX / 2);
+        for _ in 0..10 {
+            assert!(s.next().unwrap() >= Duration::MAX / 2);
+        }
+    }
+
+    #[test]
+    fn base_builder() {
+        let mut s = DecorrelatedJitter::from_millis(1).base(Duration::from_secs(1));
+        assert!(s.next().unwrap() >= Duration::from_secs(1));
+    }
+}
@@ -0,0 +1,193 @@
+//! dynamic strategy
+//!
+//! An exponential backoff whose settings can be changed while it is in use.
+//! Clones share the same [`DynamicConfig`], so one instance can be kept
+//! around to tune the backoff of every retry loop holding a clone; each loop
+//! picks up the new values on its next delay
+//!
+//! ```rust
+//! # use retry_fn::strategy::DynamicStrategy;
+//! # use std::time::Duration;
+//! let s = DynamicStrategy::from_millis(100);
+//! let mut running = s.clone();
+//! assert_eq!(running.next(), Some(Duration::from_millis(100)));
+//! s.update(|c| {
+//!     c.base = Duration::from_secs(1);
+//!     c.max_retries = Some(2);
+//! });
+//! assert_eq!(running.next(), Some(Duration::from_secs(2)));
+//! assert_eq!(running.next(), None);
+//! ```
+use super::ResetStrategy;
+use std::{
+    convert::TryFrom,
+    sync::{Arc, Mutex, MutexGuard},
+    time::Duration,
+};
+
+/// The settings of a [`DynamicStrategy`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct DynamicConfig {
+    /// the first delay
+    pub base: Duration,
+    /// each delay is the previous multiplied by `factor`, a factor of 0 is
+    /// treated as 1
+    pub factor: u32,
+    /// the largest delay that will be yielded
+    pub max_delay: Option<Duration>,
+    /// the number of delays yielded before the series ends
+    pub max_retries: Option<usize>,
+}
+
+impl DynamicConfig {
+    /// Create a new config starting at `base`, doubling each delay with no
+    /// maximum
+    pub const fn new(base: Duration) -> Self {
+        Self {
+            base,
+            factor: 2,
+            max_delay: None,
+            max_retries: None,
+        }
+    }
+
+    /// the delay for the `attempt`-th retry, or `None` if the series is over
+    fn delay(&self, attempt: usize) -> Option<Duration> {
+        if self.max_retries.is_some_and(|max| attempt >= max) {
+            return None;
+        }
+        let delay = u32::try_from(attempt)
+            .ok()
+            .and_then(|n| self.factor.max(1).checked_pow(n))
+            .and_then(|mul| self.base.checked_mul(mul))
+            .unwrap_or(Duration::MAX);
+        Some(match self.max_delay {
+            Some(max) => delay.min(max),
+            None => delay,
+        })
+    }
+}
+
+/// Define a type for the dynamic strategy iterator
+#[derive(Debug, Clone)]
+pub struct DynamicStrategy {
+    config: Arc<Mutex<DynamicConfig>>,
+    attempt: usize,
+}
+
+impl DynamicStrategy {
+    /// Create a new `DynamicStrategy` starting at `base`, doubling each delay
+    pub fn new(base: Duration) -> Self {
+        Self::with_config(DynamicConfig::new(base))
+    }
+
+    /// Create a new `DynamicStrategy` from `config`
+    pub fn with_config(config: DynamicConfig) -> Self {
+        Self {
+            config: Arc::new(Mutex::new(config)),
+            attempt: 0,
+        }
+    }
+
+    /// create a new type using n milliseconds as the base value
+    pub fn from_millis(millis: u64) -> Self {
+        Self::new(Duration::from_millis(millis))
+    }
+
+    /// create a new type using n seconds as the base value
+    pub fn from_secs(secs: u64) -> Self {
+        Self::new(Duration::from_secs(secs))
+    }
+
+    /// The current settings
+    pub fn config(&self) -> DynamicConfig {
+        *self.lock()
+    }
+
+    /// Replace the settings for every clone
+    pub fn set_config(&self, config: DynamicConfig) {
+        *self.lock() = config;
+    }
+
+    /// Change the settings for every clone
+    pub fn update(&self, f: impl FnOnce(&mut DynamicConfig)) {
+        f(&mut self.lock());
+    }
+
+    fn lock(&self) -> MutexGuard<'_, DynamicConfig> {
+        // the config is plain data, so a poisoned lock is still usable
+        self.config.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Iterator for DynamicStrategy {
+    type Item = Duration;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let next = self.config().delay(self.attempt)?;
+        self.attempt += 1;
+        Some(next)
+    }
+}
+
+/// Resets the attempt count, the shared settings are left unchanged
+impl ResetStrategy for DynamicStrategy {
+    fn reset(&mut self) {
+        self.attempt = 0;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn doubles() {
+        let s = DynamicStrategy::from_millis(100);
+        assert_eq!(
+            s.take(3).collect::<Vec<_>>(),
+            vec![
+                Duration::from_millis(100),
+                Duration::from_millis(200),
+                Duration::from_millis(400),
+            ]
+        );
+    }
+
+    #[test]
+    fn limits() {
+        let s = DynamicStrategy::with_config(DynamicConfig {
+            max_delay: Some(Duration::from_millis(300)),
+            max_retries: Some(3),
+            ..DynamicConfig::new(Duration::from_millis(100))
+        });
+        assert_eq!(
+            s.collect::<Vec<_>>(),
+            vec![
+                Duration::from_millis(100),
+                Duration::from_millis(200),
+                Duration::from_millis(300),
+            ]
+        );
+    }
+
+    #[test]
+    fn updates_shared() {
+        let s = DynamicStrategy::from_millis(100);
+        let mut a = s.clone();
+        assert_eq!(a.next(), Some(Duration::from_millis(100)));
+        s.update(|c| c.factor = 3);
+        assert_eq!(a.next(), Some(Duration::from_millis(300)));
+        s.update(|c| c.max_retries = Some(1));
+        assert_eq!(a.next(), None);
+        a.reset();
+        assert_eq!(a.next(), Some(Duration::from_millis(100)));
+    }
+
+    #[test]
+    fn saturates() {
+        let mut s = DynamicStrategy::new(Duration::MAX);
+        s.next();
+        assert_eq!(s.next(), Some(Duration::MAX));
+    }
+}
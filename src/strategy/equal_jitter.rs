@@ -0,0 +1,100 @@
+//! equal jitter
+//!
+//! Wraps another strategy, keeping half of each delay and randomizing the
+//! other half, i.e. `delay / 2 + rand(0..=delay / 2)`. Unlike [`Jitter`],
+//! this guarantees a minimum spacing between attempts.
+//!
+//! [`Jitter`]: crate::strategy::Jitter
+//!
+//! ```rust
+//! # use retry_fn::strategy::{Constant, EqualJitter};
+//! # use std::time::Duration;
+//! let mut s = EqualJitter::new(Constant::from_millis(100));
+//! let d = s.next().unwrap();
+//! assert!(d >= Duration::from_millis(50) && d <= Duration::from_millis(100));
+//! ```
+use super::{RandomSource, ResetStrategy};
+use core::time::Duration;
+
+/// Define a type that applies equal jitter to some inner strategy
+#[derive(Debug, Clone)]
+pub struct EqualJitter<S, R = fastrand::Rng> {
+    inner: S,
+    rng: R,
+}
+
+impl<S> EqualJitter<S> {
+    /// Create a new `EqualJitter` wrapping `inner`
+    #[cfg(feature = "std")]
+    pub fn new(inner: S) -> Self {
+        Self::with_rng(inner, fastrand::Rng::new())
+    }
+
+    /// Seed the random source, for a reproducible series
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.rng = fastrand::Rng::with_seed(seed);
+        self
+    }
+}
+
+impl<S, R> EqualJitter<S, R> {
+    /// Create a new `EqualJitter` wrapping `inner`, using `rng` as the random
+    /// source
+    pub const fn with_rng(inner: S, rng: R) -> Self {
+        Self { inner, rng }
+    }
+
+    /// Consume the `EqualJitter`, returning the wrapped strategy
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S, R> Iterator for EqualJitter<S, R>
+where
+    S: Iterator<Item = Duration>,
+    R: RandomSource,
+{
+    type Item = Duration;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let half = self.inner.next()? / 2;
+        Some(half + super::rand_between(&mut self.rng, Duration::from_millis(0), half))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<S, R> ResetStrategy for EqualJitter<S, R>
+where
+    S: ResetStrategy,
+{
+    fn reset(&mut self) {
+        self.inner.reset();
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use super::*;
+    use crate::strategy::Constant;
+
+    #[test]
+    fn keeps_floor() {
+        let mut s = EqualJitter::new(Constant::from_millis(100));
+        for _ in 0..100 {
+            let d = s.next().unwrap();
+            assert!(d >= Duration::from_millis(50));
+            assert!(d <= Duration::from_millis(100));
+        }
+    }
+
+    #[test]
+    fn ends_with_inner() {
+        let mut s = EqualJitter::new(Constant::from_millis(100).take(1));
+        assert!(s.next().is_some());
+        assert_eq!(s.next(), None);
+    }
+}
@@ -12,56 +12,82 @@
 //! assert_eq!(s.next(), Some(Duration::from_millis(400)));
 //! assert_eq!(s.next(), Some(Duration::from_millis(800)));
 //! ```
-use std::time::Duration;
+//!
+//! By default the starting duration itself is never yielded, the first delay
+//! is `start * base`. Use `include_first` to begin the series at `start`:
+//! ```rust
+//! # use retry_fn::strategy::ExponentialBackoff;
+//! # use std::time::Duration;
+//!
+//! let mut s = ExponentialBackoff::from_millis(100).include_first(true);
+//! assert_eq!(s.next(), Some(Duration::from_millis(100)));
+//! assert_eq!(s.next(), Some(Duration::from_millis(200)));
+//! assert_eq!(s.next(), Some(Duration::from_millis(400)));
+//! ```
+use super::ResetStrategy;
+use core::time::Duration;
 
 /// Define a type for the exponential time iterator
 #[derive(Debug, Copy, Clone)]
 pub struct ExponentialBackoff {
+    start: Duration,
     current: Duration,
     base: u32,
     max: Option<Duration>,
+    include_first: bool,
+    started: bool,
 }
 
 impl ExponentialBackoff {
     /// Create a new exp type with a starting duration
-    pub fn new(first: Duration) -> Self {
+    pub const fn new(first: Duration) -> Self {
         Self {
+            start: first,
             current: first,
             base: 2,
             max: None,
+            include_first: false,
+            started: false,
         }
     }
 
     /// Set the base that we will multiply the series with
     /// base 2 is the default
-    pub fn base(mut self, base: u32) -> Self {
+    pub const fn base(mut self, base: u32) -> Self {
         self.base = base;
         self
     }
 
     /// The maximum time the series will allow
-    pub fn max(mut self, max: Duration) -> Self {
+    pub const fn max(mut self, max: Duration) -> Self {
         self.max = Some(max);
         self
     }
 
+    /// When `true`, the starting duration is yielded as the first delay
+    /// instead of `start * base`. `false` is the default
+    pub const fn include_first(mut self, include_first: bool) -> Self {
+        self.include_first = include_first;
+        self
+    }
+
     /// create a new type using n milliseconds as the start value
-    pub fn from_millis(millis: u64) -> Self {
+    pub const fn from_millis(millis: u64) -> Self {
         Self::new(Duration::from_millis(millis))
     }
 
     /// create a new type using n seconds as the start value
-    pub fn from_secs(secs: u64) -> Self {
+    pub const fn from_secs(secs: u64) -> Self {
         Self::new(Duration::from_secs(secs))
     }
 
     /// create a new type using n microseconds as the start value
-    pub fn from_micros(micros: u64) -> Self {
+    pub const fn from_micros(micros: u64) -> Self {
         Self::new(Duration::from_micros(micros))
     }
 
     /// create a new type using n nanoseconds as the start value
-    pub fn from_nanos(nanos: u64) -> Self {
+    pub const fn from_nanos(nanos: u64) -> Self {
         Self::new(Duration::from_nanos(nanos))
     }
 }
@@ -70,10 +96,12 @@ impl Iterator for ExponentialBackoff {
     type Item = Duration;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let next = self
-            .current
-            .checked_mul(self.base)
-            .unwrap_or_else(|| Duration::from_millis(u64::MAX));
+        let next = if self.include_first && !self.started {
+            self.current
+        } else {
+            self.current.checked_mul(self.base).unwrap_or(Duration::MAX)
+        };
+        self.started = true;
         self.current = next;
 
         match self.max {
@@ -83,6 +111,13 @@ impl Iterator for ExponentialBackoff {
     }
 }
 
+impl ResetStrategy for ExponentialBackoff {
+    fn reset(&mut self) {
+        self.current = self.start;
+        self.started = false;
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -114,4 +149,48 @@ mod test {
         assert_eq!(s.next(), Some(Duration::from_millis(1_000_000)));
         assert_eq!(s.next(), Some(Duration::from_millis(1_000_000)));
     }
+
+    #[test]
+    fn include_first() {
+        let mut s = ExponentialBackoff::from_millis(100).include_first(true);
+        assert_eq!(s.next(), Some(Duration::from_millis(100)));
+        assert_eq!(s.next(), Some(Duration::from_millis(200)));
+        assert_eq!(s.next(), Some(Duration::from_millis(400)));
+    }
+
+    #[test]
+    fn include_first_capped() {
+        let mut s = ExponentialBackoff::from_millis(100)
+            .include_first(true)
+            .max(Duration::from_millis(50));
+        assert_eq!(s.next(), Some(Duration::from_millis(50)));
+        assert_eq!(s.next(), Some(Duration::from_millis(50)));
+    }
+
+    #[test]
+    fn saturates() {
+        let mut s = ExponentialBackoff::new(Duration::MAX / 2).base(3);
+        assert_eq!(s.next(), Some(Duration::MAX));
+        assert_eq!(s.next(), Some(Duration::MAX));
+    }
+
+    #[test]
+    fn const_policy() {
+        const POLICY: ExponentialBackoff = ExponentialBackoff::from_millis(100)
+            .base(3)
+            .max(Duration::from_millis(500));
+        let mut s = POLICY;
+        assert_eq!(s.next(), Some(Duration::from_millis(300)));
+        assert_eq!(s.next(), Some(Duration::from_millis(500)));
+    }
+
+    #[test]
+    fn reset() {
+        let mut s = ExponentialBackoff::from_millis(100).include_first(true);
+        assert_eq!(s.next(), Some(Duration::from_millis(100)));
+        assert_eq!(s.next(), Some(Duration::from_millis(200)));
+        s.reset();
+        assert_eq!(s.next(), Some(Duration::from_millis(100)));
+        assert_eq!(s.next(), Some(Duration::from_millis(200)));
+    }
 }
@@ -0,0 +1,133 @@
+//! strategy extensions
+//!
+//! Chainable combinators for any strategy, i.e. any iterator of `Duration`s.
+//! Each method wraps the strategy in a nameable type from this module
+//!
+//! ```rust
+//! # use retry_fn::strategy::{ExponentialBackoff, StrategyExt};
+//! # use std::time::Duration;
+//! let s = ExponentialBackoff::from_millis(100)
+//!     .max_delay(Duration::from_secs(1))
+//!     .jitter()
+//!     .max_retries(5);
+//! assert_eq!(s.count(), 5);
+//! ```
+#[cfg(feature = "std")]
+use crate::Instant;
+use core::time::Duration;
+
+#[cfg(feature = "alloc")]
+use super::Preview;
+use super::{Clamp, MaxDelay, MaxRetries, MaxTotalDelay, SkipFirst};
+#[cfg(feature = "std")]
+use super::{Deadline, EqualJitter, Jitter, TokenBucket};
+
+/// Extension methods for composing strategies
+pub trait StrategyExt: Iterator<Item = Duration> + Sized {
+    /// Bound each delay to `min..=max`, see [`Clamp`]
+    ///
+    /// # Panics
+    /// Panics if `min > max`
+    fn clamp(self, min: Duration, max: Duration) -> Clamp<Self> {
+        Clamp::new(self, min, max)
+    }
+
+    /// Apply full jitter to each delay, see [`Jitter`]
+    #[cfg(feature = "std")]
+    fn jitter(self) -> Jitter<Self> {
+        Jitter::new(self)
+    }
+
+    /// Apply equal jitter to each delay, see [`EqualJitter`]
+    #[cfg(feature = "std")]
+    fn equal_jitter(self) -> EqualJitter<Self> {
+        EqualJitter::new(self)
+    }
+
+    /// Cap each delay at `max`, see [`MaxDelay`]
+    fn max_delay(self, max: Duration) -> MaxDelay<Self> {
+        MaxDelay::new(self, max)
+    }
+
+    /// End after `max` delays, see [`MaxRetries`]
+    fn max_retries(self, max: usize) -> MaxRetries<Self> {
+        MaxRetries::new(self, max)
+    }
+
+    /// End once the sum of all delays would exceed `budget`, see
+    /// [`MaxTotalDelay`]
+    fn max_total_delay(self, budget: Duration) -> MaxTotalDelay<Self> {
+        MaxTotalDelay::new(self, budget)
+    }
+
+    /// End once `deadline` has passed, see [`Deadline`]
+    #[cfg(feature = "std")]
+    fn deadline(self, deadline: Instant) -> Deadline<Self> {
+        Deadline::new(self, deadline)
+    }
+
+    /// Rate limit with a token bucket of `capacity` tokens, refilling one
+    /// every `refill`, see [`TokenBucket`]
+    ///
+    /// # Panics
+    /// Panics if `refill` is zero
+    #[cfg(feature = "std")]
+    fn token_bucket(self, capacity: u32, refill: Duration) -> TokenBucket<Self> {
+        TokenBucket::new(self, capacity, refill)
+    }
+
+    /// Drop the first delay, see [`SkipFirst`]
+    fn skip_first(self) -> SkipFirst<Self> {
+        SkipFirst::new(self)
+    }
+
+    /// Take the first `n` delays and their total without sleeping, see
+    /// [`Preview`]
+    #[cfg(feature = "alloc")]
+    fn preview(self, n: usize) -> Preview {
+        Preview::new(self, n)
+    }
+}
+
+impl<S> StrategyExt for S where S: Iterator<Item = Duration> {}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use super::*;
+    use crate::strategy::{Constant, ExponentialBackoff};
+
+    #[test]
+    fn chains() {
+        let s = ExponentialBackoff::from_millis(100)
+            .skip_first()
+            .max_delay(Duration::from_millis(1000))
+            .max_total_delay(Duration::from_millis(2500));
+        assert_eq!(
+            s.collect::<Vec<_>>(),
+            vec![
+                Duration::from_millis(400),
+                Duration::from_millis(800),
+                Duration::from_millis(1000),
+            ]
+        );
+    }
+
+    #[test]
+    fn jitter_then_limit() {
+        let s = Constant::from_millis(100).jitter().max_retries(3);
+        let delays = s.collect::<Vec<_>>();
+        assert_eq!(delays.len(), 3);
+        assert!(delays.iter().all(|d| *d <= Duration::from_millis(100)));
+    }
+
+    #[test]
+    fn clamp_jitter() {
+        let mut s = Constant::from_millis(100)
+            .jitter()
+            .clamp(Duration::from_millis(50), Duration::from_millis(80));
+        for _ in 0..100 {
+            let d = s.next().unwrap();
+            assert!(d >= Duration::from_millis(50) && d <= Duration::from_millis(80));
+        }
+    }
+}
@@ -0,0 +1,97 @@
+//! strategy from a closure
+//!
+//! Build a strategy from a function of the attempt number, starting at `0`.
+//! The series ends when the function returns `None`
+//!
+//! ```rust
+//! # use retry_fn::strategy::from_fn;
+//! # use std::time::Duration;
+//! let mut s = from_fn(|attempt| {
+//!     if attempt < 3 {
+//!         Some(Duration::from_millis(100 * (attempt as u64 + 1)))
+//!     } else {
+//!         None
+//!     }
+//! });
+//! assert_eq!(s.next(), Some(Duration::from_millis(100)));
+//! assert_eq!(s.next(), Some(Duration::from_millis(200)));
+//! assert_eq!(s.next(), Some(Duration::from_millis(300)));
+//! assert_eq!(s.next(), None);
+//! ```
+use super::ResetStrategy;
+use core::{fmt, time::Duration};
+
+/// Create a new strategy calling `f` with the attempt number for each delay
+pub fn from_fn<F>(f: F) -> FromFn<F>
+where
+    F: FnMut(usize) -> Option<Duration>,
+{
+    FromFn { f, attempt: 0 }
+}
+
+/// Define a type for a strategy built from a closure, see [`from_fn`]
+#[derive(Copy, Clone)]
+pub struct FromFn<F> {
+    f: F,
+    attempt: usize,
+}
+
+impl<F> fmt::Debug for FromFn<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FromFn")
+            .field("attempt", &self.attempt)
+            .finish()
+    }
+}
+
+impl<F> Iterator for FromFn<F>
+where
+    F: FnMut(usize) -> Option<Duration>,
+{
+    type Item = Duration;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let next = (self.f)(self.attempt)?;
+        self.attempt += 1;
+        Some(next)
+    }
+}
+
+impl<F> ResetStrategy for FromFn<F> {
+    fn reset(&mut self) {
+        self.attempt = 0;
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn linear() {
+        let s = from_fn(|n| Some(Duration::from_millis(10 * n as u64)));
+        assert_eq!(
+            s.take(3).collect::<Vec<_>>(),
+            vec![
+                Duration::from_millis(0),
+                Duration::from_millis(10),
+                Duration::from_millis(20),
+            ]
+        );
+    }
+
+    #[test]
+    fn reset() {
+        let mut s = from_fn(|n| {
+            if n < 1 {
+                Some(Duration::from_secs(1))
+            } else {
+                None
+            }
+        });
+        assert_eq!(s.next(), Some(Duration::from_secs(1)));
+        assert_eq!(s.next(), None);
+        s.reset();
+        assert_eq!(s.next(), Some(Duration::from_secs(1)));
+    }
+}
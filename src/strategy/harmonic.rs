@@ -0,0 +1,91 @@
+//! harmonic backoff
+//!
+//! Delays that grow quickly at first and then flatten out toward a limit,
+//! without ever reaching it. The nth delay (from 1) is
+//! `limit - (limit - start) / n`
+//! ex. start = 100ms, limit = 1s |-|-----|------|-------|-------|...
+//!
+//! ```rust
+//! # use retry_fn::strategy::HarmonicBackoff;
+//! # use std::time::Duration;
+//! let mut s = HarmonicBackoff::from_millis(100, 1000);
+//! assert_eq!(s.next(), Some(Duration::from_millis(100)));
+//! assert_eq!(s.next(), Some(Duration::from_millis(550)));
+//! assert_eq!(s.next(), Some(Duration::from_millis(700)));
+//! assert_eq!(s.next(), Some(Duration::from_millis(775)));
+//! ```
+use super::ResetStrategy;
+use core::time::Duration;
+
+/// Define a type for the harmonic backoff iterator
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct HarmonicBackoff {
+    start: Duration,
+    limit: Duration,
+    attempt: u32,
+}
+
+impl HarmonicBackoff {
+    /// Create a new `HarmonicBackoff` starting at `start` and approaching
+    /// `limit`. If `limit` is less than `start`, `start` is always yielded
+    pub const fn new(start: Duration, limit: Duration) -> Self {
+        Self {
+            start,
+            limit,
+            attempt: 0,
+        }
+    }
+
+    /// create a new type using n milliseconds as the start and limit
+    pub const fn from_millis(start: u64, limit: u64) -> Self {
+        Self::new(Duration::from_millis(start), Duration::from_millis(limit))
+    }
+
+    /// create a new type using n seconds as the start and limit
+    pub const fn from_secs(start: u64, limit: u64) -> Self {
+        Self::new(Duration::from_secs(start), Duration::from_secs(limit))
+    }
+}
+
+impl Iterator for HarmonicBackoff {
+    type Item = Duration;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.attempt = self.attempt.saturating_add(1);
+        let gap = match self.limit.checked_sub(self.start) {
+            Some(gap) => gap,
+            None => return Some(self.start),
+        };
+        Some(self.limit - gap / self.attempt)
+    }
+}
+
+impl ResetStrategy for HarmonicBackoff {
+    fn reset(&mut self) {
+        self.attempt = 0;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn approaches_limit() {
+        let s = HarmonicBackoff::from_secs(1, 10);
+        let mut prev = Duration::from_millis(0);
+        for d in s.take(1000) {
+            assert!(d >= prev);
+            assert!(d < Duration::from_secs(10));
+            prev = d;
+        }
+        assert!(prev > Duration::from_millis(9990));
+    }
+
+    #[test]
+    fn inverted() {
+        let mut s = HarmonicBackoff::from_secs(2, 1);
+        assert_eq!(s.next(), Some(Duration::from_secs(2)));
+        assert_eq!(s.next(), Some(Duration::from_secs(2)));
+    }
+}
@@ -1,7 +1,8 @@
 //! immediate return
 //!
 //! This distribution just returns immediately, using 0 as it's Duration
-use std::time::Duration;
+use super::{MaxRetries, ResetStrategy};
+use core::time::Duration;
 
 /// Define type for Immediate strategy
 #[derive(Debug, Default, Copy, Clone)]
@@ -10,9 +11,16 @@ pub struct Immediate;
 impl Immediate {
     /// Create new `Immediate`
     /// (not necessary, you can just use `Immediate` since it holds no data)
-    pub fn new() -> Self {
+    pub const fn new() -> Self {
         Self
     }
+
+    /// Create a new `Immediate` that ends after `n` delays. `Immediate` on
+    /// its own never ends, so this is the safer choice when there is no
+    /// other way out of retrying
+    pub const fn with_limit(n: usize) -> MaxRetries<Self> {
+        MaxRetries::new(Self, n)
+    }
 }
 
 impl Iterator for Immediate {
@@ -23,7 +31,11 @@ impl Iterator for Immediate {
     }
 }
 
-#[cfg(test)]
+impl ResetStrategy for Immediate {
+    fn reset(&mut self) {}
+}
+
+#[cfg(all(test, feature = "std"))]
 mod test {
     use super::*;
     #[test]
@@ -33,4 +45,10 @@ mod test {
         assert_eq!(s.next(), Some(Duration::from_millis(0)));
         assert_eq!(s.next(), Some(Duration::from_millis(0)));
     }
+
+    #[test]
+    fn with_limit() {
+        let s = Immediate::with_limit(3);
+        assert_eq!(s.collect::<Vec<_>>(), vec![Duration::from_millis(0); 3]);
+    }
 }
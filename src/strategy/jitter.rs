@@ -0,0 +1,139 @@
+//! full jitter
+//!
+//! Wraps another strategy and picks a random duration between zero and the
+//! delay it yields, so many clients backing off at once don't retry in
+//! lockstep. See "full jitter" in
+//! <https://aws.amazon.com/blogs/architecture/exponential-backoff-and-jitter/>
+//!
+//! ```rust
+//! # use retry_fn::strategy::{ExponentialBackoff, Jitter};
+//! # use std::time::Duration;
+//! let mut s = Jitter::new(ExponentialBackoff::from_millis(100));
+//! assert!(s.next().unwrap() <= Duration::from_millis(200));
+//! assert!(s.next().unwrap() <= Duration::from_millis(400));
+//! assert!(s.next().unwrap() <= Duration::from_millis(800));
+//! ```
+use super::{RandomSource, ResetStrategy};
+use core::time::Duration;
+
+/// Define a type that applies full jitter to some inner strategy
+#[derive(Debug, Clone)]
+pub struct Jitter<S, R = fastrand::Rng> {
+    inner: S,
+    rng: R,
+}
+
+impl<S> Jitter<S> {
+    /// Create a new `Jitter` wrapping `inner`
+    #[cfg(feature = "std")]
+    pub fn new(inner: S) -> Self {
+        Self::with_rng(inner, fastrand::Rng::new())
+    }
+
+    /// Seed the random source, for a reproducible series
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.rng = fastrand::Rng::with_seed(seed);
+        self
+    }
+}
+
+impl<S, R> Jitter<S, R> {
+    /// Create a new `Jitter` wrapping `inner`, using `rng` as the random
+    /// source
+    pub const fn with_rng(inner: S, rng: R) -> Self {
+        Self { inner, rng }
+    }
+
+    /// Consume the `Jitter`, returning the wrapped strategy
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S, R> Iterator for Jitter<S, R>
+where
+    S: Iterator<Item = Duration>,
+    R: RandomSource,
+{
+    type Item = Duration;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let delay = self.inner.next()?;
+        Some(super::rand_between(
+            &mut self.rng,
+            Duration::from_millis(0),
+            delay,
+        ))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<S, R> ResetStrategy for Jitter<S, R>
+where
+    S: ResetStrategy,
+{
+    fn reset(&mut self) {
+        self.inner.reset();
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use super::*;
+    use crate::strategy::{Constant, ExponentialBackoff};
+
+    #[test]
+    fn within_delay() {
+        let mut s = Jitter::new(Constant::from_millis(100));
+        for _ in 0..100 {
+            assert!(s.next().unwrap() <= Duration::from_millis(100));
+        }
+    }
+
+    #[test]
+    fn follows_inner() {
+        let s = Jitter::new(ExponentialBackoff::from_millis(100));
+        for (i, dur) in s.take(5).enumerate() {
+            assert!(dur <= Duration::from_millis(100 * 2u64.pow(i as u32 + 1)));
+        }
+    }
+
+    #[test]
+    fn ends_with_inner() {
+        let mut s = Jitter::new(Constant::from_millis(100).take(2));
+        assert!(s.next().is_some());
+        assert!(s.next().is_some());
+        assert_eq!(s.next(), None);
+    }
+
+    #[test]
+    fn seeded() {
+        let a = Jitter::new(Constant::from_secs(1)).seed(7);
+        let b = Jitter::new(Constant::from_secs(1)).seed(7);
+        assert_eq!(
+            a.take(10).collect::<Vec<_>>(),
+            b.take(10).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn custom_rng() {
+        struct Max;
+        impl RandomSource for Max {
+            fn next_u64(&mut self) -> u64 {
+                u64::MAX
+            }
+        }
+        let mut s = Jitter::with_rng(Constant::from_millis(100), Max);
+        assert_eq!(s.next(), Some(Duration::from_millis(100)));
+    }
+
+    #[test]
+    fn zero_stays_zero() {
+        let mut s = Jitter::new(Constant::from_millis(0));
+        assert_eq!(s.next(), Some(Duration::from_millis(0)));
+    }
+}
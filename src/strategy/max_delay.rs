@@ -0,0 +1,85 @@
+//! max delay
+//!
+//! Wraps another strategy and caps each delay it yields at some maximum
+//!
+//! ```rust
+//! # use retry_fn::strategy::{ExponentialBackoff, MaxDelay};
+//! # use std::time::Duration;
+//! let mut s = MaxDelay::new(ExponentialBackoff::from_millis(100), Duration::from_millis(300));
+//! assert_eq!(s.next(), Some(Duration::from_millis(200)));
+//! assert_eq!(s.next(), Some(Duration::from_millis(300)));
+//! assert_eq!(s.next(), Some(Duration::from_millis(300)));
+//! ```
+use super::ResetStrategy;
+use core::time::Duration;
+
+/// Define a type that caps the delays of some inner strategy
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct MaxDelay<S> {
+    inner: S,
+    max: Duration,
+}
+
+impl<S> MaxDelay<S> {
+    /// Create a new `MaxDelay` wrapping `inner`, never yielding more than
+    /// `max`
+    pub const fn new(inner: S, max: Duration) -> Self {
+        Self { inner, max }
+    }
+
+    /// The largest delay that will be yielded
+    pub fn max_delay(&self) -> Duration {
+        self.max
+    }
+
+    /// Consume the `MaxDelay`, returning the wrapped strategy
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S> Iterator for MaxDelay<S>
+where
+    S: Iterator<Item = Duration>,
+{
+    type Item = Duration;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|d| d.min(self.max))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<S> ResetStrategy for MaxDelay<S>
+where
+    S: ResetStrategy,
+{
+    fn reset(&mut self) {
+        self.inner.reset();
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use super::*;
+    use crate::strategy::Schedule;
+
+    #[test]
+    fn caps_delays() {
+        let s = MaxDelay::new(
+            Schedule::from_millis(&[100, 500, 200]),
+            Duration::from_millis(300),
+        );
+        assert_eq!(
+            s.collect::<Vec<_>>(),
+            vec![
+                Duration::from_millis(100),
+                Duration::from_millis(300),
+                Duration::from_millis(200),
+            ]
+        );
+    }
+}
@@ -0,0 +1,132 @@
+//! max retries
+//!
+//! Wraps another strategy and ends the series after some number of delays
+//!
+//! ```rust
+//! # use retry_fn::strategy::{Constant, MaxRetries};
+//! # use std::time::Duration;
+//! let mut s = MaxRetries::new(Constant::from_millis(100), 2);
+//! assert_eq!(s.next(), Some(Duration::from_millis(100)));
+//! assert_eq!(s.next(), Some(Duration::from_millis(100)));
+//! assert_eq!(s.next(), None);
+//! ```
+use super::ResetStrategy;
+use core::time::Duration;
+
+/// Define a type that limits the number of delays of some inner strategy
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct MaxRetries<S> {
+    inner: S,
+    max: usize,
+    retries: usize,
+    done: bool,
+}
+
+impl<S> MaxRetries<S> {
+    /// Create a new `MaxRetries` wrapping `inner`, ending after `max` delays
+    pub const fn new(inner: S, max: usize) -> Self {
+        Self {
+            inner,
+            max,
+            retries: 0,
+            done: false,
+        }
+    }
+
+    /// The maximum number of delays that will be yielded
+    pub fn max_retries(&self) -> usize {
+        self.max
+    }
+
+    /// The number of delays yielded so far
+    pub fn retries(&self) -> usize {
+        self.retries
+    }
+
+    /// Consume the `MaxRetries`, returning the wrapped strategy
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S> Iterator for MaxRetries<S>
+where
+    S: Iterator<Item = Duration>,
+{
+    type Item = Duration;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done || self.retries >= self.max {
+            return None;
+        }
+        match self.inner.next() {
+            Some(d) => {
+                self.retries += 1;
+                Some(d)
+            }
+            None => {
+                self.done = true;
+                None
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let left = self.max - self.retries;
+        if self.done || left == 0 {
+            return (0, Some(0));
+        }
+        let (lo, hi) = self.inner.size_hint();
+        let hi = match hi {
+            Some(hi) => hi.min(left),
+            None => left,
+        };
+        (lo.min(left), Some(hi))
+    }
+}
+
+impl<S> ResetStrategy for MaxRetries<S>
+where
+    S: ResetStrategy,
+{
+    fn reset(&mut self) {
+        self.inner.reset();
+        self.retries = 0;
+        self.done = false;
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use super::*;
+    use crate::strategy::{Constant, Schedule};
+
+    #[test]
+    fn stops_after_max() {
+        let s = MaxRetries::new(Constant::from_millis(100), 3);
+        assert_eq!(s.size_hint(), (0, Some(3)));
+        assert_eq!(s.collect::<Vec<_>>(), vec![Duration::from_millis(100); 3]);
+    }
+
+    #[test]
+    fn inner_ends_first() {
+        let mut s = MaxRetries::new(Schedule::from_millis(&[10, 20]), 5);
+        assert_eq!(s.next(), Some(Duration::from_millis(10)));
+        assert_eq!(s.next(), Some(Duration::from_millis(20)));
+        assert_eq!(s.next(), None);
+        assert_eq!(s.next(), None);
+        assert_eq!(s.retries(), 2);
+    }
+
+    #[test]
+    fn const_policy() {
+        static POLICY: MaxRetries<Constant> = MaxRetries::new(Constant::from_secs(1), 2);
+        assert_eq!(POLICY.count(), 2);
+    }
+
+    #[test]
+    fn zero() {
+        let mut s = MaxRetries::new(Constant::from_millis(100), 0);
+        assert_eq!(s.next(), None);
+    }
+}
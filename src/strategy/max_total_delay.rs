@@ -0,0 +1,134 @@
+//! max total delay
+//!
+//! Wraps another strategy and ends the series once the sum of all delays
+//! yielded would exceed some budget
+//!
+//! ```rust
+//! # use retry_fn::strategy::{Constant, MaxTotalDelay};
+//! # use std::time::Duration;
+//! let mut s = MaxTotalDelay::new(Constant::from_millis(100), Duration::from_millis(250));
+//! assert_eq!(s.next(), Some(Duration::from_millis(100)));
+//! assert_eq!(s.next(), Some(Duration::from_millis(100)));
+//! assert_eq!(s.next(), None);
+//! ```
+use super::ResetStrategy;
+use core::time::Duration;
+
+/// Define a type that limits the total delay of some inner strategy
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct MaxTotalDelay<S> {
+    inner: S,
+    budget: Duration,
+    total: Duration,
+    done: bool,
+}
+
+impl<S> MaxTotalDelay<S> {
+    /// Create a new `MaxTotalDelay` wrapping `inner`, ending once the total of
+    /// all delays would be more than `budget`
+    pub const fn new(inner: S, budget: Duration) -> Self {
+        Self {
+            inner,
+            budget,
+            total: Duration::from_millis(0),
+            done: false,
+        }
+    }
+
+    /// The sum of all the delays yielded so far
+    pub fn total_delay(&self) -> Duration {
+        self.total
+    }
+
+    /// Consume the `MaxTotalDelay`, returning the wrapped strategy
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S> Iterator for MaxTotalDelay<S>
+where
+    S: Iterator<Item = Duration>,
+{
+    type Item = Duration;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let next = self.inner.next();
+        match next.and_then(|d| self.total.checked_add(d).map(|t| (d, t))) {
+            Some((d, total)) if total <= self.budget => {
+                self.total = total;
+                Some(d)
+            }
+            _ => {
+                self.done = true;
+                None
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.done {
+            (0, Some(0))
+        } else {
+            (0, self.inner.size_hint().1)
+        }
+    }
+}
+
+impl<S> ResetStrategy for MaxTotalDelay<S>
+where
+    S: ResetStrategy,
+{
+    fn reset(&mut self) {
+        self.inner.reset();
+        self.total = Duration::from_millis(0);
+        self.done = false;
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use super::*;
+    use crate::strategy::{Constant, ExponentialBackoff};
+
+    #[test]
+    fn stops_at_budget() {
+        let s = MaxTotalDelay::new(
+            ExponentialBackoff::from_millis(100),
+            Duration::from_millis(1400),
+        );
+        assert_eq!(
+            s.collect::<Vec<_>>(),
+            vec![
+                Duration::from_millis(200),
+                Duration::from_millis(400),
+                Duration::from_millis(800),
+            ]
+        );
+    }
+
+    #[test]
+    fn exact_budget() {
+        let mut s = MaxTotalDelay::new(Constant::from_millis(100), Duration::from_millis(200));
+        assert_eq!(s.next(), Some(Duration::from_millis(100)));
+        assert_eq!(s.next(), Some(Duration::from_millis(100)));
+        assert_eq!(s.total_delay(), Duration::from_millis(200));
+        assert_eq!(s.next(), None);
+        assert_eq!(s.next(), None);
+    }
+
+    #[test]
+    fn reset() {
+        let mut s = MaxTotalDelay::new(
+            ExponentialBackoff::from_millis(100),
+            Duration::from_millis(600),
+        );
+        assert_eq!(s.by_ref().count(), 2);
+        s.reset();
+        assert_eq!(s.total_delay(), Duration::from_millis(0));
+        assert_eq!(s.next(), Some(Duration::from_millis(200)));
+    }
+}
@@ -1,8 +1,116 @@
 //! Different iterators to retry using
+#[cfg(feature = "alloc")]
+mod adaptive;
+mod array_schedule;
+mod clamp;
 mod constant;
+#[cfg(feature = "std")]
+mod deadline;
+mod decay;
+mod decorrelated;
+#[cfg(feature = "std")]
+mod dynamic;
+mod equal_jitter;
 mod exponential;
+mod ext;
+mod from_fn;
+mod harmonic;
 mod immediate;
+mod jitter;
+mod max_delay;
+mod max_retries;
+mod max_total_delay;
+#[cfg(feature = "alloc")]
+mod preview;
+mod random;
+mod random_source;
+#[cfg(feature = "std")]
+mod registry;
+mod reset;
+mod retry_strategy;
+#[cfg(feature = "alloc")]
+mod schedule;
+mod skip_first;
+#[cfg(feature = "std")]
+mod spec;
+mod spread;
+#[cfg(feature = "alloc")]
+mod step;
+#[cfg(feature = "std")]
+mod token_bucket;
+#[cfg(feature = "std")]
+mod wall_clock;
 
+#[cfg(feature = "alloc")]
+pub use adaptive::*;
+pub use array_schedule::*;
+pub use clamp::*;
 pub use constant::*;
+#[cfg(feature = "std")]
+pub use deadline::*;
+pub use decay::*;
+pub use decorrelated::*;
+#[cfg(feature = "std")]
+pub use dynamic::*;
+pub use equal_jitter::*;
 pub use exponential::*;
+pub use ext::*;
+pub use from_fn::*;
+pub use harmonic::*;
 pub use immediate::*;
+pub use jitter::*;
+pub use max_delay::*;
+pub use max_retries::*;
+pub use max_total_delay::*;
+#[cfg(feature = "alloc")]
+pub use preview::*;
+pub use random::*;
+pub use random_source::*;
+#[cfg(feature = "std")]
+pub use registry::*;
+pub use reset::*;
+pub use retry_strategy::*;
+#[cfg(feature = "alloc")]
+pub use schedule::*;
+pub use skip_first::*;
+#[cfg(feature = "std")]
+pub use spec::*;
+pub use spread::*;
+#[cfg(feature = "alloc")]
+pub use step::*;
+#[cfg(feature = "std")]
+pub use token_bucket::*;
+#[cfg(feature = "std")]
+pub use wall_clock::*;
+
+use core::time::Duration;
+
+/// pick a random duration in `lo..=hi`, with nanosecond granularity for
+/// spans up to `u64::MAX` nanos and coarser above that
+pub(crate) fn rand_between<R>(rng: &mut R, lo: Duration, hi: Duration) -> Duration
+where
+    R: RandomSource + ?Sized,
+{
+    let (lo, hi) = (lo.as_nanos(), hi.as_nanos());
+    if hi <= lo {
+        return from_nanos(lo);
+    }
+    let span = hi - lo;
+    let r = u128::from(rng.next_u64());
+    let offset = if span < u128::from(u64::MAX) {
+        // scale into the span with a widening multiply
+        (r * (span + 1)) >> 64
+    } else {
+        r * (span >> 64)
+    };
+    from_nanos(lo + offset)
+}
+
+/// `Duration::from_nanos` for the full range of a `Duration`
+fn from_nanos(nanos: u128) -> Duration {
+    const NANOS_PER_SEC: u128 = 1_000_000_000;
+    Duration::new(
+        (nanos / NANOS_PER_SEC) as u64,
+        (nanos % NANOS_PER_SEC) as u32,
+    )
+}
@@ -0,0 +1,90 @@
+//! preview
+//!
+//! Take the first few delays of a strategy without sleeping, ex. to log the
+//! planned schedule or assert on it in a test
+//!
+//! ```rust
+//! # use retry_fn::strategy::{ExponentialBackoff, StrategyExt};
+//! # use std::time::Duration;
+//! let p = ExponentialBackoff::from_millis(100).preview(3);
+//! assert_eq!(
+//!     p.delays,
+//!     vec![
+//!         Duration::from_millis(200),
+//!         Duration::from_millis(400),
+//!         Duration::from_millis(800),
+//!     ]
+//! );
+//! assert_eq!(p.total, Duration::from_millis(1400));
+//! ```
+use alloc::vec::Vec;
+use core::time::Duration;
+
+/// The first delays of some strategy, see [`StrategyExt::preview`]
+///
+/// [`StrategyExt::preview`]: crate::strategy::StrategyExt::preview
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Preview {
+    /// the delays, in order. Fewer than asked for if the strategy ended
+    pub delays: Vec<Duration>,
+    /// the sum of all the delays, saturating at `Duration::MAX`
+    pub total: Duration,
+}
+
+impl Preview {
+    /// Take up to `n` delays from `strategy`
+    pub fn new<S>(strategy: S, n: usize) -> Self
+    where
+        S: IntoIterator<Item = Duration>,
+    {
+        let delays = strategy.into_iter().take(n).collect::<Vec<_>>();
+        let total = delays.iter().fold(Duration::from_millis(0), |acc, d| {
+            acc.checked_add(*d).unwrap_or(Duration::MAX)
+        });
+        Self { delays, total }
+    }
+
+    /// The time elapsed before each attempt after the first, i.e. the
+    /// running total of the delays
+    pub fn cumulative(&self) -> Vec<Duration> {
+        self.delays
+            .iter()
+            .scan(Duration::from_millis(0), |acc, d| {
+                *acc = acc.checked_add(*d).unwrap_or(Duration::MAX);
+                Some(*acc)
+            })
+            .collect()
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use super::*;
+    use crate::strategy::{Constant, Schedule};
+
+    #[test]
+    fn cumulative() {
+        let p = Preview::new(Constant::from_secs(1), 3);
+        assert_eq!(p.total, Duration::from_secs(3));
+        assert_eq!(
+            p.cumulative(),
+            vec![
+                Duration::from_secs(1),
+                Duration::from_secs(2),
+                Duration::from_secs(3),
+            ]
+        );
+    }
+
+    #[test]
+    fn short_strategy() {
+        let p = Preview::new(Schedule::from_millis(&[10]), 5);
+        assert_eq!(p.delays, vec![Duration::from_millis(10)]);
+    }
+
+    #[test]
+    fn saturates() {
+        let p = Preview::new(Constant::new(Duration::MAX), 2);
+        assert_eq!(p.total, Duration::MAX);
+    }
+}
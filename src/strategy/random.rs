@@ -0,0 +1,115 @@
+//! random range
+//!
+//! Yields a uniformly random delay between some minimum and maximum on every
+//! attempt, useful to spread out many workers polling the same resource
+//!
+//! ```rust
+//! # use retry_fn::strategy::RandomRange;
+//! # use std::time::Duration;
+//! let mut s = RandomRange::new(Duration::from_millis(100), Duration::from_millis(500));
+//! let d = s.next().unwrap();
+//! assert!(d >= Duration::from_millis(100) && d <= Duration::from_millis(500));
+//! ```
+use super::{RandomSource, ResetStrategy};
+use core::time::Duration;
+
+/// Define a type for the random range iterator
+#[derive(Debug, Clone)]
+pub struct RandomRange<R = fastrand::Rng> {
+    min: Duration,
+    max: Duration,
+    rng: R,
+}
+
+#[cfg(feature = "std")]
+impl RandomRange {
+    /// Create a new `RandomRange` yielding delays in `min..=max`. If `max`
+    /// is less than `min`, `min` is always yielded
+    pub fn new(min: Duration, max: Duration) -> Self {
+        Self::with_rng(min, max, fastrand::Rng::new())
+    }
+
+    /// create a new type using a range of milliseconds
+    pub fn from_millis(min: u64, max: u64) -> Self {
+        Self::new(Duration::from_millis(min), Duration::from_millis(max))
+    }
+
+    /// create a new type using a range of seconds
+    pub fn from_secs(min: u64, max: u64) -> Self {
+        Self::new(Duration::from_secs(min), Duration::from_secs(max))
+    }
+}
+
+impl RandomRange {
+    /// Seed the random source, for a reproducible series
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.rng = fastrand::Rng::with_seed(seed);
+        self
+    }
+}
+
+impl<R> RandomRange<R> {
+    /// Create a new `RandomRange` yielding delays in `min..=max`, using `rng`
+    /// as the random source
+    pub const fn with_rng(min: Duration, max: Duration, rng: R) -> Self {
+        Self { min, max, rng }
+    }
+}
+
+impl<R> Iterator for RandomRange<R>
+where
+    R: RandomSource,
+{
+    type Item = Duration;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(super::rand_between(&mut self.rng, self.min, self.max))
+    }
+}
+
+impl<R> ResetStrategy for RandomRange<R> {
+    fn reset(&mut self) {}
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn within_range() {
+        let mut s = RandomRange::from_millis(100, 200);
+        for _ in 0..100 {
+            let d = s.next().unwrap();
+            assert!(d >= Duration::from_millis(100));
+            assert!(d <= Duration::from_millis(200));
+        }
+    }
+
+    #[test]
+    fn seeded() {
+        let a = RandomRange::from_secs(1, 10).seed(42);
+        let b = RandomRange::from_secs(1, 10).seed(42);
+        assert_eq!(
+            a.take(10).collect::<Vec<_>>(),
+            b.take(10).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn borrowed_rng() {
+        let mut rng = fastrand::Rng::with_seed(1);
+        let mut s = RandomRange::with_rng(
+            Duration::from_millis(100),
+            Duration::from_millis(200),
+            &mut rng,
+        );
+        let d = s.next().unwrap();
+        assert!(d >= Duration::from_millis(100) && d <= Duration::from_millis(200));
+    }
+
+    #[test]
+    fn inverted_range() {
+        let mut s = RandomRange::from_secs(2, 1);
+        assert_eq!(s.next(), Some(Duration::from_secs(2)));
+    }
+}
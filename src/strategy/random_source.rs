@@ -0,0 +1,43 @@
+//! random sources
+//!
+//! The jittered strategies get their randomness from a `RandomSource`. By
+//! default this is a `fastrand::Rng` seeded from the OS, use `seed` on any of
+//! them for a reproducible series, or `with_rng` to bring your own source
+//! (ex. a hardware RNG on `no_std`)
+//!
+//! ```rust
+//! # use retry_fn::strategy::{Constant, Jitter, RandomSource};
+//! # use std::time::Duration;
+//! struct Counter(u64);
+//!
+//! impl RandomSource for Counter {
+//!     fn next_u64(&mut self) -> u64 {
+//!         self.0 = self.0.wrapping_add(u64::MAX / 4);
+//!         self.0
+//!     }
+//! }
+//!
+//! let mut s = Jitter::with_rng(Constant::from_millis(100), Counter(0));
+//! assert!(s.next().unwrap() <= Duration::from_millis(100));
+//! ```
+
+/// A source of random numbers for jittered strategies
+pub trait RandomSource {
+    /// Return a uniformly random `u64`
+    fn next_u64(&mut self) -> u64;
+}
+
+impl RandomSource for fastrand::Rng {
+    fn next_u64(&mut self) -> u64 {
+        self.u64(..)
+    }
+}
+
+impl<R> RandomSource for &mut R
+where
+    R: RandomSource + ?Sized,
+{
+    fn next_u64(&mut self) -> u64 {
+        (**self).next_u64()
+    }
+}
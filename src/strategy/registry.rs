@@ -0,0 +1,134 @@
+//! named strategies
+//!
+//! `Registry` maps policy names like `db` or `payments-api` to a [`Spec`], so
+//! a whole set of policies can be loaded once at startup and looked up by
+//! name wherever a retry is run. Each lookup returns a fresh strategy.
+//!
+//! A registry can be parsed from text with one `name = spec` per line. Blank
+//! lines and lines starting with `#` are skipped
+//!
+//! ```rust
+//! # use retry_fn::strategy::Registry;
+//! # use std::time::Duration;
+//! let registry: Registry = "
+//!     ## backoffs for our dependencies
+//!     db = exponential:100ms,max=1s,retries=3
+//!     payments-api = constant:2s,retries=5
+//! "
+//! .parse()
+//! .unwrap();
+//! let mut s = registry.get("db").unwrap();
+//! assert_eq!(s.next(), Some(Duration::from_millis(200)));
+//! assert!(registry.get("cache").is_none());
+//! ```
+use super::{ParseSpecError, Spec};
+use std::{collections::BTreeMap, str::FromStr};
+
+/// Define a type for a set of named strategies
+#[derive(Debug, Clone, Default)]
+pub struct Registry {
+    specs: BTreeMap<String, String>,
+}
+
+impl Registry {
+    /// Create a new empty `Registry`
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register `spec` under `name`, replacing any previous policy with that
+    /// name. Fails if `spec` can't be parsed, see [`Spec`]
+    pub fn insert(&mut self, name: impl Into<String>, spec: &str) -> Result<(), ParseSpecError> {
+        spec.parse::<Spec>()?;
+        self.specs.insert(name.into(), spec.trim().to_owned());
+        Ok(())
+    }
+
+    /// Remove the policy registered under `name`, returning whether there
+    /// was one
+    pub fn remove(&mut self, name: &str) -> bool {
+        self.specs.remove(name).is_some()
+    }
+
+    /// A new strategy for the policy registered under `name`
+    pub fn get(&self, name: &str) -> Option<Spec> {
+        self.specs.get(name).and_then(|s| s.parse().ok())
+    }
+
+    /// Whether a policy is registered under `name`
+    pub fn contains(&self, name: &str) -> bool {
+        self.specs.contains_key(name)
+    }
+
+    /// The registered policy names, in order
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.specs.keys().map(String::as_str)
+    }
+}
+
+impl FromStr for Registry {
+    type Err = ParseSpecError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut registry = Registry::new();
+        for (n, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let err = |msg: String| ParseSpecError::new(format!("line {}: {}", n + 1, msg));
+            let i = line
+                .find('=')
+                .ok_or_else(|| err(format!("expected `name = spec`, got `{}`", line)))?;
+            let name = line[..i].trim();
+            if name.is_empty() {
+                return Err(err("missing name".to_owned()));
+            }
+            registry
+                .insert(name, &line[i + 1..])
+                .map_err(|e| err(e.msg))?;
+        }
+        Ok(registry)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn insert_get() {
+        let mut r = Registry::new();
+        r.insert("db", "constant:1s,retries=2").unwrap();
+        assert!(r.insert("bad", "linear:1s").is_err());
+        assert_eq!(r.names().collect::<Vec<_>>(), vec!["db"]);
+        // each lookup starts a new series
+        assert_eq!(r.get("db").unwrap().count(), 2);
+        assert_eq!(r.get("db").unwrap().count(), 2);
+        assert!(r.remove("db"));
+        assert!(!r.contains("db"));
+    }
+
+    #[test]
+    fn parse() {
+        let r: Registry = "a = immediate\n\n# comment\nb=constant:5ms"
+            .parse()
+            .unwrap();
+        assert_eq!(r.names().collect::<Vec<_>>(), vec!["a", "b"]);
+        assert_eq!(r.get("b").unwrap().next(), Some(Duration::from_millis(5)));
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            "a = immediate\nb = fixed:1s"
+                .parse::<Registry>()
+                .unwrap_err()
+                .to_string(),
+            "invalid strategy spec: line 2: unknown kind `fixed`"
+        );
+        assert!("constant:1s".parse::<Registry>().is_err());
+        assert!("= constant:1s".parse::<Registry>().is_err());
+    }
+}
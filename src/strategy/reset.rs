@@ -0,0 +1,21 @@
+//! reset
+//!
+//! Strategies are iterators, so they change as delays are taken from them.
+//! `ResetStrategy` puts a strategy back to its starting state, so one stored
+//! in a struct can be reused for every call instead of being rebuilt
+//!
+//! ```rust
+//! # use retry_fn::strategy::{ExponentialBackoff, ResetStrategy};
+//! # use std::time::Duration;
+//! let mut s = ExponentialBackoff::from_millis(100);
+//! assert_eq!(s.next(), Some(Duration::from_millis(200)));
+//! assert_eq!(s.next(), Some(Duration::from_millis(400)));
+//! s.reset();
+//! assert_eq!(s.next(), Some(Duration::from_millis(200)));
+//! ```
+
+/// A strategy that can be returned to its starting state
+pub trait ResetStrategy {
+    /// Reset the strategy so the next delay is the first in the series
+    fn reset(&mut self);
+}
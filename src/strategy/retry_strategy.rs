@@ -0,0 +1,77 @@
+//! error-aware strategies
+//!
+//! A `RetryStrategy` computes the next delay from the number of retries so
+//! far and the error of the last attempt, if the attempt returned one with
+//! `RetryResult::RetryWith`. Any iterator of `Duration`s is a `RetryStrategy`
+//! that ignores the error
+//!
+//! ```rust
+//! # use retry_fn::strategy::RetryStrategy;
+//! # use std::time::Duration;
+//! struct Hint(Duration);
+//!
+//! struct FromHint;
+//!
+//! impl RetryStrategy<Hint> for FromHint {
+//!     fn delay(&mut self, attempt: usize, err: Option<&Hint>) -> Option<Duration> {
+//!         if attempt >= 3 {
+//!             return None;
+//!         }
+//!         Some(err.map_or(Duration::from_millis(100), |h| h.0))
+//!     }
+//! }
+//!
+//! let mut s = FromHint;
+//! assert_eq!(s.delay(0, None), Some(Duration::from_millis(100)));
+//! assert_eq!(
+//!     s.delay(1, Some(&Hint(Duration::from_secs(1)))),
+//!     Some(Duration::from_secs(1))
+//! );
+//! assert_eq!(s.delay(3, None), None);
+//! ```
+use core::time::Duration;
+
+/// A strategy that can use the last error to decide the next delay
+pub trait RetryStrategy<E> {
+    /// The delay before the next attempt, or `None` to stop retrying.
+    /// `attempt` is the number of retries so far and `err` the error the last
+    /// attempt returned, if any
+    fn delay(&mut self, attempt: usize, err: Option<&E>) -> Option<Duration>;
+
+    /// An upper bound on the delays left, `None` if it isn't known. The
+    /// retry loops stop without sleeping once this is `Some(0)`
+    fn remaining(&self) -> Option<usize> {
+        None
+    }
+}
+
+impl<I, E> RetryStrategy<E> for I
+where
+    I: Iterator<Item = Duration>,
+{
+    fn delay(&mut self, _attempt: usize, _err: Option<&E>) -> Option<Duration> {
+        self.next()
+    }
+
+    fn remaining(&self) -> Option<usize> {
+        self.size_hint().1
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use super::*;
+    use crate::strategy::Schedule;
+
+    #[test]
+    fn iterator_ignores_err() {
+        let mut s = Schedule::from_millis(&[10, 20]);
+        assert_eq!(s.delay(0, Some(&"err")), Some(Duration::from_millis(10)));
+        assert_eq!(
+            RetryStrategy::<&str>::delay(&mut s, 1, None),
+            Some(Duration::from_millis(20))
+        );
+        assert_eq!(RetryStrategy::<&str>::remaining(&s), Some(0));
+        assert_eq!(s.delay(2, Some(&"err")), None);
+    }
+}
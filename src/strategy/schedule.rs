@@ -0,0 +1,129 @@
+//! fixed schedule
+//!
+//! Yields exactly the delays it was given, in order, then ends
+//! ex. |-|---|--------|
+//!
+//! ```rust
+//! # use retry_fn::strategy::Schedule;
+//! # use std::time::Duration;
+//! let mut s = Schedule::new(vec![
+//!     Duration::from_millis(100),
+//!     Duration::from_millis(500),
+//!     Duration::from_secs(2),
+//! ]);
+//! assert_eq!(s.next(), Some(Duration::from_millis(100)));
+//! assert_eq!(s.next(), Some(Duration::from_millis(500)));
+//! assert_eq!(s.next(), Some(Duration::from_secs(2)));
+//! assert_eq!(s.next(), None);
+//! ```
+use super::ResetStrategy;
+use alloc::vec::Vec;
+use core::{iter::FromIterator, time::Duration};
+
+/// Define a type for a fixed schedule of delays
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schedule {
+    delays: Vec<Duration>,
+    pos: usize,
+}
+
+impl From<Vec<Duration>> for Schedule {
+    fn from(delays: Vec<Duration>) -> Self {
+        Self { delays, pos: 0 }
+    }
+}
+
+impl From<&[Duration]> for Schedule {
+    fn from(delays: &[Duration]) -> Self {
+        Vec::from(delays).into()
+    }
+}
+
+impl FromIterator<Duration> for Schedule {
+    fn from_iter<T: IntoIterator<Item = Duration>>(iter: T) -> Self {
+        iter.into_iter().collect::<Vec<_>>().into()
+    }
+}
+
+impl Schedule {
+    /// Create a new `Schedule` from a list of delays
+    pub fn new(delays: Vec<Duration>) -> Self {
+        delays.into()
+    }
+
+    /// create a new type from a list of millisecond delays
+    pub fn from_millis(millis: &[u64]) -> Self {
+        millis.iter().copied().map(Duration::from_millis).collect()
+    }
+
+    /// create a new type from a list of second delays
+    pub fn from_secs(secs: &[u64]) -> Self {
+        secs.iter().copied().map(Duration::from_secs).collect()
+    }
+
+    /// The full list of delays in this schedule
+    pub fn delays(&self) -> &[Duration] {
+        &self.delays
+    }
+}
+
+impl Iterator for Schedule {
+    type Item = Duration;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let next = self.delays.get(self.pos).copied()?;
+        self.pos += 1;
+        Some(next)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.delays.len() - self.pos;
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for Schedule {}
+
+impl ResetStrategy for Schedule {
+    fn reset(&mut self) {
+        self.pos = 0;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn yields_in_order() {
+        let mut s = Schedule::from_millis(&[100, 20, 300]);
+        assert_eq!(s.len(), 3);
+        assert_eq!(s.next(), Some(Duration::from_millis(100)));
+        assert_eq!(s.next(), Some(Duration::from_millis(20)));
+        assert_eq!(s.next(), Some(Duration::from_millis(300)));
+        assert_eq!(s.next(), None);
+        assert_eq!(s.next(), None);
+    }
+
+    #[test]
+    fn from_slice() {
+        let delays = [Duration::from_secs(1), Duration::from_secs(2)];
+        let s = Schedule::from(&delays[..]);
+        assert_eq!(s.collect::<Vec<_>>(), delays.to_vec());
+    }
+
+    #[test]
+    fn reset() {
+        let mut s = Schedule::from_millis(&[100, 200]);
+        assert_eq!(s.by_ref().count(), 2);
+        s.reset();
+        assert_eq!(s.len(), 2);
+        assert_eq!(s.next(), Some(Duration::from_millis(100)));
+    }
+
+    #[test]
+    fn empty() {
+        let mut s = Schedule::new(Vec::new());
+        assert_eq!(s.next(), None);
+    }
+}
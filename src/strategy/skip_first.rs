@@ -0,0 +1,90 @@
+//! skip first
+//!
+//! Wraps another strategy and drops the first delay it yields
+//!
+//! ```rust
+//! # use retry_fn::strategy::{Schedule, SkipFirst};
+//! # use std::time::Duration;
+//! let mut s = SkipFirst::new(Schedule::from_millis(&[10, 20, 30]));
+//! assert_eq!(s.next(), Some(Duration::from_millis(20)));
+//! assert_eq!(s.next(), Some(Duration::from_millis(30)));
+//! assert_eq!(s.next(), None);
+//! ```
+use super::ResetStrategy;
+use core::time::Duration;
+
+/// Define a type that skips the first delay of some inner strategy
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SkipFirst<S> {
+    inner: S,
+    skipped: bool,
+}
+
+impl<S> SkipFirst<S> {
+    /// Create a new `SkipFirst` wrapping `inner`
+    pub const fn new(inner: S) -> Self {
+        Self {
+            inner,
+            skipped: false,
+        }
+    }
+
+    /// Consume the `SkipFirst`, returning the wrapped strategy
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S> Iterator for SkipFirst<S>
+where
+    S: Iterator<Item = Duration>,
+{
+    type Item = Duration;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if !self.skipped {
+            self.skipped = true;
+            self.inner.next()?;
+        }
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lo, hi) = self.inner.size_hint();
+        if self.skipped {
+            (lo, hi)
+        } else {
+            (lo.saturating_sub(1), hi.map(|hi| hi.saturating_sub(1)))
+        }
+    }
+}
+
+impl<S> ResetStrategy for SkipFirst<S>
+where
+    S: ResetStrategy,
+{
+    fn reset(&mut self) {
+        self.inner.reset();
+        self.skipped = false;
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use super::*;
+    use crate::strategy::{ExponentialBackoff, Schedule};
+
+    #[test]
+    fn skips_one() {
+        let mut s = SkipFirst::new(ExponentialBackoff::from_millis(100));
+        assert_eq!(s.next(), Some(Duration::from_millis(400)));
+        assert_eq!(s.next(), Some(Duration::from_millis(800)));
+    }
+
+    #[test]
+    fn empty() {
+        let mut s = SkipFirst::new(Schedule::from_millis(&[10]));
+        assert_eq!(s.size_hint(), (0, Some(0)));
+        assert_eq!(s.next(), None);
+    }
+}
@@ -0,0 +1,322 @@
+//! strategies from a spec string
+//!
+//! `Spec` parses a one-line description of a strategy, useful for command
+//! line flags and environment variables. A spec is a kind, optionally
+//! followed by comma separated arguments, either after a `:` or in
+//! parentheses, so `exponential:100ms,max=10s` and
+//! `exponential(100ms, max=10s)` are the same spec
+//!
+//! - `immediate`
+//! - `constant:<duration>`
+//! - `exponential:<duration>`, also takes `base=<n>`
+//! - `decorrelated:<duration>`
+//! - `random:<duration>..<duration>`
+//!
+//! Any kind also takes `max=<duration>` to cap each delay, `retries=<n>` to
+//! end after n delays, and `jitter` to apply full jitter. Durations are
+//! numbers with a unit of `ns`, `us`, `ms`, `s`, `m` or `h`, and units can be
+//! combined, ex. `1m30s`
+//!
+//! ```rust
+//! # use retry_fn::strategy::Spec;
+//! # use std::time::Duration;
+//! let mut s: Spec = "exponential:100ms,max=1s,retries=3".parse().unwrap();
+//! assert_eq!(s.next(), Some(Duration::from_millis(200)));
+//! assert_eq!(s.next(), Some(Duration::from_millis(400)));
+//! assert_eq!(s.next(), Some(Duration::from_millis(800)));
+//! assert_eq!(s.next(), None);
+//!
+//! let s: Spec = "constant(2s, jitter)".parse().unwrap();
+//! assert!(s.take(5).all(|d| d <= Duration::from_secs(2)));
+//! ```
+use super::{
+    Constant, DecorrelatedJitter, ExponentialBackoff, Immediate, RandomRange, ResetStrategy,
+};
+use std::{error::Error, fmt, str::FromStr, time::Duration};
+
+/// Define a type for a strategy parsed from a spec string
+///
+/// A clone with `jitter` gets its own seed, so clones don't jitter in
+/// lockstep
+#[derive(Debug)]
+pub struct Spec {
+    kind: Kind,
+    max: Option<Duration>,
+    retries: Option<usize>,
+    jitter: Option<fastrand::Rng>,
+    taken: usize,
+}
+
+#[derive(Debug, Clone)]
+enum Kind {
+    Immediate(Immediate),
+    Constant(Constant),
+    Exponential(ExponentialBackoff),
+    Decorrelated(DecorrelatedJitter),
+    Random(RandomRange),
+}
+
+/// Error returned when a spec string can't be parsed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseSpecError {
+    pub(crate) msg: String,
+}
+
+impl ParseSpecError {
+    pub(crate) fn new(msg: impl Into<String>) -> Self {
+        Self { msg: msg.into() }
+    }
+}
+
+impl Clone for Spec {
+    fn clone(&self) -> Self {
+        Self {
+            kind: self.kind.clone(),
+            max: self.max,
+            retries: self.retries,
+            jitter: self.jitter.as_ref().map(|_| fastrand::Rng::new()),
+            taken: self.taken,
+        }
+    }
+}
+
+impl fmt::Display for ParseSpecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid strategy spec: {}", self.msg)
+    }
+}
+
+impl Error for ParseSpecError {}
+
+impl FromStr for Spec {
+    type Err = ParseSpecError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (name, args) = match s.find([':', '(']) {
+            Some(i) if s[i..].starts_with('(') => {
+                let args = s[i + 1..]
+                    .strip_suffix(')')
+                    .ok_or_else(|| ParseSpecError::new(format!("missing `)` in `{}`", s)))?;
+                (&s[..i], split_args(args))
+            }
+            Some(i) => (&s[..i], split_args(&s[i + 1..])),
+            None => (s, Vec::new()),
+        };
+        let mut args = args.into_iter();
+        let mut arg = |what: &str| {
+            args.next()
+                .filter(|a| !a.is_empty() && !a.contains('='))
+                .ok_or_else(|| ParseSpecError::new(format!("`{}` needs {}", name, what)))
+        };
+        let mut kind = match name.trim() {
+            "immediate" => Kind::Immediate(Immediate),
+            "constant" => Kind::Constant(Constant::new(parse_duration(arg("a delay")?)?)),
+            "exponential" => {
+                Kind::Exponential(ExponentialBackoff::new(parse_duration(arg("a delay")?)?))
+            }
+            "decorrelated" => {
+                Kind::Decorrelated(DecorrelatedJitter::new(parse_duration(arg("a delay")?)?))
+            }
+            "random" => {
+                let range = arg("a range")?;
+                let i = range
+                    .find("..")
+                    .ok_or_else(|| ParseSpecError::new(format!("bad range `{}`", range)))?;
+                Kind::Random(RandomRange::new(
+                    parse_duration(&range[..i])?,
+                    parse_duration(&range[i + 2..])?,
+                ))
+            }
+            other => return Err(ParseSpecError::new(format!("unknown kind `{}`", other))),
+        };
+        let (mut max, mut retries, mut jitter) = (None, None, None);
+        for opt in args {
+            let (key, val) = match opt.find('=') {
+                Some(i) => (opt[..i].trim(), Some(opt[i + 1..].trim())),
+                None => (opt, None),
+            };
+            match (key, val, &mut kind) {
+                ("max", Some(v), _) => max = Some(parse_duration(v)?),
+                ("retries", Some(v), _) => {
+                    retries = Some(
+                        v.parse()
+                            .map_err(|_| ParseSpecError::new(format!("bad retries `{}`", v)))?,
+                    )
+                }
+                ("jitter", None, _) => jitter = Some(fastrand::Rng::new()),
+                ("base", Some(v), Kind::Exponential(e)) => {
+                    let base = v
+                        .parse()
+                        .map_err(|_| ParseSpecError::new(format!("bad base `{}`", v)))?;
+                    *e = e.base(base);
+                }
+                _ => return Err(ParseSpecError::new(format!("unknown option `{}`", opt))),
+            }
+        }
+        Ok(Spec {
+            kind,
+            max,
+            retries,
+            jitter,
+            taken: 0,
+        })
+    }
+}
+
+/// split comma separated arguments, `immediate()` has none
+fn split_args(args: &str) -> Vec<&str> {
+    if args.trim().is_empty() {
+        return Vec::new();
+    }
+    args.split(',').map(str::trim).collect()
+}
+
+/// parse a duration like `100ms` or `1m30s`
+fn parse_duration(s: &str) -> Result<Duration, ParseSpecError> {
+    let bad = || ParseSpecError::new(format!("bad duration `{}`", s));
+    let mut rest = s.trim();
+    if rest.is_empty() {
+        return Err(bad());
+    }
+    let mut total = Duration::from_millis(0);
+    while !rest.is_empty() {
+        let digits = rest.find(|c: char| !c.is_ascii_digit()).ok_or_else(bad)?;
+        let n: u64 = rest[..digits].parse().map_err(|_| bad())?;
+        rest = &rest[digits..];
+        let unit = rest
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let d = match &rest[..unit] {
+            "ns" => Duration::from_nanos(n),
+            "us" => Duration::from_micros(n),
+            "ms" => Duration::from_millis(n),
+            "s" => Duration::from_secs(n),
+            "m" => Duration::from_secs(n.checked_mul(60).ok_or_else(bad)?),
+            "h" => Duration::from_secs(n.checked_mul(3600).ok_or_else(bad)?),
+            _ => return Err(bad()),
+        };
+        total = total.checked_add(d).ok_or_else(bad)?;
+        rest = &rest[unit..];
+    }
+    Ok(total)
+}
+
+impl Iterator for Spec {
+    type Item = Duration;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.retries.is_some_and(|r| self.taken >= r) {
+            return None;
+        }
+        let mut next = match &mut self.kind {
+            Kind::Immediate(s) => s.next(),
+            Kind::Constant(s) => s.next(),
+            Kind::Exponential(s) => s.next(),
+            Kind::Decorrelated(s) => s.next(),
+            Kind::Random(s) => s.next(),
+        }?;
+        if let Some(max) = self.max {
+            next = next.min(max);
+        }
+        if let Some(rng) = &mut self.jitter {
+            next = super::rand_between(rng, Duration::from_millis(0), next);
+        }
+        self.taken += 1;
+        Some(next)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self.retries {
+            Some(r) => {
+                let left = r.saturating_sub(self.taken);
+                (left, Some(left))
+            }
+            None => (usize::MAX, None),
+        }
+    }
+}
+
+impl ResetStrategy for Spec {
+    fn reset(&mut self) {
+        match &mut self.kind {
+            Kind::Immediate(s) => s.reset(),
+            Kind::Constant(s) => s.reset(),
+            Kind::Exponential(s) => s.reset(),
+            Kind::Decorrelated(s) => s.reset(),
+            Kind::Random(s) => s.reset(),
+        }
+        self.taken = 0;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn durations() {
+        assert_eq!(parse_duration("100ms"), Ok(Duration::from_millis(100)));
+        assert_eq!(parse_duration("1m30s"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_duration("2h"), Ok(Duration::from_secs(7200)));
+        assert_eq!(parse_duration("5us"), Ok(Duration::from_micros(5)));
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("10").is_err());
+        assert!(parse_duration("ms").is_err());
+        assert!(parse_duration("10 days").is_err());
+    }
+
+    #[test]
+    fn constant() {
+        let s: Spec = "constant:2s".parse().unwrap();
+        assert_eq!(
+            s.take(2).collect::<Vec<_>>(),
+            vec![Duration::from_secs(2); 2]
+        );
+    }
+
+    #[test]
+    fn exponential_base() {
+        let mut s: Spec = "exponential:10ms, base=10, retries=2".parse().unwrap();
+        assert_eq!(s.next(), Some(Duration::from_millis(100)));
+        assert_eq!(s.next(), Some(Duration::from_millis(1000)));
+        assert_eq!(s.next(), None);
+        s.reset();
+        assert_eq!(s.next(), Some(Duration::from_millis(100)));
+    }
+
+    #[test]
+    fn parens() {
+        let mut s: Spec = "exponential(100ms, max=300ms, retries=3)".parse().unwrap();
+        assert_eq!(s.next(), Some(Duration::from_millis(200)));
+        assert_eq!(s.next(), Some(Duration::from_millis(300)));
+        assert_eq!(s.next(), Some(Duration::from_millis(300)));
+        assert_eq!(s.next(), None);
+        assert!("immediate()".parse::<Spec>().is_ok());
+        assert!("constant(1s".parse::<Spec>().is_err());
+        assert!("constant()".parse::<Spec>().is_err());
+    }
+
+    #[test]
+    fn random() {
+        let s: Spec = "random:1s..2s".parse().unwrap();
+        for d in s.take(10) {
+            assert!(d >= Duration::from_secs(1) && d <= Duration::from_secs(2));
+        }
+    }
+
+    #[test]
+    fn errors() {
+        assert!("".parse::<Spec>().is_err());
+        assert!("linear:1s".parse::<Spec>().is_err());
+        assert!("constant".parse::<Spec>().is_err());
+        assert!("constant:max=1s".parse::<Spec>().is_err());
+        assert!("constant:1s,base=2".parse::<Spec>().is_err());
+        assert!("constant:1s,jitter=yes".parse::<Spec>().is_err());
+        assert!("random:1s".parse::<Spec>().is_err());
+        assert_eq!(
+            "fixed:1s".parse::<Spec>().unwrap_err().to_string(),
+            "invalid strategy spec: unknown kind `fixed`"
+        );
+    }
+}
@@ -0,0 +1,138 @@
+//! spread
+//!
+//! Spread a fixed number of attempts over a total window of time. Delays are
+//! either all equal, or double each time like exponential backoff, and always
+//! add up to exactly the window
+//!
+//! ```rust
+//! # use retry_fn::strategy::Spread;
+//! # use std::time::Duration;
+//! let s = Spread::attempts_over(4, Duration::from_secs(10));
+//! assert_eq!(s.collect::<Vec<_>>(), vec![Duration::from_millis(2500); 4]);
+//!
+//! let s = Spread::exponential_over(3, Duration::from_secs(7));
+//! assert_eq!(
+//!     s.collect::<Vec<_>>(),
+//!     vec![
+//!         Duration::from_secs(1),
+//!         Duration::from_secs(2),
+//!         Duration::from_secs(4),
+//!     ]
+//! );
+//! ```
+use super::ResetStrategy;
+use core::time::Duration;
+
+/// Define a type for the spread iterator
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Spread {
+    attempts: u32,
+    window: Duration,
+    exponential: bool,
+    taken: u32,
+    spent: Duration,
+}
+
+impl Spread {
+    /// Create a new `Spread` of `attempts` equal delays adding up to `window`
+    pub const fn attempts_over(attempts: u32, window: Duration) -> Self {
+        Self {
+            attempts,
+            window,
+            exponential: false,
+            taken: 0,
+            spent: Duration::from_millis(0),
+        }
+    }
+
+    /// Create a new `Spread` of `attempts` doubling delays adding up to
+    /// `window`
+    pub const fn exponential_over(attempts: u32, window: Duration) -> Self {
+        Self {
+            exponential: true,
+            ..Self::attempts_over(attempts, window)
+        }
+    }
+
+    /// The total of all delays in the series
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+}
+
+impl Iterator for Spread {
+    type Item = Duration;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.taken >= self.attempts {
+            return None;
+        }
+        self.taken += 1;
+        let left = self
+            .window
+            .checked_sub(self.spent)
+            .unwrap_or_else(|| Duration::from_millis(0));
+        let next = if self.taken == self.attempts {
+            // the last delay takes whatever is left over from rounding
+            left
+        } else if self.exponential {
+            // delay i of n is window * 2^i / (2^n - 1)
+            let share = pow2(self.taken - 1) / (pow2(self.attempts) - 1.);
+            self.window.mul_f64(share).min(left)
+        } else {
+            self.window / self.attempts
+        };
+        self.spent += next;
+        Some(next)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let left = (self.attempts - self.taken) as usize;
+        (left, Some(left))
+    }
+}
+
+impl ExactSizeIterator for Spread {}
+
+/// `2^n` as a float, `f64::powi` needs std
+fn pow2(n: u32) -> f64 {
+    (0..n).fold(1., |p, _| p * 2.)
+}
+
+impl ResetStrategy for Spread {
+    fn reset(&mut self) {
+        self.taken = 0;
+        self.spent = Duration::from_millis(0);
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn sums_to_window() {
+        for &n in &[1, 3, 7, 8, 64, 200] {
+            let window = Duration::from_secs(600);
+            let s = Spread::attempts_over(n, window);
+            assert_eq!(s.len(), n as usize);
+            assert_eq!(s.sum::<Duration>(), window);
+            let s = Spread::exponential_over(n, window);
+            assert_eq!(s.sum::<Duration>(), window);
+        }
+    }
+
+    #[test]
+    fn exponential_doubles() {
+        let s = Spread::exponential_over(8, Duration::from_millis(255));
+        let delays = s.collect::<Vec<_>>();
+        assert_eq!(delays[0], Duration::from_millis(1));
+        assert_eq!(delays[7], Duration::from_millis(128));
+    }
+
+    #[test]
+    fn zero_attempts() {
+        let mut s = Spread::attempts_over(0, Duration::from_secs(1));
+        assert_eq!(s.next(), None);
+    }
+}
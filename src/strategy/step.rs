@@ -0,0 +1,163 @@
+//! step backoff
+//!
+//! A piecewise schedule, made of steps that each repeat one delay some number
+//! of times, optionally followed by a delay that repeats forever
+//! ex. |-|-|-|---|---|---|---|---|----------|----------|...
+//!
+//! ```rust
+//! # use retry_fn::strategy::StepBackoff;
+//! # use std::time::Duration;
+//! let mut s = StepBackoff::new()
+//!     .step(2, Duration::from_millis(100))
+//!     .step(1, Duration::from_secs(1))
+//!     .then_forever(Duration::from_secs(30));
+//! assert_eq!(s.next(), Some(Duration::from_millis(100)));
+//! assert_eq!(s.next(), Some(Duration::from_millis(100)));
+//! assert_eq!(s.next(), Some(Duration::from_secs(1)));
+//! assert_eq!(s.next(), Some(Duration::from_secs(30)));
+//! assert_eq!(s.next(), Some(Duration::from_secs(30)));
+//! ```
+use super::ResetStrategy;
+use alloc::vec::Vec;
+use core::time::Duration;
+
+/// Define a type for the step backoff iterator
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct StepBackoff {
+    steps: Vec<(usize, Duration)>,
+    forever: Option<Duration>,
+    step: usize,
+    taken: usize,
+}
+
+impl StepBackoff {
+    /// Create a new, empty, `StepBackoff`. With no steps added it will end
+    /// immediately
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a step that yields `duration` `count` times
+    pub fn step(mut self, count: usize, duration: Duration) -> Self {
+        self.steps.push((count, duration));
+        self
+    }
+
+    /// After all steps are exhausted, yield `duration` forever. Without this
+    /// the series ends after the last step
+    pub fn then_forever(mut self, duration: Duration) -> Self {
+        self.forever = Some(duration);
+        self
+    }
+
+    /// The steps in this backoff, as `(count, duration)` pairs
+    pub fn steps(&self) -> &[(usize, Duration)] {
+        &self.steps
+    }
+
+    /// The delay used once all steps are exhausted, if any
+    pub fn forever(&self) -> Option<Duration> {
+        self.forever
+    }
+}
+
+impl Iterator for StepBackoff {
+    type Item = Duration;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(&(count, duration)) = self.steps.get(self.step) {
+            if self.taken < count {
+                self.taken += 1;
+                return Some(duration);
+            }
+            self.step += 1;
+            self.taken = 0;
+        }
+        self.forever
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.forever.is_some() {
+            return (usize::MAX, None);
+        }
+        let left = self.steps[self.step.min(self.steps.len())..]
+            .iter()
+            .try_fold(0usize, |n, &(count, _)| n.checked_add(count))
+            .map(|n| n - self.taken);
+        match left {
+            Some(left) => (left, Some(left)),
+            None => (usize::MAX, None),
+        }
+    }
+}
+
+impl ResetStrategy for StepBackoff {
+    fn reset(&mut self) {
+        self.step = 0;
+        self.taken = 0;
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn steps() {
+        let s = StepBackoff::new()
+            .step(3, Duration::from_millis(100))
+            .step(0, Duration::from_millis(200))
+            .step(2, Duration::from_secs(1));
+        assert_eq!(
+            s.collect::<Vec<_>>(),
+            vec![
+                Duration::from_millis(100),
+                Duration::from_millis(100),
+                Duration::from_millis(100),
+                Duration::from_secs(1),
+                Duration::from_secs(1),
+            ]
+        );
+    }
+
+    #[test]
+    fn forever() {
+        let mut s = StepBackoff::new()
+            .step(1, Duration::from_millis(100))
+            .then_forever(Duration::from_secs(30));
+        assert_eq!(s.next(), Some(Duration::from_millis(100)));
+        for _ in 0..10 {
+            assert_eq!(s.next(), Some(Duration::from_secs(30)));
+        }
+    }
+
+    #[test]
+    fn reset() {
+        let mut s = StepBackoff::new()
+            .step(1, Duration::from_millis(100))
+            .step(1, Duration::from_millis(200));
+        assert_eq!(s.by_ref().count(), 2);
+        s.reset();
+        assert_eq!(s.next(), Some(Duration::from_millis(100)));
+    }
+
+    #[test]
+    fn empty() {
+        let mut s = StepBackoff::new();
+        assert_eq!(s.next(), None);
+    }
+
+    #[test]
+    fn size_hint() {
+        let mut s = StepBackoff::new()
+            .step(2, Duration::from_millis(100))
+            .step(1, Duration::from_secs(1));
+        assert_eq!(s.size_hint(), (3, Some(3)));
+        s.next();
+        assert_eq!(s.size_hint(), (2, Some(2)));
+        s.by_ref().count();
+        assert_eq!(s.size_hint(), (0, Some(0)));
+        let s = s.then_forever(Duration::from_secs(30));
+        assert_eq!(s.size_hint(), (usize::MAX, None));
+    }
+}
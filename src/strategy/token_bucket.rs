@@ -0,0 +1,169 @@
+//! token bucket
+//!
+//! Wraps another strategy and rate limits it with a token bucket. Each delay
+//! takes a token, and tokens refill at one per `refill` up to `capacity`. When
+//! the bucket is empty the delay is stretched until the next token is ready,
+//! so bursts of retries slow down automatically
+//!
+//! ```rust
+//! # use retry_fn::strategy::{Immediate, TokenBucket};
+//! # use std::time::Duration;
+//! let mut s = TokenBucket::new(Immediate, 2, Duration::from_secs(1));
+//! assert_eq!(s.next(), Some(Duration::from_millis(0)));
+//! assert_eq!(s.next(), Some(Duration::from_millis(0)));
+//! assert!(s.next().unwrap() > Duration::from_millis(900));
+//! ```
+use super::ResetStrategy;
+use crate::Instant;
+use std::time::Duration;
+
+/// Define a type that rate limits some inner strategy with a token bucket
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct TokenBucket<S> {
+    inner: S,
+    capacity: u32,
+    refill: Duration,
+    tokens: u32,
+    last: Instant,
+}
+
+impl<S> TokenBucket<S> {
+    /// Create a new `TokenBucket` wrapping `inner`, starting with a full
+    /// bucket of `capacity` tokens and adding one every `refill`
+    ///
+    /// # Panics
+    /// Panics if `refill` is zero
+    pub fn new(inner: S, capacity: u32, refill: Duration) -> Self {
+        assert!(
+            refill > Duration::from_millis(0),
+            "token bucket refill must be non-zero"
+        );
+        Self {
+            inner,
+            capacity,
+            refill,
+            tokens: capacity,
+            last: Instant::now(),
+        }
+    }
+
+    /// The number of tokens currently in the bucket
+    pub fn tokens(&self) -> u32 {
+        self.tokens
+    }
+
+    /// Consume the `TokenBucket`, returning the wrapped strategy
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+
+    fn fill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.last);
+        let n = elapsed.as_nanos() / self.refill.as_nanos();
+        if n == 0 {
+            return;
+        }
+        let room = u128::from(self.capacity - self.tokens);
+        if n >= room {
+            self.tokens = self.capacity;
+            self.last = now;
+        } else {
+            self.tokens += n as u32;
+            self.last += self.refill * n as u32;
+        }
+    }
+
+    fn delay_at(&mut self, delay: Duration, now: Instant) -> Duration {
+        self.fill(now);
+        if self.tokens > 0 {
+            self.tokens -= 1;
+            return delay;
+        }
+        // borrow the next token, waiting until it's ready
+        match self.last.checked_add(self.refill) {
+            Some(last) => {
+                self.last = last;
+                delay.max(last.saturating_duration_since(now))
+            }
+            None => Duration::MAX,
+        }
+    }
+}
+
+impl<S> Iterator for TokenBucket<S>
+where
+    S: Iterator<Item = Duration>,
+{
+    type Item = Duration;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let delay = self.inner.next()?;
+        Some(self.delay_at(delay, Instant::now()))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+/// Resets the inner strategy, the bucket is left as is so the rate limit
+/// carries across calls
+impl<S> ResetStrategy for TokenBucket<S>
+where
+    S: ResetStrategy,
+{
+    fn reset(&mut self) {
+        self.inner.reset();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::strategy::{Constant, Immediate};
+
+    #[test]
+    fn stretches_when_empty() {
+        let mut s = TokenBucket::new(Immediate, 1, Duration::from_secs(1));
+        let now = s.last;
+        assert_eq!(
+            s.delay_at(Duration::from_millis(0), now),
+            Duration::from_millis(0)
+        );
+        assert_eq!(
+            s.delay_at(Duration::from_millis(0), now),
+            Duration::from_secs(1)
+        );
+        assert_eq!(
+            s.delay_at(Duration::from_millis(0), now),
+            Duration::from_secs(2)
+        );
+    }
+
+    #[test]
+    fn refills() {
+        let mut s = TokenBucket::new(Immediate, 2, Duration::from_secs(1));
+        let now = s.last;
+        s.delay_at(Duration::from_millis(0), now);
+        s.delay_at(Duration::from_millis(0), now);
+        assert_eq!(s.tokens(), 0);
+        let later = now + Duration::from_millis(1500);
+        assert_eq!(
+            s.delay_at(Duration::from_millis(0), later),
+            Duration::from_millis(0)
+        );
+        assert_eq!(
+            s.delay_at(Duration::from_millis(0), later),
+            Duration::from_millis(500)
+        );
+        let much_later = later + Duration::from_secs(60);
+        s.fill(much_later);
+        assert_eq!(s.tokens(), 2);
+    }
+
+    #[test]
+    fn keeps_longer_delay() {
+        let mut s = TokenBucket::new(Constant::from_secs(5), 0, Duration::from_secs(1));
+        assert_eq!(s.next(), Some(Duration::from_secs(5)));
+    }
+}
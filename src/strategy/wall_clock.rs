@@ -0,0 +1,116 @@
+//! wall clock
+//!
+//! Retry at fixed points on the wall clock instead of after relative delays.
+//! Points are every `period` since the unix epoch, shifted by some optional
+//! offset, so `every(Duration::from_secs(60))` retries on each minute boundary
+//! and `every(Duration::from_secs(3600)).offset(Duration::from_secs(300))`
+//! retries every hour at :05 (UTC). Each delay is the time until the next point
+//!
+//! ```rust
+//! # use retry_fn::strategy::WallClock;
+//! # use std::time::Duration;
+//! let mut s = WallClock::every(Duration::from_secs(60));
+//! assert!(s.next().unwrap() <= Duration::from_secs(60));
+//! ```
+use super::ResetStrategy;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Define a type for the wall clock iterator
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct WallClock {
+    period: Duration,
+    offset: Duration,
+}
+
+impl WallClock {
+    /// Create a new `WallClock` retrying on every multiple of `period` since
+    /// the unix epoch
+    ///
+    /// # Panics
+    /// Panics if `period` is zero
+    pub const fn every(period: Duration) -> Self {
+        assert!(period.as_nanos() > 0, "wall clock period must be non-zero");
+        Self {
+            period,
+            offset: Duration::from_millis(0),
+        }
+    }
+
+    /// Shift each point forward by `offset`, offsets longer than the period
+    /// wrap around
+    pub const fn offset(mut self, offset: Duration) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// create a new type retrying every n seconds
+    pub const fn every_secs(secs: u64) -> Self {
+        Self::every(Duration::from_secs(secs))
+    }
+
+    /// create a new type retrying every n minutes
+    pub const fn every_mins(mins: u64) -> Self {
+        Self::every(Duration::from_secs(mins.saturating_mul(60)))
+    }
+
+    /// The time from `now` until the next point, if `now` is exactly on a
+    /// point the following one is used
+    fn until_next(&self, now: SystemTime) -> Duration {
+        let since = now
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_else(|_| Duration::from_millis(0))
+            .as_nanos();
+        let period = self.period.as_nanos();
+        let offset = self.offset.as_nanos() % period;
+        let into = (since + period - offset) % period;
+        let nanos = period - into;
+        Duration::new(
+            (nanos / 1_000_000_000) as u64,
+            (nanos % 1_000_000_000) as u32,
+        )
+    }
+}
+
+impl Iterator for WallClock {
+    type Item = Duration;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.until_next(SystemTime::now()))
+    }
+}
+
+impl ResetStrategy for WallClock {
+    fn reset(&mut self) {}
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn at(secs: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn minute_boundary() {
+        let s = WallClock::every_mins(1);
+        assert_eq!(s.until_next(at(61)), Duration::from_secs(59));
+        assert_eq!(s.until_next(at(119)), Duration::from_secs(1));
+        assert_eq!(s.until_next(at(120)), Duration::from_secs(60));
+    }
+
+    #[test]
+    fn hourly_offset() {
+        let s = WallClock::every_mins(60).offset(Duration::from_secs(300));
+        assert_eq!(s.until_next(at(0)), Duration::from_secs(300));
+        assert_eq!(s.until_next(at(301)), Duration::from_secs(3599));
+        assert_eq!(s.until_next(at(3600 * 5 + 200)), Duration::from_secs(100));
+    }
+
+    #[test]
+    fn sub_second() {
+        let s = WallClock::every(Duration::from_millis(250));
+        let now = UNIX_EPOCH + Duration::from_millis(1100);
+        assert_eq!(s.until_next(now), Duration::from_millis(150));
+    }
+}
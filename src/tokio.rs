@@ -30,11 +30,248 @@
 //! # }
 //! ```
 
-retry_impl!(tokio::time::sleep);
+#[cfg(feature = "tokio-spawn")]
+use std::{
+    pin::Pin,
+    sync::{Arc, Mutex, PoisonError},
+    task::{Context, Poll},
+};
+
+/// tokio's timer
+#[derive(Debug, Clone, Copy)]
+pub struct Timer;
+
+impl crate::runtime::Sleeper for Timer {
+    type Sleep = tokio::time::Sleep;
+
+    fn sleep(dur: Duration) -> Self::Sleep {
+        tokio::time::sleep(dur)
+    }
+
+    fn sleep_until(deadline: crate::Instant) -> Self::Sleep {
+        tokio::time::sleep_until(tokio::time::Instant::from_std(deadline))
+    }
+
+    fn now() -> crate::Instant {
+        tokio::time::Instant::now().into_std()
+    }
+}
+
+retry_impl!(Timer);
+
+/// Connect to `addr` with tokio's `TcpStream`, retrying transient errors
+/// with the delays of `strategy`. See [`crate::net::connect_with_retry`].
+/// Enable the `tokio-net` feature to use this
+///
+/// # Returns
+/// The stream, otherwise the last error as a `FailedAttempt` once an error
+/// isn't transient or the strategy ends
+#[cfg(feature = "tokio-net")]
+pub async fn connect_with_retry<A, S>(
+    addr: A,
+    strategy: S,
+) -> Result<tokio::net::TcpStream, RetryErr<std::io::Error>>
+where
+    A: tokio::net::ToSocketAddrs,
+    S: RetryStrategy<std::io::Error>,
+{
+    let addr = &addr;
+    retry_transient(strategy, move |_| tokio::net::TcpStream::connect(addr)).await
+}
+
+/// Retry a future based on an iterator over Duration until `token` is
+/// cancelled. Cancelling drops the running attempt or pending delay right
+/// away, see [`crate::retry_cancellable`]. Enable the `tokio-util` feature
+/// to use this
+///
+/// ```rust,no_run
+/// use retry_fn::{strategy::Constant, tokio::retry_cancellable, RetryErr, RetryResult};
+/// use tokio_util::sync::CancellationToken;
+/// # async fn run() {
+/// let token = CancellationToken::new();
+/// let shutdown = token.clone();
+/// tokio::spawn(async move { shutdown.cancel() });
+/// let res = retry_cancellable(&token, Constant::from_secs(60), |_| async {
+///     RetryResult::<(), &str>::Retry()
+/// })
+/// .await;
+/// assert!(matches!(res, Err(RetryErr::Cancelled { .. })));
+/// # }
+/// ```
+///
+/// # Returns
+/// If successful, return `Ok`, otherwise return `Retry` to try again or `Err`
+/// to exit with an error. Once cancelled, returns `Cancelled` without calling
+/// the function again
+#[cfg(feature = "tokio-util")]
+pub async fn retry_cancellable<I, F, Fut, T, E>(
+    token: &tokio_util::sync::CancellationToken,
+    iter: I,
+    mut f: F,
+) -> Result<T, RetryErr<E>>
+where
+    I: IntoIterator<Item = Duration>,
+    F: FnMut(RetryOp) -> Fut,
+    Fut: Future<Output = RetryResult<T, E>>,
+{
+    use crate::runtime::Timeout;
+
+    let mut watch = crate::Stopwatch::timed(<Timer as crate::runtime::Sleeper>::now);
+    let mut count = 0;
+    let mut total_delay = Duration::from_millis(0);
+    let mut delays = iter.into_iter();
+    if crate::exhausted(&delays) {
+        return Err(RetryErr::IteratorEnded {
+            tries: 0,
+            total_delay,
+        });
+    }
+    loop {
+        if token.is_cancelled() {
+            return Err(RetryErr::Cancelled {
+                tries: count,
+                total_delay,
+            });
+        }
+        let attempt = Timeout {
+            fut: Box::pin(f(watch.op(count, total_delay))),
+            sleep: Box::pin(token.cancelled()),
+        };
+        let res = match attempt.await {
+            Some(res) => res,
+            None => {
+                return Err(RetryErr::Cancelled {
+                    tries: count,
+                    total_delay,
+                })
+            }
+        };
+        let err = match res.step(count, total_delay) {
+            ControlFlow::Continue(err) => err,
+            ControlFlow::Break(res) => return res,
+        };
+        watch.done();
+        let dur = match crate::next_delay(&mut delays) {
+            Some(dur) => dur,
+            None => return Err(RetryErr::ended(count, total_delay, err)),
+        };
+        let delay = Timeout {
+            fut: Box::pin(tokio::time::sleep(dur)),
+            sleep: Box::pin(token.cancelled()),
+        };
+        if delay.await.is_none() {
+            return Err(RetryErr::Cancelled {
+                tries: count,
+                total_delay,
+            });
+        }
+        total_delay = total_delay.saturating_add(dur);
+        count += 1;
+    }
+}
+
+/// Run [`retry`] as a background task on the current tokio runtime, for
+/// retried jobs nobody waits on right away. Enable the `tokio-spawn` feature
+/// to use this
+///
+/// ```rust,no_run
+/// use retry_fn::{strategy::Constant, tokio::spawn_retry, RetryResult};
+/// # async fn run() {
+/// let handle = spawn_retry(Constant::from_millis(100).take(3), |op| async move {
+///     if op.retries >= 2 {
+///         RetryResult::<_, &str>::Ok(op.retries)
+///     } else {
+///         RetryResult::Retry()
+///     }
+/// });
+/// println!("on attempt {}", handle.attempts());
+/// assert_eq!(handle.await.ok(), Some(2));
+/// # }
+/// ```
+///
+/// # Returns
+/// A [`RetryHandle`] to await the result, abort the task or check how many
+/// attempts it has started
+///
+/// # Panics
+/// If called outside of a tokio runtime
+#[cfg(feature = "tokio-spawn")]
+pub fn spawn_retry<I, F, Fut, T, E>(iter: I, mut f: F) -> RetryHandle<T, E>
+where
+    I: IntoIterator<Item = Duration>,
+    I::IntoIter: Send + 'static,
+    F: FnMut(RetryOp) -> Fut + Send + 'static,
+    Fut: Future<Output = RetryResult<T, E>> + Send + 'static,
+    T: Send + 'static,
+    E: Send + 'static,
+{
+    let last = Arc::new(Mutex::new(None));
+    let op = last.clone();
+    let task = tokio::spawn(retry(iter, move |attempt| {
+        *op.lock().unwrap_or_else(PoisonError::into_inner) = Some(attempt);
+        f(attempt)
+    }));
+    RetryHandle { task, last }
+}
+
+/// A retry running in the background, see [`spawn_retry`]. Awaiting it gives
+/// the result of the retry, dropping it detaches the task
+#[cfg(feature = "tokio-spawn")]
+#[derive(Debug)]
+pub struct RetryHandle<T, E> {
+    task: tokio::task::JoinHandle<Result<T, RetryErr<E>>>,
+    last: Arc<Mutex<Option<RetryOp>>>,
+}
+
+#[cfg(feature = "tokio-spawn")]
+impl<T, E> RetryHandle<T, E> {
+    /// the number of attempts started so far
+    pub fn attempts(&self) -> usize {
+        match self.op() {
+            Some(op) => op.retries + 1,
+            None => 0,
+        }
+    }
+
+    /// Abort the task, dropping the running attempt or pending delay. Awaiting
+    /// the handle afterwards returns `Cancelled`
+    pub fn abort(&self) {
+        self.task.abort();
+    }
+
+    fn op(&self) -> Option<RetryOp> {
+        *self.last.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(feature = "tokio-spawn")]
+impl<T, E> Future for RetryHandle<T, E> {
+    type Output = Result<T, RetryErr<E>>;
+
+    /// # Panics
+    /// If the retried future panicked
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let res = match Pin::new(&mut self.task).poll(cx) {
+            Poll::Ready(res) => res,
+            Poll::Pending => return Poll::Pending,
+        };
+        Poll::Ready(match res {
+            Ok(res) => res,
+            Err(err) if err.is_panic() => std::panic::resume_unwind(err.into_panic()),
+            Err(_) => {
+                let (tries, total_delay) = match self.op() {
+                    Some(op) => (op.retries, op.total_delay),
+                    None => (0, Duration::from_millis(0)),
+                };
+                Err(RetryErr::Cancelled { tries, total_delay })
+            }
+        })
+    }
+}
 
 #[cfg(test)]
 mod test {
-    use crate::RetryResult;
+    use crate::{RetryBudget, RetryErr, RetryResult};
 
     use super::*;
     use crate::strategy::*;
@@ -87,4 +324,682 @@ mod test {
 
         Ok(())
     }
+
+    #[tokio::test(start_paused = true)]
+    async fn total_delay_saturates() {
+        let res = retry(vec![Duration::MAX; 3], |op| async move {
+            if op.retries >= 2 {
+                RetryResult::<Duration, &str>::Ok(op.total_delay)
+            } else {
+                RetryResult::Retry()
+            }
+        })
+        .await;
+        assert_eq!(res.ok(), Some(Duration::MAX));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn initial_delay() {
+        let start = tokio::time::Instant::now();
+        let res = retry_delayed(
+            Duration::from_secs(5),
+            Constant::from_secs(1),
+            |op| async move { RetryResult::<Duration, &str>::Ok(op.total_delay) },
+        )
+        .await;
+        assert_eq!(res.ok(), Some(Duration::from_secs(5)));
+        assert!(start.elapsed() >= Duration::from_secs(5));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn attempt_timeout() {
+        let res = tokio::spawn(retry_with_timeout(
+            Constant::from_secs(1).take(3),
+            Duration::from_secs(5),
+            |op| async move {
+                if op.retries == 1 {
+                    RetryResult::<(), _>::RetryWith("busy")
+                } else {
+                    tokio::time::sleep(Duration::from_secs(60)).await;
+                    RetryResult::Ok(())
+                }
+            },
+        ))
+        .await
+        .unwrap();
+        assert!(matches!(
+            res,
+            Err(RetryErr::TimedOut { tries: 2, total_delay }) if total_delay == Duration::from_secs(2)
+        ));
+        let res = retry_with_timeout(
+            Constant::from_secs(1),
+            Duration::from_secs(5),
+            |op| async move {
+                if op.retries == 0 {
+                    tokio::time::sleep(Duration::from_secs(60)).await;
+                }
+                RetryResult::<_, ()>::Ok(op.retries)
+            },
+        )
+        .await;
+        assert_eq!(res.ok(), Some(1));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn hedged() {
+        let start = tokio::time::Instant::now();
+        // the second attempt overtakes the first
+        let res = retry_hedged(
+            Constant::from_secs(10).take(3),
+            Duration::from_secs(1),
+            |op| async move {
+                let secs = if op.retries == 0 { 60 } else { 2 };
+                tokio::time::sleep(Duration::from_secs(secs)).await;
+                RetryResult::<_, ()>::Ok(op.retries)
+            },
+        )
+        .await;
+        assert_eq!(res.ok(), Some(1));
+        assert_eq!(start.elapsed(), Duration::from_secs(3));
+        // the slow first attempt is kept and still wins
+        let res = retry_hedged(
+            Constant::from_secs(10).take(3),
+            Duration::from_secs(1),
+            |op| async move {
+                let secs = if op.retries == 0 { 2 } else { 60 };
+                tokio::time::sleep(Duration::from_secs(secs)).await;
+                RetryResult::<_, ()>::Ok(op.retries)
+            },
+        )
+        .await;
+        assert_eq!(res.ok(), Some(0));
+        // a failure while others run doesn't push back the soft deadline
+        let start = tokio::time::Instant::now();
+        let res = retry_hedged(
+            Constant::from_secs(10).take(3),
+            Duration::from_secs(1),
+            |op| async move {
+                match op.retries {
+                    0 => tokio::time::sleep(Duration::from_secs(60)).await,
+                    1 => {
+                        tokio::time::sleep(Duration::from_millis(500)).await;
+                        return RetryResult::Retry();
+                    }
+                    _ => {}
+                }
+                RetryResult::<_, ()>::Ok(op.retries)
+            },
+        )
+        .await;
+        assert_eq!(res.ok(), Some(2));
+        assert_eq!(start.elapsed(), Duration::from_secs(2));
+        // every attempt fails
+        let res = retry_hedged(Immediate.take(2), Duration::from_secs(1), |op| async move {
+            RetryResult::<(), _>::RetryWith(op.retries)
+        })
+        .await;
+        assert!(matches!(
+            res,
+            Err(RetryErr::FailedAttempt {
+                tries: 2,
+                err: 2,
+                ..
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn deadline() {
+        let start = crate::Instant::now();
+        let deadline = start + Duration::from_millis(50);
+        let res = retry_with_deadline(Constant::from_millis(20), deadline, |_| async {
+            RetryResult::<(), &str>::Retry()
+        })
+        .await;
+        assert!(matches!(res, Err(RetryErr::DeadlineExceeded { .. })));
+        assert!(start.elapsed() < Duration::from_secs(1));
+        // an attempt hanging past the deadline is dropped
+        let deadline = crate::Instant::now() + Duration::from_millis(50);
+        let res = retry_with_deadline(Constant::from_millis(10), deadline, |_| async {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            RetryResult::<(), &str>::Ok(())
+        })
+        .await;
+        assert!(matches!(
+            res,
+            Err(RetryErr::DeadlineExceeded { tries: 0, .. })
+        ));
+    }
+
+    #[cfg(feature = "tokio-util")]
+    #[tokio::test(start_paused = true)]
+    async fn cancelled_during_delay() {
+        let token = tokio_util::sync::CancellationToken::new();
+        let shutdown = token.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            shutdown.cancel();
+        });
+        let start = tokio::time::Instant::now();
+        let res = retry_cancellable(&token, Constant::from_secs(60), |_| async {
+            RetryResult::<(), &str>::Retry()
+        })
+        .await;
+        assert!(matches!(
+            res,
+            Err(RetryErr::Cancelled { tries: 0, total_delay }) if total_delay == Duration::from_millis(0)
+        ));
+        assert!(start.elapsed() < Duration::from_secs(60));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn until_cancelled() {
+        let (tx, rx) = tokio::sync::oneshot::channel::<()>();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(150)).await;
+            drop(tx);
+        });
+        let res = retry_until(Constant::from_millis(100), rx, |_| async {
+            RetryResult::<(), &str>::Retry()
+        })
+        .await;
+        assert!(matches!(
+            res,
+            Err(RetryErr::Cancelled { tries: 1, total_delay }) if total_delay == Duration::from_millis(100)
+        ));
+        // an attempt running when cancelled is dropped
+        let res = retry_until(Constant::from_millis(100), async {}, |_| async {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            RetryResult::<(), &str>::Ok(())
+        })
+        .await;
+        assert!(matches!(res, Err(RetryErr::Cancelled { tries: 0, .. })));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn nameable_future() {
+        use std::future::{ready, Ready};
+
+        type Attempt = Ready<RetryResult<usize, ()>>;
+        type AttemptFn = fn(RetryOp) -> Attempt;
+
+        fn attempt(op: RetryOp) -> Attempt {
+            ready(if op.retries >= 2 {
+                RetryResult::Ok(op.retries)
+            } else {
+                RetryResult::Retry()
+            })
+        }
+
+        struct Pending {
+            fut: RetryFuture<Timer, std::iter::Take<Constant>, AttemptFn, Attempt>,
+        }
+
+        let pending = Pending {
+            fut: retry(Constant::from_millis(10).take(5), attempt as AttemptFn),
+        };
+        assert_eq!(pending.fut.await.ok(), Some(2));
+    }
+
+    #[cfg(feature = "stream")]
+    #[tokio::test(start_paused = true)]
+    async fn attempt_stream() {
+        use crate::Attempt;
+        use futures::StreamExt;
+
+        let attempts = retry_stream(Constant::from_millis(10).take(2), |op| async move {
+            RetryResult::<(), _>::RetryWith(op.retries)
+        })
+        .collect::<Vec<_>>()
+        .await;
+        assert!(matches!(
+            attempts.as_slice(),
+            [
+                Attempt::Retry { op, delay, err: Some(0) },
+                Attempt::Err(RetryErr::FailedAttempt { tries: 1, err: 1, .. }),
+            ] if op.retries == 0 && *delay == Duration::from_millis(10)
+        ));
+    }
+
+    #[cfg(feature = "stream")]
+    #[tokio::test(start_paused = true)]
+    async fn resumes_stream() {
+        use futures::{stream, StreamExt};
+
+        let mut subscribed = Vec::new();
+        let items = resume_stream(
+            Constant::from_millis(10).take(1),
+            |n: &u32| Some(*n),
+            |last: Option<&u32>| {
+                subscribed.push(last.copied());
+                let from = last.map_or(0, |n| n + 1);
+                // fails after two items each time
+                stream::iter(vec![Ok(from), Ok(from + 1), Err("reset")])
+            },
+        )
+        .take(7)
+        .collect::<Vec<_>>()
+        .await;
+        assert_eq!(
+            items
+                .iter()
+                .filter_map(|res| res.as_ref().ok())
+                .collect::<Vec<_>>(),
+            vec![&0, &1, &2, &3, &4, &5, &6]
+        );
+        assert_eq!(subscribed, vec![None, Some(1), Some(3), Some(5)]);
+
+        let items = resume_stream(
+            Constant::from_millis(10).take(2),
+            |_: &()| None::<()>,
+            |_| stream::iter(vec![Err("down")]),
+        )
+        .collect::<Vec<_>>()
+        .await;
+        assert!(matches!(
+            items.as_slice(),
+            [Err(RetryErr::FailedAttempt {
+                tries: 2,
+                err: "down",
+                ..
+            })]
+        ));
+    }
+
+    #[cfg(feature = "sink")]
+    #[tokio::test(start_paused = true)]
+    async fn replays_into_new_sink() {
+        use futures::{Sink, SinkExt};
+        use std::{
+            pin::Pin,
+            task::{Context, Poll},
+        };
+
+        /// a sink whose flush fails `fails` times in total
+        struct Flaky {
+            fails: Arc<Mutex<usize>>,
+            out: Arc<Mutex<Vec<u32>>>,
+            buf: Vec<u32>,
+        }
+
+        impl Sink<u32> for Flaky {
+            type Error = &'static str;
+
+            fn poll_ready(
+                self: Pin<&mut Self>,
+                _: &mut Context<'_>,
+            ) -> Poll<Result<(), Self::Error>> {
+                Poll::Ready(Ok(()))
+            }
+
+            fn start_send(mut self: Pin<&mut Self>, item: u32) -> Result<(), Self::Error> {
+                self.buf.push(item);
+                Ok(())
+            }
+
+            fn poll_flush(
+                mut self: Pin<&mut Self>,
+                _: &mut Context<'_>,
+            ) -> Poll<Result<(), Self::Error>> {
+                let mut fails = self.fails.lock().unwrap();
+                if *fails > 0 {
+                    *fails -= 1;
+                    return Poll::Ready(Err("broken pipe"));
+                }
+                drop(fails);
+                let buf = std::mem::take(&mut self.buf);
+                self.out.lock().unwrap().extend(buf);
+                Poll::Ready(Ok(()))
+            }
+
+            fn poll_close(
+                self: Pin<&mut Self>,
+                cx: &mut Context<'_>,
+            ) -> Poll<Result<(), Self::Error>> {
+                self.poll_flush(cx)
+            }
+        }
+
+        let fails = Arc::new(Mutex::new(2));
+        let out = Arc::new(Mutex::new(Vec::new()));
+        let mut connects = 0;
+        let sink = retry_sink(Constant::from_millis(10).take(2), || {
+            connects += 1;
+            Flaky {
+                fails: fails.clone(),
+                out: out.clone(),
+                buf: Vec::new(),
+            }
+        });
+        futures::pin_mut!(sink);
+        sink.send(1).await.unwrap();
+        sink.send(2).await.unwrap();
+        sink.close().await.unwrap();
+        assert_eq!(*out.lock().unwrap(), vec![1, 2]);
+        assert_eq!(connects, 3);
+
+        *fails.lock().unwrap() = 5;
+        let sink = retry_sink(Constant::from_millis(10).take(2), || Flaky {
+            fails: fails.clone(),
+            out: out.clone(),
+            buf: Vec::new(),
+        });
+        futures::pin_mut!(sink);
+        assert!(matches!(
+            sink.send(3).await,
+            Err(RetryErr::FailedAttempt {
+                tries: 2,
+                err: "broken pipe",
+                ..
+            })
+        ));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn hedge_first_success() {
+        let start = tokio::time::Instant::now();
+        // the first attempt is slow, the hedge after 10ms answers first
+        let res = hedge(Constant::from_millis(10).take(2), |op| async move {
+            let took = if op.retries == 0 { 100 } else { 5 };
+            tokio::time::sleep(Duration::from_millis(took)).await;
+            RetryResult::<_, ()>::Ok(op.retries)
+        })
+        .await;
+        assert_eq!(res.ok(), Some(1));
+        assert_eq!(start.elapsed(), Duration::from_millis(15));
+
+        // failures start the next attempt without waiting
+        let start = tokio::time::Instant::now();
+        let res = hedge(Constant::from_secs(1).take(2), |op| async move {
+            RetryResult::<(), _>::RetryWith(op.retries)
+        })
+        .await;
+        assert!(matches!(
+            res,
+            Err(RetryErr::FailedAttempt { tries: 2, err: 2, total_delay }) if total_delay == Duration::from_millis(0)
+        ));
+        assert_eq!(start.elapsed(), Duration::from_millis(0));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn race() {
+        // only the last replica answers, after the others fail
+        let res = tokio::spawn(retry_race(3, Constant::from_millis(10), |op| async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            if op.retries == 2 {
+                RetryResult::Ok("found")
+            } else {
+                RetryResult::RetryWith(op.retries)
+            }
+        }))
+        .await
+        .unwrap();
+        assert_eq!(res.ok(), Some("found"));
+
+        let res = retry_race(3, Constant::from_millis(10), |op| async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            RetryResult::<(), _>::RetryWith(op.retries)
+        })
+        .await;
+        assert!(
+            matches!(res, Err(RetryErr::FailedAttempt { tries: 2, err, .. }) if err == vec![0, 1, 2])
+        );
+    }
+
+    #[tokio::test]
+    async fn budget_exhausted() {
+        let budget = Arc::new(RetryBudget::new(Duration::from_secs(60), 0, 1.0));
+        let res = retry_with_budget(&budget, Constant::from_millis(10), |_| async {
+            RetryResult::<(), &str>::Retry()
+        })
+        .await;
+        assert!(matches!(
+            res,
+            Err(RetryErr::BudgetExhausted { tries: 1, .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn strategy_uses_err() {
+        let res = retry_strategy(Constant::from_millis(10).take(3), |op| async move {
+            if op.retries >= 2 {
+                RetryResult::<usize, &str>::Ok(op.retries)
+            } else {
+                RetryResult::RetryWith("busy")
+            }
+        })
+        .await;
+        assert_eq!(res.ok(), Some(2));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn retry_if_predicate() {
+        let res = retry_if(
+            Constant::from_secs(1).take(3),
+            |op| async move { Err::<(), _>(op.retries) },
+            |_| true,
+        )
+        .await;
+        assert!(matches!(
+            res,
+            Err(RetryErr::FailedAttempt {
+                tries: 2,
+                err: 2,
+                ..
+            })
+        ));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn transient_errors() {
+        let res = retry_transient(Constant::from_secs(1), |op| async move {
+            if op.retries < 2 {
+                Err::<(), _>(io::Error::from(io::ErrorKind::TimedOut))
+            } else {
+                Err(io::Error::from(io::ErrorKind::NotFound))
+            }
+        })
+        .await;
+        assert!(matches!(
+            res,
+            Err(RetryErr::FailedAttempt { tries: 2, err, .. }) if err.kind() == io::ErrorKind::NotFound
+        ));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn result_passes_eventually() {
+        let res = retry_result(Constant::from_secs(1).take(5), |op| async move {
+            if op.retries < 3 {
+                Err("failed")
+            } else {
+                Ok(op.total_delay)
+            }
+        })
+        .await;
+        assert_eq!(res.ok(), Some(Duration::from_secs(3)));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn try_retry_in_future() {
+        async fn click(retries: usize) -> io::Result<usize> {
+            if retries < 2 {
+                Err(io::Error::from(io::ErrorKind::TimedOut))
+            } else {
+                Ok(retries)
+            }
+        }
+        let res = retry_strategy(Constant::from_secs(1).take(5), |op| async move {
+            let n = crate::try_retry!(click(op.retries).await);
+            RetryResult::<_, io::Error>::Ok(n)
+        })
+        .await;
+        assert_eq!(res.ok(), Some(2));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn control_flow() {
+        let res = retry_flow(Constant::from_secs(1), |op| async move {
+            if op.retries < 2 {
+                std::ops::ControlFlow::Continue(())
+            } else {
+                std::ops::ControlFlow::Break(Ok::<_, &str>(op.total_delay))
+            }
+        })
+        .await;
+        assert_eq!(res.ok(), Some(Duration::from_secs(2)));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn times_limits_attempts() {
+        let res = retry_times(Constant::from_secs(1), 3, |_| async {
+            RetryResult::<(), &str>::Retry()
+        })
+        .await;
+        assert!(matches!(res, Err(RetryErr::IteratorEnded { tries: 2, .. })));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn stats_on_success() {
+        let (val, stats) = retry_with_stats(Constant::from_secs(1), |op| async move {
+            if op.retries >= 2 {
+                RetryResult::<_, &str>::Ok(op.retries)
+            } else {
+                RetryResult::Retry()
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(val, 2);
+        assert_eq!(stats.attempts, 3);
+        assert_eq!(stats.total_delay, Duration::from_secs(2));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn wait_for_condition() {
+        let mut polls = 0;
+        let res = wait_until(Constant::from_secs(1).take(5), || {
+            polls += 1;
+            let ready = polls == 3;
+            async move { ready }
+        })
+        .await;
+        assert!(res.is_ok());
+        assert_eq!(polls, 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn poll_for_value() {
+        let mut polls = 0;
+        let res = poll_until(Constant::from_secs(1).take(5), || {
+            polls += 1;
+            let val = if polls == 3 { Some(polls) } else { None };
+            async move { val }
+        })
+        .await;
+        assert_eq!(res.ok(), Some(3));
+    }
+
+    #[cfg(feature = "tokio-net")]
+    #[tokio::test]
+    async fn connects() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let res = connect_with_retry(addr, Constant::from_millis(1).take(2)).await;
+        assert!(res.is_ok());
+    }
+
+    #[cfg(feature = "tokio-spawn")]
+    #[tokio::test(start_paused = true)]
+    async fn spawned() {
+        let handle = spawn_retry(Constant::from_secs(1).take(3), |op| async move {
+            if op.retries >= 2 {
+                RetryResult::<_, &str>::Ok(op.retries)
+            } else {
+                RetryResult::Retry()
+            }
+        });
+        assert_eq!(handle.await.ok(), Some(2));
+
+        let handle = spawn_retry(Constant::from_secs(1), |_| async {
+            RetryResult::<(), &str>::Retry()
+        });
+        tokio::time::sleep(Duration::from_millis(2500)).await;
+        assert_eq!(handle.attempts(), 3);
+        handle.abort();
+        assert!(matches!(
+            handle.await,
+            Err(RetryErr::Cancelled { tries: 2, total_delay }) if total_delay == Duration::from_secs(2)
+        ));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn breaker() {
+        let breaker = Arc::new(crate::circuit::CircuitBreaker::new(
+            1,
+            Duration::from_secs(60),
+        ));
+        // spawned, so the future must stay `Send`
+        let res = tokio::spawn(async move {
+            retry_with_breaker(&breaker, Constant::from_secs(1).take(4), |_| async {
+                RetryResult::<(), _>::RetryWith("down")
+            })
+            .await
+        })
+        .await
+        .unwrap();
+        assert!(matches!(res, Err(RetryErr::CircuitOpen { tries: 3, .. })));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn bulkhead() {
+        let bulkhead = Arc::new(crate::Bulkhead::new(1));
+        let res = tokio::spawn(async move {
+            retry_with_bulkhead(&bulkhead, Constant::from_secs(1).take(2), |op| {
+                let available = bulkhead.available();
+                async move {
+                    assert_eq!(available, 0);
+                    RetryResult::<_, ()>::Ok(op.retries)
+                }
+            })
+            .await
+        })
+        .await
+        .unwrap();
+        assert_eq!(res.ok(), Some(0));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn fixed_rate() {
+        let start = tokio::time::Instant::now();
+        let mut starts = Vec::new();
+        let mut last = None;
+        let res = retry_fixed_rate(Constant::from_secs(1).take(4), |op| {
+            starts.push(start.elapsed());
+            last = Some(op);
+            async move {
+                // slow attempts don't push the next ones later
+                tokio::time::sleep(Duration::from_millis(300)).await;
+                if op.retries >= 3 {
+                    RetryResult::<_, ()>::Ok(op.retries)
+                } else {
+                    RetryResult::Retry()
+                }
+            }
+        })
+        .await;
+        assert_eq!(res.ok(), Some(3));
+        // measured on tokio's paused clock, each wait is what's left of 1s
+        let last = last.unwrap();
+        assert_eq!(last.total_delay, Duration::from_millis(2100));
+        assert_eq!(last.total_elapsed, Duration::from_secs(3));
+        assert_eq!(last.last_attempt, Duration::from_millis(300));
+        for (n, at) in starts.into_iter().enumerate() {
+            let want = Duration::from_secs(n as u64);
+            assert!(
+                at >= want && at < want + Duration::from_millis(100),
+                "{:?}",
+                at
+            );
+        }
+    }
 }
@@ -0,0 +1,220 @@
+//! tower middleware
+//!
+//! Enable the `tower` feature to retry the calls of any `tower::Service`
+//! under a [`RetryPolicy`]: its strategy, which errors to retry and its
+//! budget. The request is cloned for every attempt, and each retry waits
+//! for a clone of the inner service to be ready. Add the `RetryLayer` to a
+//! `ServiceBuilder`, or wrap a client directly with `RetryService::new`
+//!
+//! ```rust,no_run
+//! # use std::{io, sync::Arc, time::Duration};
+//! use retry_fn::{strategy::ExponentialBackoff, tower::RetryLayer, RetryBudget, RetryPolicy};
+//! # fn main() {
+//! let policy = RetryPolicy::new(|| ExponentialBackoff::from_millis(50).take(3))
+//!     .classify(|e: &io::Error| e.kind() == io::ErrorKind::ConnectionReset)
+//!     .budget(Arc::new(RetryBudget::new(Duration::from_secs(10), 10, 0.2)));
+//! let layer = RetryLayer::new(policy);
+//! # let _ = layer;
+//! # }
+//! ```
+use crate::{RetryErr, RetryPolicy};
+use std::{
+    fmt,
+    future::{poll_fn, Future},
+    mem,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// A `Layer` wrapping services in a [`RetryService`]
+pub struct RetryLayer<S, E> {
+    policy: RetryPolicy<S, E>,
+}
+
+impl<S, E> RetryLayer<S, E> {
+    /// Create a new `RetryLayer` retrying under `policy`
+    pub fn new(policy: RetryPolicy<S, E>) -> Self {
+        Self { policy }
+    }
+}
+
+impl<S, E> Clone for RetryLayer<S, E> {
+    fn clone(&self) -> Self {
+        Self {
+            policy: self.policy.clone(),
+        }
+    }
+}
+
+impl<S, E> fmt::Debug for RetryLayer<S, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RetryLayer")
+            .field("policy", &self.policy)
+            .finish()
+    }
+}
+
+impl<Svc, S, E> Layer<Svc> for RetryLayer<S, E> {
+    type Service = RetryService<Svc, S, E>;
+
+    fn layer(&self, inner: Svc) -> Self::Service {
+        RetryService {
+            inner,
+            policy: self.policy.clone(),
+        }
+    }
+}
+
+/// A `Service` retrying the calls of `Svc` under a [`RetryPolicy`], see the
+/// [module docs](self)
+pub struct RetryService<Svc, S, E> {
+    inner: Svc,
+    policy: RetryPolicy<S, E>,
+}
+
+impl<Svc, S, E> RetryService<Svc, S, E> {
+    /// Wrap `inner`, retrying its calls under `policy`
+    pub fn new(inner: Svc, policy: RetryPolicy<S, E>) -> Self {
+        Self { inner, policy }
+    }
+}
+
+impl<Svc, S, E> Clone for RetryService<Svc, S, E>
+where
+    Svc: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            policy: self.policy.clone(),
+        }
+    }
+}
+
+impl<Svc, S, E> fmt::Debug for RetryService<Svc, S, E>
+where
+    Svc: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RetryService")
+            .field("inner", &self.inner)
+            .field("policy", &self.policy)
+            .finish()
+    }
+}
+
+impl<Svc, Req, S, E> Service<Req> for RetryService<Svc, S, E>
+where
+    Svc: Service<Req, Error = E> + Clone + Send + 'static,
+    Svc::Response: Send,
+    Svc::Future: Send,
+    Req: Clone + Send + 'static,
+    S: IntoIterator<Item = Duration> + 'static,
+    S::IntoIter: Send,
+    E: Send + 'static,
+{
+    type Response = Svc::Response;
+    type Error = RetryErr<E>;
+    type Future = Pin<Box<dyn Future<Output = Result<Svc::Response, RetryErr<E>>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner
+            .poll_ready(cx)
+            .map_err(|err| RetryErr::FailedAttempt {
+                tries: 0,
+                total_delay: Duration::from_millis(0),
+                err,
+            })
+    }
+
+    fn call(&mut self, req: Req) -> Self::Future {
+        // the service polled ready runs the first attempt, retries wait for
+        // a clone to be ready
+        let fresh = self.inner.clone();
+        let mut ready = Some(mem::replace(&mut self.inner, fresh.clone()));
+        let policy = self.policy.clone();
+        Box::pin(async move {
+            policy
+                .retry_async(move |_| {
+                    let (mut svc, is_ready) = match ready.take() {
+                        Some(svc) => (svc, true),
+                        None => (fresh.clone(), false),
+                    };
+                    let req = req.clone();
+                    async move {
+                        if !is_ready {
+                            poll_fn(|cx| svc.poll_ready(cx)).await?;
+                        }
+                        svc.call(req).await
+                    }
+                })
+                .await
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{strategy::Constant, RetryBudget};
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    /// fails the first `fails` calls
+    #[derive(Clone)]
+    struct Flaky {
+        calls: Arc<AtomicUsize>,
+        fails: usize,
+    }
+
+    impl Service<u32> for Flaky {
+        type Response = u32;
+        type Error = &'static str;
+        type Future = std::future::Ready<Result<u32, &'static str>>;
+
+        fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, req: u32) -> Self::Future {
+            let n = self.calls.fetch_add(1, Ordering::SeqCst);
+            std::future::ready(if n < self.fails {
+                Err("busy")
+            } else {
+                Ok(req * 2)
+            })
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn retries_service() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let policy = RetryPolicy::new(|| Constant::from_millis(10).take(3));
+        let mut svc = RetryLayer::new(policy.clone()).layer(Flaky {
+            calls: calls.clone(),
+            fails: 2,
+        });
+        poll_fn(|cx| svc.poll_ready(cx)).await.unwrap();
+        assert_eq!(svc.call(21).await.ok(), Some(42));
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        let budget = Arc::new(RetryBudget::new(Duration::from_secs(10), 0, 0.0));
+        let mut svc = RetryService::new(
+            Flaky {
+                calls: Arc::new(AtomicUsize::new(0)),
+                fails: 2,
+            },
+            policy.budget(budget),
+        );
+        poll_fn(|cx| svc.poll_ready(cx)).await.unwrap();
+        assert!(matches!(
+            svc.call(21).await,
+            Err(RetryErr::BudgetExhausted { tries: 0, .. })
+        ));
+    }
+}
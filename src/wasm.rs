@@ -0,0 +1,39 @@
+//! retry impls for the browser
+//!
+//! Enable the `wasm` feature to get access to these functions on
+//! `wasm32-unknown-unknown`. They wait with the browser's `setTimeout` through
+//! gloo-timers, and the feature also switches time measurements to a clock
+//! that works in the browser. The blocking functions at the crate root can't
+//! be used there, since the main thread can't sleep
+//!
+//! ```rust,no_run
+//! use retry_fn::{strategy::ExponentialBackoff, wasm::retry, RetryResult};
+//! # async fn fetch_status() -> Result<u16, ()> { Ok(200) }
+//! # async fn run() {
+//! let res = retry(ExponentialBackoff::from_millis(100).take(5), |_| async {
+//!     match fetch_status().await {
+//!         Ok(status) if status < 500 => RetryResult::Ok(status),
+//!         Ok(status) => RetryResult::RetryWith(status),
+//!         Err(()) => RetryResult::Retry(),
+//!     }
+//! })
+//! .await;
+//! # }
+//! ```
+use gloo_timers::future::TimeoutFuture;
+use std::convert::TryFrom;
+
+/// the browser's timer
+#[derive(Debug, Clone, Copy)]
+pub struct Timer;
+
+impl crate::runtime::Sleeper for Timer {
+    type Sleep = TimeoutFuture;
+
+    fn sleep(dur: Duration) -> Self::Sleep {
+        // `setTimeout` takes a u32 of milliseconds
+        TimeoutFuture::new(u32::try_from(dur.as_millis()).unwrap_or(u32::MAX))
+    }
+}
+
+retry_impl!(Timer);
@@ -0,0 +1,42 @@
+#![cfg(feature = "macros")]
+use retry_fn::attr::retry;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+#[retry(strategy = "constant:1ms", max_attempts = 3)]
+fn flaky(label: &str, mut n: usize) -> Result<String, String> {
+    n += CALLS.fetch_add(1, Ordering::SeqCst);
+    if n < 2 {
+        Err(format!("{} busy", label))
+    } else {
+        Ok(format!("{} {}", label, n))
+    }
+}
+
+#[retry(strategy = "constant(1ms, retries=2)")]
+fn always_fails(n: u32) -> Result<(), u32> {
+    Err(n)
+}
+
+#[test]
+fn retries_until_ok() {
+    assert_eq!(flaky("job", 0), Ok("job 2".to_owned()));
+    assert_eq!(CALLS.load(Ordering::SeqCst), 3);
+    assert_eq!(always_fails(7), Err(7));
+}
+
+#[cfg(feature = "tokio-runtime")]
+#[retry(strategy = "constant:1s", max_attempts = 2)]
+async fn fetch(url: String) -> Result<usize, String> {
+    Err(url)
+}
+
+#[cfg(feature = "tokio-runtime")]
+#[tokio::test(start_paused = true)]
+async fn retries_async() {
+    assert_eq!(
+        fetch("http://example.com".to_owned()).await,
+        Err("http://example.com".to_owned())
+    );
+}